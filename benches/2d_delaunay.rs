@@ -3,18 +3,18 @@
 
 #![allow(missing_docs)]
 use bevy::prelude::*;
-use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use rand::{SeedableRng, seq::IteratorRandom};
 use rand_chacha::ChaCha20Rng;
-use std::hint::black_box;
+use std::{collections::BTreeSet, hint::black_box};
 use voronoi_mosaic::prelude::*;
 
 /// Create the required data before benchmarking
-fn prepare_data() -> Vec<Vec2> {
+fn prepare_data(point_count: usize) -> Vec<Vec2> {
 	let mut rng_seed = ChaCha20Rng::seed_from_u64(123456789);
 
 	let mut points = vec![];
-	let point_count = 1000;
+	let mut seen = BTreeSet::new();
 	while points.len() < point_count {
 		let x_range = std::ops::Range {
 			start: 0,
@@ -26,25 +26,62 @@ fn prepare_data() -> Vec<Vec2> {
 		};
 		let x = x_range.choose(&mut rng_seed).unwrap();
 		let y = y_range.choose(&mut rng_seed).unwrap();
-		let point = Vec2::new(x as f32, y as f32);
-		if !points.contains(&point) {
-			points.push(point);
+		if seen.insert((x, y)) {
+			points.push(Vec2::new(x as f32, y as f32));
 		}
 	}
 	points
 }
 
 /// Call the code to benchmark
-fn init(points: &mut Vec<Vec2>) {
+fn init(points: &[Vec2]) {
 	let _data = Delaunay2d::compute_triangulation_2d(points);
 }
 /// Benchmark
 pub fn criterion_benchmark(c: &mut Criterion) {
-	let mut data = prepare_data();
+	let data = prepare_data(1000);
 	let mut group = c.benchmark_group("2d");
 	group.significance_level(0.1).sample_size(100);
 	group.throughput(Throughput::Bytes(data.len() as u64));
-	group.bench_function("2d_delaunay", |b| b.iter(|| init(black_box(&mut data))));
+	group.bench_function("2d_delaunay", |b| b.iter(|| init(black_box(&data))));
+	group.finish();
+
+	let mut group = c.benchmark_group("2d_large");
+	group.significance_level(0.1).sample_size(10);
+	for point_count in [10_000, 100_000, 250_000] {
+		let data = prepare_data(point_count);
+		group.throughput(Throughput::Elements(data.len() as u64));
+		group.bench_with_input(
+			BenchmarkId::new("2d_delaunay", point_count),
+			&data,
+			|b, data| b.iter(|| init(black_box(data))),
+		);
+	}
+	group.finish();
+
+	let mut group = c.benchmark_group("2d_queries");
+	group.significance_level(0.1).sample_size(100);
+	let delaunay = Delaunay2d::compute_triangulation_2d(&prepare_data(100_000)).unwrap();
+	// query positions are offset from the points so they are not vertices
+	let queries: Vec<Vec2> = prepare_data(1000)
+		.iter()
+		.map(|point| *point + Vec2::splat(0.5))
+		.collect();
+	group.throughput(Throughput::Elements(queries.len() as u64));
+	group.bench_function("2d_nearest_vertex", |b| {
+		b.iter(|| {
+			for query in black_box(&queries) {
+				let _ = delaunay.nearest_vertex(*query);
+			}
+		})
+	});
+	group.bench_function("2d_k_nearest_vertices", |b| {
+		b.iter(|| {
+			for query in black_box(&queries) {
+				let _ = delaunay.k_nearest_vertices(*query, 8);
+			}
+		})
+	});
 	group.finish();
 }
 
@@ -3,18 +3,18 @@
 
 #![allow(missing_docs)]
 use bevy::prelude::*;
-use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use rand::{SeedableRng, seq::IteratorRandom};
 use rand_chacha::ChaCha20Rng;
-use std::hint::black_box;
+use std::{collections::BTreeSet, hint::black_box};
 use voronoi_mosaic::prelude::*;
 
 /// Create the required data before benchmarking
-fn prepare_data() -> Delaunay2d {
+fn prepare_data(point_count: usize) -> Delaunay2d {
 	let mut rng_seed = ChaCha20Rng::seed_from_u64(123456789);
 
 	let mut points = vec![];
-	let point_count = 1000;
+	let mut seen = BTreeSet::new();
 	while points.len() < point_count {
 		let x_range = std::ops::Range {
 			start: 0,
@@ -26,9 +26,8 @@ fn prepare_data() -> Delaunay2d {
 		};
 		let x = x_range.choose(&mut rng_seed).unwrap();
 		let y = y_range.choose(&mut rng_seed).unwrap();
-		let point = Vec2::new(x as f32, y as f32);
-		if !points.contains(&point) {
-			points.push(point);
+		if seen.insert((x, y)) {
+			points.push(Vec2::new(x as f32, y as f32));
 		}
 	}
 	let data = Delaunay2d::compute_triangulation_2d(&points);
@@ -41,12 +40,25 @@ fn init(delaunay: &Delaunay2d) {
 }
 /// Benchmark
 pub fn criterion_benchmark(c: &mut Criterion) {
-	let data = prepare_data();
+	let data = prepare_data(1000);
 	let mut group = c.benchmark_group("2d");
 	group.significance_level(0.1).sample_size(100);
 	group.throughput(Throughput::Bytes(data.get_triangles().len() as u64));
 	group.bench_function("2d_voronoi", |b| b.iter(|| init(black_box(&data))));
 	group.finish();
+
+	let mut group = c.benchmark_group("2d_large");
+	group.significance_level(0.1).sample_size(10);
+	for point_count in [10_000, 100_000, 250_000] {
+		let data = prepare_data(point_count);
+		group.throughput(Throughput::Elements(data.get_triangles().len() as u64));
+		group.bench_with_input(
+			BenchmarkId::new("2d_voronoi", point_count),
+			&data,
+			|b, data| b.iter(|| init(black_box(data))),
+		);
+	}
+	group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
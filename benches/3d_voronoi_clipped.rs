@@ -53,7 +53,7 @@ fn init(voronoi: &mut Voronoi3d) {
 		Vec3::new(10000.0, 10000.0, 10000.0),
 		Vec3::new(0.0, 10000.0, 10000.0),
 	];
-	voronoi.as_clipped_bevy3d_meshes(&boundary);
+	let _m = voronoi.as_clipped_bevy3d_meshes(&boundary);
 }
 /// Benchmark
 pub fn criterion_benchmark(c: &mut Criterion) {
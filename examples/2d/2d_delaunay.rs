@@ -63,10 +63,11 @@ fn visuals(
 	// } else {
 	// 	warn!("Data computation failed");
 	// }
-	if let Some(delaunay) = Delaunay2d::compute_triangulation_2d(&points) {
-		create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, &delaunay);
-	} else {
-		warn!("Delaunay computation failed");
+	match Delaunay2d::compute_triangulation_2d(&points) {
+		Ok(delaunay) => {
+			create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, &delaunay);
+		}
+		Err(e) => warn!("Delaunay computation failed: {}", e),
 	}
 }
 
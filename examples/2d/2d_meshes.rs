@@ -118,16 +118,16 @@ fn visuals(
 		Vec2::new(399.0, 0.0),
 	];
 	// compute data
-	let mosaic = Mosaic2d::new(&points);
-	if let Some(delaunay) = mosaic.get_delaunay() {
-		create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, delaunay);
-		if let Some(voronoi) = mosaic.get_voronoi() {
+	match Mosaic2d::new(&points) {
+		Ok(mosaic) => {
+			let delaunay = mosaic.get_delaunay();
+			create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, delaunay);
+			let voronoi = mosaic.get_voronoi();
 			// add simple shapes to showcase what the data looks like
 			create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, voronoi);
 			create_mesh_visuals(&mut cmds, &mut meshes, &mut materials, voronoi);
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -213,7 +213,11 @@ fn create_voronoi_cell_visuals(
 				VoronoiLabel,
 				Visibility::Hidden,
 			));
-			// mark the edges
+			// mark the edges, an open cell does not join its last vertex
+			// back to its first
+			if cell.is_open() && i == cell.get_vertex_ids().len() - 1 {
+				continue;
+			}
 			let (v1, v0) = if i < cell.get_vertex_ids().len() - 1 {
 				(cell.get_vertex_ids()[i + 1], *vertex_id)
 			} else {
@@ -253,7 +257,13 @@ fn create_mesh_visuals(
 	materials: &mut ResMut<Assets<ColorMaterial>>,
 	voronoi: &Voronoi2d,
 ) {
-	let meshes = voronoi.as_bevy2d_meshes();
+	let meshes = match voronoi.as_bevy2d_meshes() {
+		Ok(meshes) => meshes,
+		Err(e) => {
+			warn!("Mesh generation failed: {}", e);
+			return;
+		}
+	};
 	for (i, (mesh, position)) in meshes.values().enumerate() {
 		// randomise mesh colour
 		let colour = Color::hsl(360. * i as f32 / meshes.len() as f32, 0.95, 0.7);
@@ -95,10 +95,11 @@ fn visuals(
 		}
 	}
 	// compute data
-	let mosaic = Mosaic2d::new(&points);
-	if let Some(delaunay) = mosaic.get_delaunay() {
-		create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, delaunay);
-		if let Some(voronoi) = mosaic.get_voronoi() {
+	match Mosaic2d::new(&points) {
+		Ok(mosaic) => {
+			let delaunay = mosaic.get_delaunay();
+			create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, delaunay);
+			let voronoi = mosaic.get_voronoi();
 			// create the voronoi markers before mutation so it can be
 			// seen with the actual meshes how they have been clipped
 			create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, voronoi);
@@ -111,8 +112,7 @@ fn visuals(
 			];
 			create_clipped_mesh_visuals(&mut cmds, &mut meshes, &mut materials, voronoi, &boundary);
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -198,7 +198,11 @@ fn create_voronoi_cell_visuals(
 				VoronoiLabel,
 				Visibility::Hidden,
 			));
-			// mark the edges
+			// mark the edges, an open cell does not join its last vertex
+			// back to its first
+			if cell.is_open() && i == cell.get_vertex_ids().len() - 1 {
+				continue;
+			}
 			let (v1, v0) = if i < cell.get_vertex_ids().len() - 1 {
 				(cell.get_vertex_ids()[i + 1], *vertex_id)
 			} else {
@@ -238,7 +242,13 @@ fn create_mesh_visuals(
 	materials: &mut ResMut<Assets<ColorMaterial>>,
 	voronoi: &Voronoi2d,
 ) {
-	let meshes = voronoi.as_bevy2d_meshes();
+	let meshes = match voronoi.as_bevy2d_meshes() {
+		Ok(meshes) => meshes,
+		Err(e) => {
+			warn!("Mesh generation failed: {}", e);
+			return;
+		}
+	};
 	for (i, (mesh, position)) in meshes.values().enumerate() {
 		// randomise mesh colour
 		let colour = Color::hsl(360. * i as f32 / meshes.len() as f32, 0.95, 0.7);
@@ -265,7 +275,13 @@ fn create_clipped_mesh_visuals(
 	voronoi: &Voronoi2d,
 	boundary: &[Vec2],
 ) {
-	let meshes = voronoi.as_clipped_bevy2d_meshes(boundary);
+	let meshes = match voronoi.as_clipped_bevy2d_meshes(boundary) {
+		Ok(meshes) => meshes,
+		Err(e) => {
+			warn!("Mesh generation failed: {}", e);
+			return;
+		}
+	};
 	for (i, (mesh, position)) in meshes.values().enumerate() {
 		// randomise mesh colour
 		let colour = Color::hsl(360. * i as f32 / meshes.len() as f32, 0.95, 0.7);
@@ -53,13 +53,14 @@ fn visuals(
 		Vec2::new(190.0, -60.0),
 	];
 	// compute data
-	if let Some(delaunay) = Delaunay2d::compute_triangulation_2d(&points) {
-		if let Some(voronoi) = Voronoi2d::from_delaunay_2d(&delaunay) {
-			// add simple shapes to showcase what the data looks like
-			create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, &voronoi);
+	match Delaunay2d::compute_triangulation_2d(&points) {
+		Ok(delaunay) => {
+			if let Ok(voronoi) = Voronoi2d::from_delaunay_2d(&delaunay) {
+				// add simple shapes to showcase what the data looks like
+				create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, &voronoi);
+			}
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -89,7 +90,11 @@ fn create_voronoi_cell_visuals(
 				VoronoiLabel,
 				Visibility::Visible,
 			));
-			// mark the edges
+			// mark the edges, an open cell does not join its last vertex
+			// back to its first
+			if cell.is_open() && i == cell.get_vertex_ids().len() - 1 {
+				continue;
+			}
 			let (v1, v0) = if i < cell.get_vertex_ids().len() - 1 {
 				(cell.get_vertex_ids()[i + 1], *vertex_id)
 			} else {
@@ -95,10 +95,11 @@ fn visuals(
 		Vec3::new(0.0, 0.0, 0.0),
 	];
 	// compute data
-	if let Some(delaunay) = mosaic_3d::delaunay::Delaunay3d::compute_triangulation_3d(&points) {
-		create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, &delaunay);
-	} else {
-		warn!("Data computation failed");
+	match mosaic_3d::delaunay::Delaunay3d::compute_triangulation_3d(&points) {
+		Ok(delaunay) => {
+			create_delaunay_visuals(&mut cmds, &mut meshes, &mut materials, &delaunay);
+		}
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -99,15 +99,15 @@ fn visuals(
 		Vec3::new(0.0, 0.0, 0.0),
 	];
 	// compute data
-	let mosaic = Mosaic3d::new(&points);
-	if let Some(delaunay) = mosaic.get_delaunay() {
-		create_delaunay_visuals(&mut cmds, &mut mesh_assets, &mut materials, delaunay);
-		if let Some(voronoi) = mosaic.get_voronoi() {
+	match Mosaic3d::new(&points) {
+		Ok(mosaic) => {
+			let delaunay = mosaic.get_delaunay();
+			create_delaunay_visuals(&mut cmds, &mut mesh_assets, &mut materials, delaunay);
+			let voronoi = mosaic.get_voronoi();
 			create_voronoi_cell_visuals(&mut cmds, &mut mesh_assets, &mut materials, voronoi);
 			create_mesh_visuals(&mut cmds, &mut mesh_assets, &mut materials, voronoi);
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -240,7 +240,13 @@ fn create_mesh_visuals(
 	materials: &mut ResMut<Assets<StandardMaterial>>,
 	voronoi: &Voronoi3d,
 ) {
-	let meshes = voronoi.as_bevy3d_meshes();
+	let meshes = match voronoi.as_bevy3d_meshes() {
+		Ok(meshes) => meshes,
+		Err(e) => {
+			warn!("Mesh generation failed: {}", e);
+			return;
+		}
+	};
 	for (i, (mesh, position)) in meshes.values().enumerate() {
 		// randomise mesh colour
 		let colour = Color::hsl(360. * i as f32 / meshes.len() as f32, 0.95, 0.7);
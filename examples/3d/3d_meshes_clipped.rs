@@ -99,10 +99,11 @@ fn visuals(
 		Vec3::new(0.0, 0.0, 0.0),
 	];
 	// compute data
-	let mosaic = Mosaic3d::new(&points);
-	if let Some(delaunay) = mosaic.get_delaunay() {
-		create_delaunay_visuals(&mut cmds, &mut mesh_assets, &mut materials, delaunay);
-		if let Some(voronoi) = mosaic.get_voronoi() {
+	match Mosaic3d::new(&points) {
+		Ok(mosaic) => {
+			let delaunay = mosaic.get_delaunay();
+			create_delaunay_visuals(&mut cmds, &mut mesh_assets, &mut materials, delaunay);
+			let voronoi = mosaic.get_voronoi();
 			// show voronoi pre-clip
 			create_voronoi_cell_visuals(&mut cmds, &mut mesh_assets, &mut materials, voronoi);
 			//TODO proper boundary
@@ -115,8 +116,7 @@ fn visuals(
 				&boundary,
 			);
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -250,7 +250,13 @@ fn create_clipped_mesh_visuals(
 	voronoi: &Voronoi3d,
 	boundary: &[Vec3],
 ) {
-	let meshes = voronoi.as_clipped_bevy3d_meshes(boundary);
+	let meshes = match voronoi.as_clipped_bevy3d_meshes(boundary) {
+		Ok(meshes) => meshes,
+		Err(e) => {
+			warn!("Mesh generation failed: {}", e);
+			return;
+		}
+	};
 	for (i, (mesh, position)) in meshes.values().enumerate() {
 		// randomise mesh colour
 		let colour = Color::hsl(360. * i as f32 / meshes.len() as f32, 0.95, 0.7);
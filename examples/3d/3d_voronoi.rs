@@ -94,13 +94,14 @@ fn visuals(
 		Vec3::new(0.0, 0.0, 0.0),
 	];
 	// compute data
-	if let Some(delaunay) = Delaunay3d::compute_triangulation_3d(&points) {
-		if let Some(voronoi) = Voronoi3d::from_delaunay_3d(&delaunay) {
-			// add simple shapes to showcase what the data looks like
-			create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, &voronoi);
+	match Delaunay3d::compute_triangulation_3d(&points) {
+		Ok(delaunay) => {
+			if let Ok(voronoi) = Voronoi3d::from_delaunay_3d(&delaunay) {
+				// add simple shapes to showcase what the data looks like
+				create_voronoi_cell_visuals(&mut cmds, &mut meshes, &mut materials, &voronoi);
+			}
 		}
-	} else {
-		warn!("Data computation failed");
+		Err(e) => warn!("Data computation failed: {}", e),
 	}
 }
 
@@ -0,0 +1,171 @@
+//! Describes the ways in which a triangulation, tessellation or mesh
+//! generation can fail
+//!
+
+use std::fmt;
+
+/// The phases of generating a Delaunay-Voronoi dual
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MosaicStage {
+	/// Computing the Delaunay Triangulation/Tetrahedralization
+	Delaunay,
+	/// Computing the Voronoi Tessellation from the Delaunay
+	Voronoi,
+}
+
+impl fmt::Display for MosaicStage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MosaicStage::Delaunay => write!(f, "Delaunay"),
+			MosaicStage::Voronoi => write!(f, "Voronoi"),
+		}
+	}
+}
+
+/// Reasons why a triangulation, tessellation or set of meshes could not be
+/// generated
+#[derive(Debug, Clone, PartialEq)]
+pub enum MosaicError {
+	/// Fewer points were supplied than the minimum required
+	InsufficientPoints {
+		/// Minimum number of points needed
+		required: usize,
+		/// Number of points that were supplied
+		supplied: usize,
+	},
+	/// A point contains a coordinate that is `NaN` or infinite
+	NonFiniteCoordinate {
+		/// Index of the offending point in the supplied data set
+		index: usize,
+	},
+	/// Every point lies along a single line (or on top of each other) so no
+	/// triangle can be formed
+	CollinearPoints,
+	/// Every point lies on a single plane (or line) so no tetrahedron can be
+	/// formed
+	CoplanarPoints,
+	/// No triangles or tetrahedra survived the removal of the starting
+	/// super triangle/tetrahedra
+	EmptyTriangulation,
+	/// A point sits outside of a triangulation whose outline cannot be
+	/// extended to reach it
+	OutsideTriangulation {
+		/// Index of the offending point in the supplied data set
+		index: usize,
+	},
+	/// No Voronoi Cells could be formed from the Delaunay
+	EmptyTessellation,
+	/// A boundary polygon/polyhedron used for clipping is unusable
+	InvalidBoundary(&'static str),
+	/// A constrained segment cannot be added to the triangulation
+	InvalidConstraint(&'static str),
+	/// The bounds used to refine a triangulation are unusable
+	InvalidRefinement(&'static str),
+	/// A supplied triangulation cannot be made Delaunay
+	InvalidTriangulation(&'static str),
+	/// A vertex ID does not exist within the triangulation
+	UnknownVertex {
+		/// The ID that was requested
+		vertex_id: usize,
+	},
+	/// A change would leave a vertex that is not part of any triangle
+	UncoveredVertex {
+		/// The ID of the vertex left without a triangle
+		vertex_id: usize,
+	},
+	/// A change would leave a vertex joining triangles that share no edge
+	/// around it
+	PinchedVertex {
+		/// The ID of the vertex the triangles would meet at
+		vertex_id: usize,
+	},
+	/// A stage of generating a Delaunay-Voronoi dual failed
+	Stage {
+		/// The stage which failed
+		stage: MosaicStage,
+		/// Why it failed
+		source: Box<MosaicError>,
+	},
+}
+
+impl fmt::Display for MosaicError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MosaicError::InsufficientPoints { required, supplied } => write!(
+				f,
+				"Minimum of {} points required, supplied {} points",
+				required, supplied
+			),
+			MosaicError::NonFiniteCoordinate { index } => {
+				write!(f, "Point at index {} has a non-finite coordinate", index)
+			}
+			MosaicError::CollinearPoints => write!(f, "All points are collinear"),
+			MosaicError::CoplanarPoints => write!(f, "All points are coplanar"),
+			MosaicError::EmptyTriangulation => write!(f, "No triangulation found"),
+			MosaicError::OutsideTriangulation { index } => {
+				write!(
+					f,
+					"Point at index {} is outside of the triangulation",
+					index
+				)
+			}
+			MosaicError::EmptyTessellation => write!(f, "No Voronoi Cells found"),
+			MosaicError::InvalidBoundary(reason) => write!(f, "Invalid boundary: {}", reason),
+			MosaicError::InvalidConstraint(reason) => write!(f, "Invalid constraint: {}", reason),
+			MosaicError::InvalidRefinement(reason) => write!(f, "Invalid refinement: {}", reason),
+			MosaicError::InvalidTriangulation(reason) => {
+				write!(f, "Invalid triangulation: {}", reason)
+			}
+			MosaicError::UnknownVertex { vertex_id } => {
+				write!(f, "Vertex {} is not part of the triangulation", vertex_id)
+			}
+			MosaicError::UncoveredVertex { vertex_id } => {
+				write!(f, "Vertex {} would not be part of any triangle", vertex_id)
+			}
+			MosaicError::PinchedVertex { vertex_id } => {
+				write!(f, "Triangles would only meet at vertex {}", vertex_id)
+			}
+			MosaicError::Stage { stage, source } => {
+				write!(f, "{} stage failed: {}", stage, source)
+			}
+		}
+	}
+}
+
+impl std::error::Error for MosaicError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			MosaicError::Stage { source, .. } => Some(source.as_ref()),
+			_ => None,
+		}
+	}
+}
+
+impl MosaicError {
+	/// Wrap an error to record the [MosaicStage] it occured in
+	pub fn at_stage(self, stage: MosaicStage) -> Self {
+		MosaicError::Stage {
+			stage,
+			source: Box::new(self),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::error::Error;
+
+	#[test]
+	fn stage_source() {
+		let error = MosaicError::CollinearPoints.at_stage(MosaicStage::Delaunay);
+		assert_eq!(
+			MosaicError::CollinearPoints.to_string(),
+			error.source().unwrap().to_string()
+		);
+		assert_eq!(
+			"Delaunay stage failed: All points are collinear",
+			error.to_string()
+		);
+	}
+}
@@ -2,9 +2,13 @@
 //! via Delaunay Triangulation and Voronoi Tessellation
 //!
 
+pub mod error;
 pub mod prelude;
 pub mod utilities;
 
+#[cfg(any(feature = "2d", feature = "3d"))]
+pub mod quality;
+
 #[cfg(feature = "2d")]
 pub mod mosaic_2d;
 
@@ -0,0 +1,306 @@
+//! Outline a set of points more tightly than their convex hull with an alpha
+//! shape.
+//!
+//! Each triangle of a Delaunay Triangulation is kept if its circumradius is
+//! no larger than `alpha`. A large `alpha` keeps every triangle and the shape
+//! is the convex hull, as `alpha` shrinks the large triangles spanning gaps
+//! between the points are dropped and the outline shrinks into bays and
+//! holes until the shape breaks apart into separate pieces.
+//!
+//! The edges of kept triangles that do not border another kept triangle form
+//! the boundary. The boundary of each group of kept triangles joined by their
+//! edges is one polygon, an outer ring running anti-clockwise and any holes
+//! within it running clockwise.
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::math::DVec2;
+
+use crate::{mosaic_2d::delaunay::Delaunay2d, utilities::find_root};
+
+/// An outer ring and the holes within it, each ring is a list of vertex IDs
+/// where the last vertex joins back to the first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphaPolygon2d {
+	/// Vertex IDs around the outside, anti-clockwise
+	outer_ring: Vec<usize>,
+	/// Vertex IDs around each hole, clockwise
+	holes: Vec<Vec<usize>>,
+}
+
+impl AlphaPolygon2d {
+	/// Get the vertex IDs around the outside of the polygon in anti-clockwise
+	/// order
+	pub fn get_outer_ring(&self) -> &[usize] {
+		&self.outer_ring
+	}
+	/// Get the vertex IDs around each hole in clockwise order
+	pub fn get_holes(&self) -> &[Vec<usize>] {
+		&self.holes
+	}
+}
+
+/// The triangles kept by an alpha shape and the polygons outlining them, see
+/// [Delaunay2d::alpha_shape]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphaShape2d {
+	/// IDs of the kept triangles
+	triangles: Vec<usize>,
+	/// Outline of each group of triangles joined by their edges
+	polygons: Vec<AlphaPolygon2d>,
+}
+
+impl AlphaShape2d {
+	/// Get the IDs of the kept triangles
+	pub fn get_triangles(&self) -> &[usize] {
+		&self.triangles
+	}
+	/// Get the outline of each group of kept triangles joined by their edges
+	pub fn get_polygons(&self) -> &[AlphaPolygon2d] {
+		&self.polygons
+	}
+}
+
+impl Delaunay2d {
+	/// Compute the alpha shape of the triangulation, keeping each triangle
+	/// whose circumradius is no larger than `alpha`, see the module docs.
+	///
+	/// Polygons that only touch at a vertex are separate polygons. The shape
+	/// is empty if `alpha` is not positive or the triangulation is a
+	/// collinear chain
+	pub fn alpha_shape(&self, alpha: f32) -> AlphaShape2d {
+		let triangles: Vec<usize> = self
+			.get_triangles()
+			.keys()
+			.copied()
+			.filter(|tri_id| self.circumradius(*tri_id) <= alpha as f64)
+			.collect();
+		let kept: BTreeSet<usize> = triangles.iter().copied().collect();
+		let is_kept = |neighbour: Option<usize>| neighbour.is_some_and(|n| kept.contains(&n));
+		// label the groups of kept triangles joined by their edges
+		let mut groups = BTreeMap::new();
+		for tri_id in triangles.iter() {
+			if groups.contains_key(tri_id) {
+				continue;
+			}
+			let mut stack = vec![*tri_id];
+			groups.insert(*tri_id, *tri_id);
+			while let Some(current) = stack.pop() {
+				for n in self.triangle_neighbours(current).into_iter().flatten() {
+					if let Some(n) = n
+						&& kept.contains(n)
+						&& !groups.contains_key(n)
+					{
+						groups.insert(*n, *tri_id);
+						stack.push(*n);
+					}
+				}
+			}
+		}
+		// trace each ring of boundary edges, an edge is `(triangle ID, edge
+		// index)` with the kept triangle on its left
+		let mut rings: BTreeMap<usize, Vec<(f64, Vec<usize>)>> = BTreeMap::new();
+		let mut visited = BTreeSet::new();
+		for tri_id in triangles.iter() {
+			for i in 0..3 {
+				if is_kept(self.triangle_neighbours(*tri_id).unwrap()[i])
+					|| visited.contains(&(*tri_id, i))
+				{
+					continue;
+				}
+				let mut ring = vec![];
+				let mut current = (*tri_id, i);
+				while visited.insert(current) {
+					let ids = self.get_triangles()[&current.0].get_vertex_ids();
+					ring.push(ids[current.1]);
+					current = self.next_boundary_edge(current, &is_kept);
+				}
+				let positions: Vec<DVec2> = ring
+					.iter()
+					.map(|id| self.get_vertex_lookup()[id].as_dvec2())
+					.collect();
+				let twice_area: f64 = positions
+					.iter()
+					.zip(positions.iter().cycle().skip(1))
+					.map(|(a, b)| a.perp_dot(*b))
+					.sum();
+				rings
+					.entry(groups[tri_id])
+					.or_default()
+					.push((twice_area, ring));
+			}
+		}
+		let mut polygons = vec![];
+		for mut group_rings in rings.into_values() {
+			// the largest anti-clockwise ring surrounds the group
+			group_rings.sort_by(|a, b| b.0.total_cmp(&a.0));
+			let mut group_rings = group_rings.into_iter();
+			let Some((_, outer_ring)) = group_rings.next() else {
+				continue;
+			};
+			let mut polygon = AlphaPolygon2d {
+				outer_ring,
+				holes: vec![],
+			};
+			for (twice_area, ring) in group_rings {
+				if twice_area > 0.0 {
+					polygons.push(AlphaPolygon2d {
+						outer_ring: ring,
+						holes: vec![],
+					});
+				} else {
+					polygon.holes.push(ring);
+				}
+			}
+			polygons.push(polygon);
+		}
+		AlphaShape2d {
+			triangles,
+			polygons,
+		}
+	}
+	/// Find the smallest `alpha` whose alpha shape is a single polygon that
+	/// uses every vertex, its triangles all joined by their edges. Holes may
+	/// remain within it.
+	///
+	/// `None` if the triangulation is a collinear chain
+	pub fn find_connected_alpha(&self) -> Option<f32> {
+		let mut by_radius: Vec<(f64, usize)> = self
+			.get_triangles()
+			.keys()
+			.map(|tri_id| (self.circumradius(*tri_id), *tri_id))
+			.collect();
+		by_radius.sort_by(|a, b| a.0.total_cmp(&b.0));
+		// add triangles smallest first, merging each with the kept triangles
+		// beside it, until one group covers every vertex
+		let mut parents: BTreeMap<usize, usize> = BTreeMap::new();
+		let mut group_count = 0;
+		let mut covered: BTreeSet<usize> = BTreeSet::new();
+		for (index, (radius, tri_id)) in by_radius.iter().enumerate() {
+			parents.insert(*tri_id, *tri_id);
+			group_count += 1;
+			for n in self.triangle_neighbours(*tri_id).into_iter().flatten() {
+				if let Some(n) = n
+					&& parents.contains_key(n)
+				{
+					let (root, other) = (
+						find_root(&mut parents, *tri_id),
+						find_root(&mut parents, *n),
+					);
+					if root != other {
+						parents.insert(other, root);
+						group_count -= 1;
+					}
+				}
+			}
+			covered.extend(
+				self.get_triangles()[tri_id]
+					.get_vertex_ids()
+					.iter()
+					.copied(),
+			);
+			// triangles of equal radius are kept together
+			let is_last_of_radius = by_radius
+				.get(index + 1)
+				.is_none_or(|(next, _)| next > radius);
+			if is_last_of_radius
+				&& group_count == 1
+				&& covered.len() == self.get_vertex_lookup().len()
+			{
+				// round up so the triangle is kept by the f32 alpha
+				let alpha = *radius as f32;
+				return Some(if (alpha as f64) < *radius {
+					alpha.next_up()
+				} else {
+					alpha
+				});
+			}
+		}
+		None
+	}
+	/// Radius of the circle through the vertices of a triangle
+	fn circumradius(&self, triangle_id: usize) -> f64 {
+		let [a, b, c] = self.get_triangles()[&triangle_id]
+			.get_vertex_ids()
+			.map(|id| self.get_vertex_lookup()[&id].as_dvec2());
+		let twice_area = (b - a).perp_dot(c - a);
+		a.distance(b) * b.distance(c) * c.distance(a) / (2.0 * twice_area)
+	}
+	/// From a boundary edge of kept triangles find the next boundary edge
+	/// along the ring. Rotating clockwise about the end of the edge through
+	/// kept triangles finds the edge, this keeps rings that touch at a vertex
+	/// apart
+	fn next_boundary_edge(
+		&self,
+		edge: (usize, usize),
+		is_kept: &impl Fn(Option<usize>) -> bool,
+	) -> (usize, usize) {
+		let (mut tri_id, i) = edge;
+		let vertex = self.get_triangles()[&tri_id].get_vertex_ids()[(i + 1) % 3];
+		loop {
+			let ids = self.get_triangles()[&tri_id].get_vertex_ids();
+			// the edge leading out of the vertex
+			let j = ids.iter().position(|id| *id == vertex).unwrap();
+			let neighbour = self.triangle_neighbours(tri_id).unwrap()[j];
+			match neighbour {
+				Some(n) if is_kept(neighbour) => tri_id = n,
+				_ => return (tri_id, j),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy::prelude::*;
+
+	/// Points around two circles, one within the other
+	fn annulus() -> Vec<Vec2> {
+		let mut points = vec![];
+		for (radius, count) in [(100.0, 64), (80.0, 48), (60.0, 40)] {
+			for i in 0..count {
+				let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+				points.push(Vec2::new(angle.cos(), angle.sin()) * radius);
+			}
+		}
+		points
+	}
+	#[test]
+	fn alpha_shape() {
+		let data = Delaunay2d::compute_triangulation_2d(&annulus()).unwrap();
+		// the ring of triangles is kept but not the centre
+		let shape = data.alpha_shape(15.0);
+		assert_eq!(1, shape.get_polygons().len());
+		let polygon = &shape.get_polygons()[0];
+		assert_eq!(64, polygon.get_outer_ring().len());
+		assert_eq!(1, polygon.get_holes().len());
+		assert_eq!(40, polygon.get_holes()[0].len());
+		for id in polygon.get_holes()[0].iter() {
+			assert!(*id >= 112);
+		}
+		// a large alpha is the convex hull
+		let shape = data.alpha_shape(f32::MAX);
+		assert_eq!(data.get_triangles().len(), shape.get_triangles().len());
+		assert_eq!(
+			data.convex_hull().get_vertex_ids().len(),
+			shape.get_polygons()[0].get_outer_ring().len()
+		);
+		assert!(shape.get_polygons()[0].get_holes().is_empty());
+		assert!(data.alpha_shape(0.0).get_polygons().is_empty());
+	}
+	#[test]
+	fn connected_alpha() {
+		// two clusters far apart
+		let mut points = annulus();
+		points.extend(annulus().iter().map(|p| *p + Vec2::new(1000.0, 0.0)));
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let alpha = data.find_connected_alpha().unwrap();
+		assert!(alpha > 400.0);
+		let shape = data.alpha_shape(alpha);
+		assert_eq!(1, shape.get_polygons().len());
+		let smaller = data.alpha_shape(alpha.next_down());
+		assert!(smaller.get_polygons().len() > 1);
+	}
+}
@@ -2,7 +2,9 @@
 //! triangle such that all vertices sit along the circumference of the circle
 //!
 
-use bevy::prelude::*;
+use bevy::{math::DVec2, prelude::*};
+
+use crate::utilities::{incircle, orient2d};
 
 /// Describes a circle which has three vertices of a triangle sat along its circumference
 pub struct Circumcircle {
@@ -10,12 +12,24 @@ pub struct Circumcircle {
 	circumcentre: Vec2,
 	/// Circle radius sqaured
 	radius_sqaured: f32,
+	/// The triangle vertices that sit on the circumference, used for exact
+	/// containment tests
+	vertices: [Vec2; 3],
+	/// Whether the vertices are in an anti-clockwise order
+	is_anti_clockwise: bool,
 }
 impl Circumcircle {
 	/// From triangle vertices describe the properties of a circumcircle
 	///
-	/// If an edge length between vertices is zero then a circumcircle cannot be found
+	/// If the vertices are collinear (including an edge length of zero) then a circumcircle cannot be found
 	pub fn new(vertex_a: Vec2, vertex_b: Vec2, vertex_c: Vec2) -> Option<Self> {
+		let orientation = orient2d(vertex_a, vertex_b, vertex_c);
+		if orientation == 0.0 {
+			warn!("Failed to generate circumcircle");
+			return None;
+		}
+		let vertices = [vertex_a, vertex_b, vertex_c];
+		let is_anti_clockwise = orientation > 0.0;
 		// https://en.wikipedia.org/wiki/Circumcircle
 		let denom = 2.0
 			* ((vertex_a.x * (vertex_b.y - vertex_c.y))
@@ -40,10 +54,32 @@ impl Circumcircle {
 			Some(Circumcircle {
 				circumcentre,
 				radius_sqaured,
+				vertices,
+				is_anti_clockwise,
 			})
 		} else {
-			warn!("Failed to generate circumcircle");
-			None
+			// the triangle is too small for f32 precision, fall back to f64
+			let (a, b, c) = (
+				vertex_a.as_dvec2(),
+				vertex_b.as_dvec2(),
+				vertex_c.as_dvec2(),
+			);
+			let denom = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+			let centre_x = (a.length_squared() * (b.y - c.y)
+				+ b.length_squared() * (c.y - a.y)
+				+ c.length_squared() * (a.y - b.y))
+				/ denom;
+			let centre_y = (a.length_squared() * (c.x - b.x)
+				+ b.length_squared() * (a.x - c.x)
+				+ c.length_squared() * (b.x - a.x))
+				/ denom;
+			let circumcentre = DVec2::new(centre_x, centre_y);
+			Some(Circumcircle {
+				circumcentre: circumcentre.as_vec2(),
+				radius_sqaured: (circumcentre - a).length_squared() as f32,
+				vertices,
+				is_anti_clockwise,
+			})
 		}
 	}
 	/// Get the centre of the circumcircle
@@ -54,14 +90,19 @@ impl Circumcircle {
 	pub fn get_radius_sqaured(&self) -> f32 {
 		self.radius_sqaured
 	}
-	/// Check if a point is within the circumcircle
+	/// Check if a point is within the circumcircle, a point sat exactly on the
+	/// circumference is not considered within.
+	///
+	/// This uses the exact [incircle] predicate against the triangle vertices
+	/// rather than the rounded centre and radius
 	pub fn is_point_within_circle(&self, point: &Vec2) -> bool {
-		//TODO does this need a tolarance factor like spheres to handle values extremely close to one another???
-		// (y - center_y)^2 + (x - center_x)^2 < radius^2
-		let left =
-			(point.y - self.circumcentre.y).powf(2.0) + (point.x - self.circumcentre.x).powf(2.0);
-		let right = self.radius_sqaured;
-		left < right
+		let [a, b, c] = self.vertices;
+		let det = incircle(a, b, c, *point);
+		if self.is_anti_clockwise {
+			det > 0.0
+		} else {
+			det < 0.0
+		}
 	}
 }
 
@@ -121,5 +162,23 @@ mod tests {
 		let point = Vec2::new(10.0, 3.0);
 		assert!(!circumcircle.is_point_within_circle(&point));
 	}
-	//TODO test for None in invalid triangle setup
+	#[test]
+	fn new_circumcircle_is_none_collinear() {
+		let v_a = Vec2::new(-5.0, -5.0);
+		let v_b = Vec2::new(0.0, 0.0);
+		let v_c = Vec2::new(10.0, 10.0);
+		assert!(Circumcircle::new(v_a, v_b, v_c).is_none());
+	}
+	#[test]
+	fn point_on_circumference_is_not_within() {
+		let v_a = Vec2::new(1000.0, 1000.0);
+		let v_b = Vec2::new(1001.0, 1000.0);
+		let v_c = Vec2::new(1001.0, 1001.0);
+		let circumcircle = Circumcircle::new(v_a, v_b, v_c).unwrap();
+		assert!(!circumcircle.is_point_within_circle(&Vec2::new(1000.0, 1001.0)));
+		// clockwise vertices give the same answer
+		let circumcircle = Circumcircle::new(v_c, v_b, v_a).unwrap();
+		assert!(!circumcircle.is_point_within_circle(&Vec2::new(1000.0, 1001.0)));
+		assert!(circumcircle.is_point_within_circle(&Vec2::new(1000.5, 1000.5)));
+	}
 }
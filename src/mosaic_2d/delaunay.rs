@@ -1,161 +1,2648 @@
 //! From a series of 2d points in space compute a Delaunay Triangulation.
 //!
-//! The Bowyer-Watson algorithm is used - we describe a triangle that is large
-//! enough to enclose all the points of a data set within it. This starting
-//! triangle is stored in a mutable list which grows as more Delaunay Triangles
-//! are calculated.
+//! The Bowyer-Watson algorithm is used - the first three points that form a
+//! triangle begin the triangulation which grows as points are added.
 //!
-//! The first data point is added to the triangulation and we use the
-//! cirumcircles of any triangles in the list to determine if they are still
-//! Delaunay. If a triangles circumcircle contains a data point then the
-//! triangle is not Delaunay, the triangle is removed from the list and its
-//! edges are used to construct new triangles with the inserted data point -
-//! this makes new Delaunay Triangles. Step by step we add all points to the
-//! triangulation, computing new triangles as we go until all points have been
-//! processed and we arrive with a list of true Delaunay Triangles. Note that
-//! the original "super triangle" to kick-start the triangulation gets removed
-//! at the end as they were imaginary data points.
+//! Each data point is added to the triangulation in turn. The triangle
+//! containing the point is found by walking across the triangulation from the
+//! last triangle created, stepping over any edge the point lies beyond. If a
+//! triangles circumcircle contains the point then the triangle is not
+//! Delaunay, starting from the containing triangle we spread out across
+//! neighbouring triangles to find every triangle that is no longer Delaunay.
+//! These are removed leaving a polygonal hole around the point and the edges
+//! of the hole are joined to the point - this makes new Delaunay Triangles.
+//! Step by step we add all points to the triangulation, computing new
+//! triangles as we go until all points have been processed and we arrive with
+//! a list of true Delaunay Triangles.
+//!
+//! Rather than enclosing the points in a large imaginary "super triangle",
+//! each edge of the outer hull is treated as a ghost triangle joined to a
+//! vertex at infinity. The circumcircle of a ghost triangle is the half-plane
+//! beyond its edge, so a point outside of the triangulation is joined to every
+//! hull edge it can see and edges are then flipped until the triangulation is
+//! Delaunay again. No imaginary vertices are created which means the
+//! triangulation, including its convex hull, is exact for any spread or
+//! magnitude of points.
+//!
+//! Points are not added in the order they are supplied. They are split into
+//! pseudo-random rounds of increasing size and each round is sorted along a
+//! Hilbert curve, this keeps each walk short so the triangulation is computed
+//! in `O(n log n)` time.
+//!
+//! The ID of each vertex is the index of the point in the supplied data set
+//! that created it, i.e the vertex of the 17th point has an ID of `16`. Points
+//! that were merged as duplicates do not have a vertex of their own, instead
+//! [Delaunay2d::get_vertex_id] resolves them to the vertex they were merged
+//! into.
+//!
+//! Alongside the triangles the adjacency between them is recorded so that the
+//! triangulation can be walked one step at a time, see
+//! [Delaunay2d::triangle_neighbours], [Delaunay2d::vertex_star] and
+//! [Delaunay2d::edge_triangles].
+//!
+//! A constrained triangulation forces chosen segments between vertices to be
+//! edges of the triangulation, such as the outline of a wall or a road, see
+//! [Delaunay2d::compute_constrained_triangulation_2d]. Each triangle is then
+//! only required to be Delaunay with respect to the vertices it can see
+//! without looking across a constrained edge. Closed loops of constrained
+//! edges can describe polygons, with holes, and the triangles outside of them
+//! discarded with [Delaunay2d::discard_exterior_triangles].
+//!
+//! A triangulation can be refined into a quality mesh with
+//! [Delaunay2d::refine], new vertices are inserted until no triangle has too
+//! small an angle or too large an area.
+//!
+//! A triangulation made elsewhere, such as a mesh from another tool, can be
+//! turned into a Delaunay Triangulation with [Delaunay2d::from_triangulation]
+//! which flips its edges until every triangle is Delaunay.
 //!
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
-use bevy::prelude::*;
+use bevy::{math::DVec2, prelude::*};
 
-use crate::{mosaic_2d::triangle_node2d::*, prelude::Circumcircle};
+use crate::{
+	error::MosaicError,
+	mosaic_2d::{edge_node2d::EdgeNode2d, triangle_node2d::*},
+	prelude::{find_duplicate_points_2d, incircle, is_vertex_within_polygon, orient2d},
+};
 
 /// Describes the triangulation of a series of data points. Triangles and
-/// vertices are stored with unique IDs
+/// vertices are stored with unique IDs, a vertex ID is the index of the
+/// supplied point that it represents
 pub struct Delaunay2d {
 	/// Unqiuely ID'ed triangles
 	triangles: BTreeMap<usize, TriangleNode2d>,
-	/// Uniquely ID'ed vertices
+	/// Vertices ID'ed by the index of their supplied point
 	vertex_lookup: BTreeMap<usize, Vec2>,
+	/// Index of each supplied point that was merged into another point and
+	/// the vertex ID of the point it was merged into
+	merged_points: BTreeMap<usize, usize>,
+	/// For each triangle the ID of the triangle across each of its edges, in
+	/// the same order as [TriangleNode2d::get_edges]. `None` means the edge
+	/// sits on the outer hull
+	neighbours: BTreeMap<usize, [Option<usize>; 3]>,
+	/// A triangle that each vertex is part of, used as a starting point to
+	/// walk around a vertex
+	vertex_triangles: BTreeMap<usize, usize>,
+	/// ID to give the next triangle created
+	next_triangle_id: usize,
+	/// A triangle created by the latest insertion, the walk to the next
+	/// inserted point begins here as points tend to be inserted close
+	/// together. It may since have been removed
+	last_triangle: usize,
+	/// Distance within which an inserted point is merged into an existing
+	/// vertex, zero only merges exact duplicates
+	merge_tolerance: f32,
+	/// Number of points supplied so far, including merged points, this is
+	/// the index given to the next inserted point
+	point_count: usize,
+	/// Edges that must be part of the triangulation, stored as the vertex
+	/// IDs of each edge with the smallest first
+	constrained_edges: BTreeSet<(usize, usize)>,
+	/// When every vertex is collinear no triangles exist, instead this holds
+	/// the vertex IDs in order along their line. Empty otherwise
+	collinear_chain: Vec<usize>,
+}
+
+/// Describes how the triangulation changed when a point was inserted with
+/// [Delaunay2d::insert_point]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertResult {
+	/// Index the inserted point was given
+	point_index: usize,
+	/// ID of the vertex representing the point
+	vertex_id: usize,
+	/// IDs of triangles that no longer exist
+	removed_triangles: Vec<usize>,
+	/// IDs of triangles that were created
+	created_triangles: Vec<usize>,
+}
+
+impl InsertResult {
+	/// Get the index the inserted point was given, this is the next index
+	/// after the points the triangulation already contains
+	pub fn get_point_index(&self) -> usize {
+		self.point_index
+	}
+	/// Get the ID of the vertex that represents the point. This differs from
+	/// the point index if the point was merged into an existing vertex
+	pub fn get_vertex_id(&self) -> usize {
+		self.vertex_id
+	}
+	/// Whether the point duplicated an existing vertex and was merged into it
+	pub fn is_merged(&self) -> bool {
+		self.point_index != self.vertex_id
+	}
+	/// Get the IDs of the triangles that were removed
+	pub fn get_removed_triangles(&self) -> &[usize] {
+		&self.removed_triangles
+	}
+	/// Get the IDs of the triangles that were created
+	pub fn get_created_triangles(&self) -> &[usize] {
+		&self.created_triangles
+	}
+}
+
+/// Describes how the triangulation changed when a vertex was removed with
+/// [Delaunay2d::remove_vertex]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoveResult {
+	/// ID of the removed vertex
+	vertex_id: usize,
+	/// IDs of triangles that no longer exist
+	removed_triangles: Vec<usize>,
+	/// IDs of triangles that were created
+	created_triangles: Vec<usize>,
+}
+
+impl RemoveResult {
+	/// Get the ID of the vertex that was removed
+	pub fn get_vertex_id(&self) -> usize {
+		self.vertex_id
+	}
+	/// Get the IDs of the triangles that were removed
+	pub fn get_removed_triangles(&self) -> &[usize] {
+		&self.removed_triangles
+	}
+	/// Get the IDs of the triangles that were created
+	pub fn get_created_triangles(&self) -> &[usize] {
+		&self.created_triangles
+	}
+}
+
+/// The outer hull of a triangulation, see [Delaunay2d::convex_hull]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConvexHull2d {
+	/// Hull vertex IDs in anti-clockwise order
+	vertex_ids: Vec<usize>,
+	/// Hull edges in anti-clockwise order
+	edges: Vec<EdgeNode2d>,
+}
+
+impl ConvexHull2d {
+	/// Get the IDs of the vertices around the hull in anti-clockwise order,
+	/// starting with the lowest vertex
+	pub fn get_vertex_ids(&self) -> &[usize] {
+		&self.vertex_ids
+	}
+	/// Get the edges around the hull in anti-clockwise order, each edge runs
+	/// from a vertex to the next one anti-clockwise and the first edge begins
+	/// at the lowest vertex
+	pub fn get_edges(&self) -> &[EdgeNode2d] {
+		&self.edges
+	}
+}
+
+/// Where a point sits within a triangulation, see [Delaunay2d::locate]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+	/// The point is strictly inside a triangle
+	Triangle {
+		/// ID of the triangle
+		triangle_id: usize,
+		/// IDs of the triangle vertices
+		vertex_ids: [usize; 3],
+		/// Barycentric weight of each vertex, these sum to one
+		weights: [f32; 3],
+	},
+	/// The point sits on an edge between two vertices
+	Edge {
+		/// ID of a triangle using the edge
+		triangle_id: usize,
+		/// The edge the point sits on
+		edge: EdgeNode2d,
+		/// Weight of vertex a and vertex b of the edge, these sum to one
+		weights: [f32; 2],
+	},
+	/// The point sits on top of a vertex
+	Vertex {
+		/// ID of a triangle using the vertex
+		triangle_id: usize,
+		/// ID of the vertex
+		vertex_id: usize,
+	},
+	/// The point is outside of the triangulation
+	OutsideHull {
+		/// ID of the triangle using the nearest hull edge
+		triangle_id: usize,
+		/// The hull edge nearest to the point, it runs anti-clockwise around
+		/// the hull
+		edge: EdgeNode2d,
+		/// Weight of vertex a and vertex b of the edge that give the position
+		/// along the edge nearest to the point, these sum to one
+		weights: [f32; 2],
+	},
+}
+
+impl Location {
+	/// Get the ID of the triangle the point was found in, or next to if the
+	/// point is outside of the triangulation. This is a good starting point
+	/// to locate another point nearby with [Delaunay2d::locate_from]
+	pub fn get_triangle_id(&self) -> usize {
+		match self {
+			Location::Triangle { triangle_id, .. }
+			| Location::Edge { triangle_id, .. }
+			| Location::Vertex { triangle_id, .. }
+			| Location::OutsideHull { triangle_id, .. } => *triangle_id,
+		}
+	}
+	/// Get the ID and weight of each vertex around the point. The weighted
+	/// sum of their positions is the point, or the nearest position on the
+	/// hull if the point is outside of the triangulation
+	pub fn get_weighted_vertices(&self) -> Vec<(usize, f32)> {
+		match self {
+			Location::Triangle {
+				vertex_ids,
+				weights,
+				..
+			} => vertex_ids.iter().copied().zip(*weights).collect(),
+			Location::Edge { edge, weights, .. } | Location::OutsideHull { edge, weights, .. } => {
+				vec![
+					(edge.get_vertex_a_id(), weights[0]),
+					(edge.get_vertex_b_id(), weights[1]),
+				]
+			}
+			Location::Vertex { vertex_id, .. } => vec![(*vertex_id, 1.0)],
+		}
+	}
+	/// Whether the point is outside of the triangulation
+	pub fn is_outside_hull(&self) -> bool {
+		matches!(self, Location::OutsideHull { .. })
+	}
+}
+
+/// Largest minimum angle, in degrees, that [Delaunay2d::refine] accepts.
+/// Beyond it refinement rarely finishes
+pub const MAXIMUM_REFINEMENT_ANGLE: f32 = 34.0;
+
+/// Limits on the shape and size of triangles used by [Delaunay2d::refine]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefinementBounds {
+	/// Smallest angle in degrees a triangle may have
+	minimum_angle: f32,
+	/// Largest area any triangle may have
+	maximum_area: Option<f32>,
+	/// Largest area of a triangle whose centroid sits within a polygon
+	region_maximum_areas: Vec<(Vec<Vec2>, f32)>,
+	/// Most vertices refinement may add
+	vertex_limit: usize,
+}
+
+impl RefinementBounds {
+	/// Require every triangle to have no angle smaller than `minimum_angle`
+	/// degrees, up to [MAXIMUM_REFINEMENT_ANGLE]. An angle of zero only
+	/// limits the area of triangles. At most one million vertices are added
+	pub fn new(minimum_angle: f32) -> Self {
+		RefinementBounds {
+			minimum_angle,
+			maximum_area: None,
+			region_maximum_areas: vec![],
+			vertex_limit: 1_000_000,
+		}
+	}
+	/// Require every triangle to have an area no larger than `area`
+	pub fn with_maximum_area(mut self, area: f32) -> Self {
+		self.maximum_area = Some(area);
+		self
+	}
+	/// Require triangles whose centroid sits within the `region` polygon to
+	/// have an area no larger than `area`. Where regions overlap the smallest
+	/// area applies
+	pub fn with_region_maximum_area(mut self, region: Vec<Vec2>, area: f32) -> Self {
+		self.region_maximum_areas.push((region, area));
+		self
+	}
+	/// Stop refining once `limit` vertices have been added
+	pub fn with_vertex_limit(mut self, limit: usize) -> Self {
+		self.vertex_limit = limit;
+		self
+	}
+	/// Get the smallest angle in degrees a triangle may have
+	pub fn get_minimum_angle(&self) -> f32 {
+		self.minimum_angle
+	}
+	/// Get the largest area any triangle may have
+	pub fn get_maximum_area(&self) -> Option<f32> {
+		self.maximum_area
+	}
+	/// Get each polygon and the largest area of a triangle within it
+	pub fn get_region_maximum_areas(&self) -> &[(Vec<Vec2>, f32)] {
+		&self.region_maximum_areas
+	}
+	/// Get the most vertices refinement may add
+	pub fn get_vertex_limit(&self) -> usize {
+		self.vertex_limit
+	}
+}
+
+/// Describes the vertices added by [Delaunay2d::refine]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefineResult {
+	/// IDs of the added vertices in the order they were added
+	added_vertices: Vec<usize>,
+	/// Whether refinement finished before the vertex limit
+	is_complete: bool,
+}
+
+impl RefineResult {
+	/// Get the IDs of the added vertices in the order they were added, like
+	/// an inserted point each ID continues on from the supplied points
+	pub fn get_added_vertices(&self) -> &[usize] {
+		&self.added_vertices
+	}
+	/// Whether refinement finished rather than stopping at the vertex limit.
+	/// Small angles that are part of the shape, such as two constrained
+	/// edges meeting at a small angle, are left as they are even when
+	/// complete
+	pub fn is_complete(&self) -> bool {
+		self.is_complete
+	}
+}
+
+/// The [RefinementBounds] prepared for testing triangles
+struct QualityLimits {
+	/// Largest ratio of circumradius to shortest edge, this grows as the
+	/// smallest angle shrinks
+	radius_edge_ratio: f64,
+	/// Largest area any triangle may have
+	maximum_area: Option<f64>,
+	/// Edges of anti-clockwise region polygons and the largest area of a
+	/// triangle within each
+	regions: Vec<(Vec<(Vec2, Vec2)>, f64)>,
 }
 
 impl Delaunay2d {
 	/// From a series of 2d points in a plane compute the Delaunay
-	/// Triangulation with the Bowyer-Watson algorithm.
-	pub fn compute_triangulation_2d(points: &Vec<Vec2>) -> Option<Self> {
+	/// Triangulation with the Bowyer-Watson algorithm, see the module docs.
+	///
+	/// Fails if fewer than 3 points are supplied, a point has a non-finite
+	/// coordinate or every point is collinear
+	///
+	/// Points which exactly duplicate an earlier point are merged into it,
+	/// see [Delaunay2d::get_merged_points]
+	pub fn compute_triangulation_2d(points: &[Vec2]) -> Result<Self, MosaicError> {
+		Delaunay2d::compute_triangulation_2d_with_merge_tolerance(points, 0.0)
+	}
+	/// From a series of 2d points in a plane compute the Delaunay
+	/// Triangulation with the Bowyer-Watson algorithm, any point within
+	/// `merge_tolerance` distance of an earlier point is merged into that
+	/// earlier point rather than becoming a vertex of its own. The tolerance
+	/// also applies to points inserted later with [Delaunay2d::insert_point].
+	///
+	/// The merged points can be found with [Delaunay2d::get_merged_points]
+	pub fn compute_triangulation_2d_with_merge_tolerance(
+		points: &[Vec2],
+		merge_tolerance: f32,
+	) -> Result<Self, MosaicError> {
 		if points.len() < 3 {
-			error!(
-				"Minimum of 3 points required for triangulation, supplied {} points",
-				points.len()
+			return Err(MosaicError::InsufficientPoints {
+				required: 3,
+				supplied: points.len(),
+			});
+		}
+		if let Some(index) = points.iter().position(|p| !p.is_finite()) {
+			return Err(MosaicError::NonFiniteCoordinate { index });
+		}
+		// duplicate points cannot form triangles, identify them so they can
+		// be skipped
+		let duplicates = find_duplicate_points_2d(points, merge_tolerance);
+		let unique_points: Vec<Vec2> = points
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| !duplicates.contains_key(i))
+			.map(|(_, p)| *p)
+			.collect();
+		if are_points_collinear(&unique_points) {
+			return Err(MosaicError::CollinearPoints);
+		}
+		// store vertices with the index of their point as the id
+		let vertex_lookup = points
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| !duplicates.contains_key(i))
+			.map(|(i, p)| (i, *p))
+			.collect();
+		let mut delaunay = Delaunay2d {
+			triangles: BTreeMap::new(),
+			vertex_lookup,
+			merged_points: duplicates,
+			neighbours: BTreeMap::new(),
+			vertex_triangles: BTreeMap::new(),
+			next_triangle_id: 0,
+			last_triangle: 0,
+			merge_tolerance,
+			point_count: points.len(),
+			constrained_edges: BTreeSet::new(),
+			collinear_chain: vec![],
+		};
+		delaunay.triangulate_vertices();
+
+		if !delaunay.triangles.is_empty() {
+			Ok(delaunay)
+		} else {
+			Err(MosaicError::EmptyTriangulation)
+		}
+	}
+	/// From a series of 2d points compute the Delaunay Triangulation, unlike
+	/// [Delaunay2d::compute_triangulation_2d] any number of points is
+	/// accepted.
+	///
+	/// If every distinct point is collinear, or fewer than 3 distinct points
+	/// are supplied, no triangles can be formed. Instead the triangulation
+	/// holds no triangles and describes the line the points sit along, see
+	/// [Delaunay2d::get_collinear_chain]. A Voronoi tessellation of such a
+	/// line can be built with
+	/// [Voronoi2d::from_collinear_delaunay_2d](crate::mosaic_2d::voronoi::Voronoi2d::from_collinear_delaunay_2d).
+	///
+	/// Fails if no points are supplied or a point has a non-finite
+	/// coordinate
+	pub fn compute_triangulation_2d_allow_collinear(points: &[Vec2]) -> Result<Self, MosaicError> {
+		if points.is_empty() {
+			return Err(MosaicError::InsufficientPoints {
+				required: 1,
+				supplied: 0,
+			});
+		}
+		match Delaunay2d::compute_triangulation_2d(points) {
+			Err(MosaicError::CollinearPoints) | Err(MosaicError::InsufficientPoints { .. }) => {}
+			result => return result,
+		}
+		if let Some(index) = points.iter().position(|p| !p.is_finite()) {
+			return Err(MosaicError::NonFiniteCoordinate { index });
+		}
+		let merged_points = find_duplicate_points_2d(points, 0.0);
+		let vertex_lookup: BTreeMap<usize, Vec2> = points
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| !merged_points.contains_key(i))
+			.map(|(i, p)| (i, *p))
+			.collect();
+		let mut delaunay = Delaunay2d {
+			triangles: BTreeMap::new(),
+			vertex_lookup,
+			merged_points,
+			neighbours: BTreeMap::new(),
+			vertex_triangles: BTreeMap::new(),
+			next_triangle_id: 0,
+			last_triangle: 0,
+			merge_tolerance: 0.0,
+			point_count: points.len(),
+			constrained_edges: BTreeSet::new(),
+			collinear_chain: vec![],
+		};
+		delaunay.collinear_chain = delaunay.vertex_lookup.keys().copied().collect();
+		delaunay.sort_collinear_chain();
+		Ok(delaunay)
+	}
+	/// From vertex positions and the triangles joining them, such as a mesh
+	/// made by an artist or another tool, build a triangulation and flip its
+	/// edges until every triangle is Delaunay. Each triangle is three indices
+	/// into `vertices` in either winding and the ID of each vertex is its
+	/// index.
+	///
+	/// This is Lawson's algorithm, an edge is flipped to join the two
+	/// vertices opposite it whenever one of them sits within the circumcircle
+	/// of the triangle across from it. Returns the triangulation along with
+	/// the number of flips made, `0` if it was already Delaunay.
+	///
+	/// The triangles must form a single region without holes or overlaps and
+	/// every vertex must be part of a triangle, otherwise this fails with
+	/// [MosaicError::InvalidTriangulation]. If the outline of the region is
+	/// not convex its edges are constrained, see
+	/// [Delaunay2d::is_edge_constrained], the triangles beside them may not be
+	/// Delaunay and [Delaunay2d::insert_point] only accepts points within the
+	/// region. Also fails if fewer than 3 vertices are supplied or a vertex
+	/// has a non-finite coordinate
+	pub fn from_triangulation(
+		vertices: &[Vec2],
+		triangles: &[[usize; 3]],
+	) -> Result<(Self, usize), MosaicError> {
+		if vertices.len() < 3 {
+			return Err(MosaicError::InsufficientPoints {
+				required: 3,
+				supplied: vertices.len(),
+			});
+		}
+		if let Some(index) = vertices.iter().position(|p| !p.is_finite()) {
+			return Err(MosaicError::NonFiniteCoordinate { index });
+		}
+		let mut delaunay = Delaunay2d {
+			triangles: BTreeMap::new(),
+			vertex_lookup: vertices.iter().copied().enumerate().collect(),
+			merged_points: BTreeMap::new(),
+			neighbours: BTreeMap::new(),
+			vertex_triangles: BTreeMap::new(),
+			next_triangle_id: 0,
+			last_triangle: 0,
+			merge_tolerance: 0.0,
+			point_count: vertices.len(),
+			constrained_edges: BTreeSet::new(),
+			collinear_chain: vec![],
+		};
+		// each edge running anti-clockwise around its triangle, along with
+		// the triangle and the index of the edge within it
+		let mut edges: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
+		let mut twice_area = 0.0;
+		for [a, b, c] in triangles.iter().copied() {
+			if [a, b, c].iter().any(|id| *id >= vertices.len()) {
+				return Err(MosaicError::InvalidTriangulation(
+					"a triangle refers to a vertex that does not exist",
+				));
+			}
+			let orientation = orient2d(vertices[a], vertices[b], vertices[c]);
+			if orientation == 0.0 {
+				return Err(MosaicError::InvalidTriangulation("a triangle has no area"));
+			}
+			let ids = if orientation > 0.0 {
+				[a, b, c]
+			} else {
+				[b, a, c]
+			};
+			let tri_id = delaunay.take_triangle_id();
+			for i in 0..3 {
+				// two triangles with an edge running the same way overlap
+				if edges
+					.insert((ids[i], ids[(i + 1) % 3]), (tri_id, i))
+					.is_some()
+				{
+					return Err(MosaicError::InvalidTriangulation("triangles overlap"));
+				}
+			}
+			let [pa, pb, pc] = ids.map(|id| vertices[id].as_dvec2());
+			twice_area += (pb - pa).perp_dot(pc - pa);
+			delaunay
+				.triangles
+				.insert(tri_id, TriangleNode2d::new(ids[0], ids[1], ids[2]));
+			for id in ids {
+				delaunay.vertex_triangles.insert(id, tri_id);
+			}
+		}
+		if delaunay.vertex_triangles.len() < vertices.len() {
+			return Err(MosaicError::InvalidTriangulation(
+				"a vertex is not part of any triangle",
+			));
+		}
+		// join triangles across their shared edges, an edge with no triangle
+		// running the other way along it is on the hull
+		let mut hull = BTreeMap::new();
+		for ((a, b), (tri_id, i)) in edges.iter() {
+			let neighbour = edges.get(&(*b, *a)).map(|(n, _)| *n);
+			delaunay.neighbours.entry(*tri_id).or_insert([None; 3])[*i] = neighbour;
+			if neighbour.is_none() && hull.insert(*a, *b).is_some() {
+				return Err(MosaicError::InvalidTriangulation(
+					"the triangles do not form a single region",
+				));
+			}
+		}
+		// the hull must be one loop around the triangles, covering the same
+		// area as them
+		let Some(start) = hull.keys().next().copied() else {
+			return Err(MosaicError::InvalidTriangulation("no triangles supplied"));
+		};
+		let mut current = start;
+		let mut hull_length = 0;
+		let mut twice_hull_area = 0.0;
+		let mut is_convex = true;
+		loop {
+			let next = hull[&current];
+			if orient2d(vertices[current], vertices[next], vertices[hull[&next]]) < 0.0 {
+				is_convex = false;
+			}
+			twice_hull_area += vertices[current]
+				.as_dvec2()
+				.perp_dot(vertices[next].as_dvec2());
+			hull_length += 1;
+			current = next;
+			if current == start {
+				break;
+			}
+		}
+		if hull_length != hull.len() {
+			return Err(MosaicError::InvalidTriangulation(
+				"the triangles do not form a single region",
+			));
+		}
+		if (twice_area - twice_hull_area).abs() > twice_area * 1e-9 {
+			return Err(MosaicError::InvalidTriangulation("triangles overlap"));
+		}
+		// a concave outline is kept by constraining its edges, so only the
+		// interior edges are flipped
+		if !is_convex {
+			delaunay.constrained_edges = hull.iter().map(|(a, b)| (*a.min(b), *a.max(b))).collect();
+		}
+		let mut pending: Vec<(usize, usize)> = delaunay
+			.triangles
+			.keys()
+			.flat_map(|tri_id| (0..3).map(|i| (*tri_id, i)))
+			.collect();
+		let mut flips = 0;
+		while let Some((tri_id, edge_index)) = pending.pop() {
+			if let Some([first, second]) = delaunay.flip_if_illegal(tri_id, edge_index) {
+				flips += 1;
+				pending.extend([(first, 0), (first, 1), (second, 0), (second, 1)]);
+			}
+		}
+		delaunay.compact_triangle_ids();
+		Ok((delaunay, flips))
+	}
+	/// From a series of 2d points compute the Constrained Delaunay
+	/// Triangulation where each of the `segments` is an edge of the
+	/// triangulation. A segment is described by the indices of the two points
+	/// it joins.
+	///
+	/// A segment that passes through another point is split at the point into
+	/// two constrained edges.
+	///
+	/// Fails for the same reasons as [Delaunay2d::compute_triangulation_2d],
+	/// or if a segment refers to a point that does not exist, has no length,
+	/// or crosses another segment
+	pub fn compute_constrained_triangulation_2d(
+		points: &[Vec2],
+		segments: &[[usize; 2]],
+	) -> Result<Self, MosaicError> {
+		let mut delaunay = Delaunay2d::compute_triangulation_2d(points)?;
+		for [a, b] in segments.iter() {
+			delaunay.insert_constraint(*a, *b)?;
+		}
+		Ok(delaunay)
+	}
+	/// Force the segment joining the points at index `a` and `b` to be an edge
+	/// of the triangulation, triangles the segment crosses are replaced
+	///
+	/// Fails if either point does not exist, the segment has no length or it
+	/// crosses an existing constrained edge, in which case the triangulation
+	/// is unchanged. A collinear chain cannot be constrained and fails with
+	/// [MosaicError::CollinearPoints]
+	pub fn insert_constraint(&mut self, a: usize, b: usize) -> Result<(), MosaicError> {
+		if self.is_collinear() {
+			return Err(MosaicError::CollinearPoints);
+		}
+		let resolve = |index: usize| {
+			self.get_vertex_id(index)
+				.ok_or(MosaicError::UnknownVertex { vertex_id: index })
+		};
+		let (a, b) = (resolve(a)?, resolve(b)?);
+		if a == b {
+			return Err(MosaicError::InvalidConstraint("segment has no length"));
+		}
+		// trace the whole segment before changing anything, it is split
+		// wherever it passes through a vertex
+		let mut pieces = vec![];
+		let mut start = a;
+		while start != b {
+			let (_, end) = self.trace_segment(start, b)?;
+			pieces.push((start, end));
+			start = end;
+		}
+		for (start, end) in pieces {
+			self.enforce_edge(start, end);
+		}
+		Ok(())
+	}
+	/// Get the edges that are constrained to be part of the triangulation
+	pub fn get_constrained_edges(&self) -> Vec<EdgeNode2d> {
+		self.constrained_edges
+			.iter()
+			.map(|(a, b)| EdgeNode2d::new(*a, *b))
+			.collect()
+	}
+	/// Whether an edge is constrained to be part of the triangulation
+	pub fn is_edge_constrained(&self, edge: &EdgeNode2d) -> bool {
+		self.is_constrained(edge.get_vertex_a_id(), edge.get_vertex_b_id())
+	}
+	/// Whether any edge is constrained, the triangles beside a constrained
+	/// edge need not be Delaunay
+	pub(crate) fn has_constrained_edges(&self) -> bool {
+		!self.constrained_edges.is_empty()
+	}
+	/// Whether the triangles form a single region without holes whose
+	/// outline is convex
+	fn is_outline_convex(&self) -> bool {
+		let hull = self.convex_hull().get_vertex_ids().to_vec();
+		let outline_length = self
+			.neighbours
+			.values()
+			.flatten()
+			.filter(|neighbour| neighbour.is_none())
+			.count();
+		outline_length == hull.len()
+			&& (0..hull.len()).all(|i| {
+				let [a, b, c] = [0, 1, 2].map(|k| self.vertex_lookup[&hull[(i + k) % hull.len()]]);
+				orient2d(a, b, c) >= 0.0
+			})
+	}
+	/// Remove the triangles that sit outside of the polygons described by
+	/// closed loops of constrained edges, a loop within a polygon describes a
+	/// hole in it.
+	///
+	/// Starting outside of the hull each constrained edge crossed moves
+	/// between the outside and inside of a polygon, triangles reached by
+	/// crossing an odd number of edges are kept. Vertices no longer part of
+	/// any triangle are removed along with constrained edges that no longer
+	/// exist. Afterwards [Delaunay2d::insert_point] only accepts points
+	/// within the remaining triangles unless their outline is still convex.
+	///
+	/// Returns the IDs of the removed triangles, a collinear chain is left
+	/// unchanged
+	pub fn discard_exterior_triangles(&mut self) -> Vec<usize> {
+		if self.is_collinear() {
+			return vec![];
+		}
+		// spread inwards from the hull visiting triangles in order of how
+		// many constrained edges have been crossed to reach them
+		let mut crossings = BTreeMap::new();
+		let mut queue = VecDeque::new();
+		for (tri_id, neighbours) in self.neighbours.iter() {
+			let ids = self.triangles[tri_id].get_vertex_ids();
+			for (i, neighbour) in neighbours.iter().enumerate() {
+				if neighbour.is_none() {
+					if self.is_constrained(ids[i], ids[(i + 1) % 3]) {
+						queue.push_back((*tri_id, 1));
+					} else {
+						queue.push_front((*tri_id, 0));
+					}
+				}
+			}
+		}
+		while let Some((tri_id, count)) = queue.pop_front() {
+			if crossings.contains_key(&tri_id) {
+				continue;
+			}
+			crossings.insert(tri_id, count);
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			for (i, neighbour) in self.neighbours[&tri_id].iter().enumerate() {
+				if let Some(n) = neighbour
+					&& !crossings.contains_key(n)
+				{
+					if self.is_constrained(ids[i], ids[(i + 1) % 3]) {
+						queue.push_back((*n, count + 1));
+					} else {
+						queue.push_front((*n, count));
+					}
+				}
+			}
+		}
+		let removed: Vec<usize> = self
+			.triangles
+			.keys()
+			.filter(|tri_id| crossings.get(tri_id).is_none_or(|count| count % 2 == 0))
+			.copied()
+			.collect();
+		for tri_id in removed.iter() {
+			self.triangles.remove(tri_id);
+			self.neighbours.remove(tri_id);
+		}
+		for neighbours in self.neighbours.values_mut() {
+			for neighbour in neighbours.iter_mut() {
+				if neighbour.is_some_and(|n| !self.triangles.contains_key(&n)) {
+					*neighbour = None;
+				}
+			}
+		}
+		self.vertex_triangles.clear();
+		for (tri_id, tri) in self.triangles.iter() {
+			for vertex in tri.get_vertex_ids() {
+				self.vertex_triangles.insert(*vertex, *tri_id);
+			}
+		}
+		self.vertex_lookup
+			.retain(|id, _| self.vertex_triangles.contains_key(id));
+		self.merged_points
+			.retain(|_, survivor| self.vertex_triangles.contains_key(survivor));
+		let edges: Vec<(usize, usize)> = self.constrained_edges.iter().copied().collect();
+		for (a, b) in edges {
+			if self.edge_triangles(&EdgeNode2d::new(a, b)) == [None, None] {
+				self.constrained_edges.remove(&(a, b));
+			}
+		}
+		removed
+	}
+	/// Get a refernce to the map of unqiuely ID'ed triangles
+	pub fn get_triangles(&self) -> &BTreeMap<usize, TriangleNode2d> {
+		&self.triangles
+	}
+	/// Get a refernce to the map of vertex IDs and their position. Each ID
+	/// is the index of the supplied point the vertex was created from
+	pub fn get_vertex_lookup(&self) -> &BTreeMap<usize, Vec2> {
+		&self.vertex_lookup
+	}
+	/// Get a reference to the map of supplied point indices that were merged
+	/// into another point as duplicates. The value is the vertex ID of the
+	/// surviving point
+	pub fn get_merged_points(&self) -> &BTreeMap<usize, usize> {
+		&self.merged_points
+	}
+	/// Find the vertex ID that represents the supplied point at `point_index`.
+	///
+	/// This is the index itself unless the point was merged into another as a
+	/// duplicate, in which case the surviving vertex ID is returned. `None`
+	/// means the index is not part of the triangulation
+	pub fn get_vertex_id(&self, point_index: usize) -> Option<usize> {
+		if self.vertex_lookup.contains_key(&point_index) {
+			Some(point_index)
+		} else {
+			self.merged_points.get(&point_index).copied()
+		}
+	}
+	/// Get the vertex IDs in order along the line that every vertex sits on
+	/// when no triangles could be formed, see
+	/// [Delaunay2d::compute_triangulation_2d_allow_collinear]. Consecutive IDs
+	/// are the edges of the triangulation.
+	///
+	/// Empty if the triangulation is made of triangles
+	pub fn get_collinear_chain(&self) -> &[usize] {
+		&self.collinear_chain
+	}
+	/// Whether every vertex sits along a single line so the triangulation is
+	/// a chain of vertices rather than triangles
+	pub fn is_collinear(&self) -> bool {
+		!self.collinear_chain.is_empty()
+	}
+	/// Insert a new point into the triangulation in place, updating only the
+	/// triangles around it rather than recomputing the whole triangulation.
+	///
+	/// The point is given the next index as if it had been appended to the
+	/// supplied points. A point within the merge tolerance of an existing
+	/// vertex, see [Delaunay2d::compute_triangulation_2d_with_merge_tolerance],
+	/// is merged into the nearest such vertex and no triangles change. A point
+	/// outside of the triangulation extends the hull.
+	///
+	/// Once the outline of the triangles is no longer convex, such as after
+	/// [Delaunay2d::discard_exterior_triangles], only points within the
+	/// remaining triangles can be inserted as extending the outline could
+	/// cross the triangles beyond it.
+	///
+	/// If the triangulation is a collinear chain, see
+	/// [Delaunay2d::get_collinear_chain], a point along the line extends the
+	/// chain. Once a point sits off the line triangles can be formed and the
+	/// whole triangulation is computed, every triangle is reported as created
+	///
+	/// Fails if the point has a non-finite coordinate, or with
+	/// [MosaicError::OutsideTriangulation] if the point is outside of an
+	/// outline that is not convex
+	pub fn insert_point(&mut self, point: Vec2) -> Result<InsertResult, MosaicError> {
+		let point_index = self.point_count;
+		if !point.is_finite() {
+			return Err(MosaicError::NonFiniteCoordinate { index: point_index });
+		}
+		if self.is_collinear() {
+			self.point_count += 1;
+			return Ok(self.insert_into_chain(point_index, point));
+		}
+		// walk from the triangle made by the latest insertion, or any
+		// triangle if it has since been removed
+		let start = if self.triangles.contains_key(&self.last_triangle) {
+			self.last_triangle
+		} else {
+			self.triangles.keys().next().copied().unwrap_or_default()
+		};
+		let mut located = self.walk_to_point(start, point);
+		// a walk can step out of a concave outline or into a hole before it
+		// reaches the point, so every triangle is checked before giving up
+		if located.is_err() && self.has_constrained_edges() && !self.is_outline_convex() {
+			let containing = self.triangles.iter().find(|(_, tri)| {
+				let [a, b, c] = tri.get_vertex_ids().map(|id| self.vertex_lookup[&id]);
+				orient2d(a, b, point) >= 0.0
+					&& orient2d(b, c, point) >= 0.0
+					&& orient2d(c, a, point) >= 0.0
+			});
+			let Some((tri_id, _)) = containing else {
+				return Err(MosaicError::OutsideTriangulation { index: point_index });
+			};
+			located = Ok(*tri_id);
+		}
+		self.point_count += 1;
+		let start = match located {
+			Ok(containing) => containing,
+			Err((hull_triangle, _)) => hull_triangle,
+		};
+		// the nearest vertex is searched for from the triangle the point was
+		// found in, or beside, so the search is short
+		let nearby = self.triangles[&start].get_vertex_ids()[0];
+		if let Some(existing) = self.find_merge_vertex(point, Some(nearby)) {
+			self.merged_points.insert(point_index, existing);
+			return Ok(InsertResult {
+				point_index,
+				vertex_id: existing,
+				removed_triangles: vec![],
+				created_triangles: vec![],
+			});
+		}
+		self.vertex_lookup.insert(point_index, point);
+		let (removed_triangles, created_triangles) = self.insert_vertex(point_index, start);
+		if let Some(id) = created_triangles.last() {
+			self.last_triangle = *id;
+		}
+		Ok(InsertResult {
+			point_index,
+			vertex_id: point_index,
+			removed_triangles,
+			created_triangles,
+		})
+	}
+	/// Remove a vertex from the triangulation in place, the triangles around
+	/// it are replaced by new Delaunay triangles filling the hole it leaves.
+	/// Any supplied points that were merged into the vertex are forgotten, as
+	/// are any constrained edges using it. If the vertex split a constrained
+	/// segment in two the whole segment is constrained again.
+	///
+	/// Fails if the vertex does not exist, or if removing it would leave too
+	/// few points, points that all sit on a line, a vertex that is no longer
+	/// part of any triangle or triangles that only meet at a vertex. A vertex
+	/// of a collinear chain is simply removed from the chain
+	pub fn remove_vertex(&mut self, vertex_id: usize) -> Result<RemoveResult, MosaicError> {
+		if !self.vertex_lookup.contains_key(&vertex_id) {
+			return Err(MosaicError::UnknownVertex { vertex_id });
+		}
+		if self.is_collinear() {
+			if self.vertex_lookup.len() <= 1 {
+				return Err(MosaicError::InsufficientPoints {
+					required: 1,
+					supplied: 0,
+				});
+			}
+			self.collinear_chain.retain(|id| *id != vertex_id);
+			self.vertex_lookup.remove(&vertex_id);
+			self.merged_points
+				.retain(|_, survivor| *survivor != vertex_id);
+			return Ok(RemoveResult {
+				vertex_id,
+				removed_triangles: vec![],
+				created_triangles: vec![],
+			});
+		}
+		if self.vertex_lookup.len() <= 3 {
+			return Err(MosaicError::InsufficientPoints {
+				required: 3,
+				supplied: self.vertex_lookup.len() - 1,
+			});
+		}
+		// the edges opposite the vertex form the boundary of the hole, with
+		// the triangle on the far side of each
+		let star = self.vertex_star(vertex_id);
+		let mut link_edges = vec![];
+		for tri_id in star.iter() {
+			let ids = self.triangles[tri_id].get_vertex_ids();
+			let i = ids.iter().position(|id| *id == vertex_id).unwrap();
+			let edge_index = (i + 1) % 3;
+			link_edges.push((
+				ids[edge_index],
+				ids[(edge_index + 1) % 3],
+				self.neighbours[tri_id][edge_index],
+			));
+		}
+		let mut polygon: Vec<usize> = link_edges.iter().map(|(a, _, _)| *a).collect();
+		// a vertex on the hull leaves an open chain rather than a polygon
+		let is_closed = star.len() > 1
+			&& self.rotate_about_vertex(star[star.len() - 1], vertex_id, true) == Some(star[0]);
+		if !is_closed && let Some((_, b, _)) = link_edges.last() {
+			polygon.push(*b);
+		}
+		let (new_triangles, unfilled) = self.triangulate_hole(&polygon, vertex_id, is_closed);
+		// every vertex around the hole must still be part of a triangle
+		let mut is_covered: BTreeSet<usize> = new_triangles.iter().flatten().copied().collect();
+		for (a, b, outside) in link_edges.iter() {
+			if outside.is_some() {
+				is_covered.extend([*a, *b]);
+			}
+		}
+		if let Some(uncovered) = polygon.iter().find(|id| !is_covered.contains(id)) {
+			let remaining: Vec<Vec2> = self
+				.vertex_lookup
+				.iter()
+				.filter(|(id, _)| **id != vertex_id)
+				.map(|(_, position)| *position)
+				.collect();
+			if are_points_collinear(&remaining) {
+				return Err(MosaicError::CollinearPoints);
+			}
+			return Err(MosaicError::UncoveredVertex {
+				vertex_id: *uncovered,
+			});
+		}
+		// a vertex along the unfilled part of an open chain becomes part of
+		// the hull, if it already was then the triangles on either side of
+		// it would only meet at the vertex
+		if !is_closed && unfilled.len() > 2 {
+			let is_on_hull = |id: usize| {
+				let star = self.vertex_star(id);
+				star.len() < 2
+					|| self.rotate_about_vertex(star[star.len() - 1], id, true) != Some(star[0])
+			};
+			if let Some(pinched) = unfilled[1..unfilled.len() - 1]
+				.iter()
+				.find(|id| is_on_hull(**id))
+			{
+				return Err(MosaicError::PinchedVertex {
+					vertex_id: *pinched,
+				});
+			}
+		}
+		// a vertex between two constrained edges running in a straight line
+		// split a segment, which is restored once the vertex is gone
+		let constrained: Vec<usize> = self
+			.constrained_edges
+			.iter()
+			.filter_map(|(a, b)| match vertex_id {
+				id if id == *a => Some(*b),
+				id if id == *b => Some(*a),
+				_ => None,
+			})
+			.collect();
+		let parent_segment = match constrained[..] {
+			[u, w] => {
+				let [pu, pv, pw] = [u, vertex_id, w].map(|id| self.vertex_lookup[&id]);
+				(orient2d(pu, pv, pw) == 0.0 && (pu - pv).dot(pw - pv) < 0.0).then_some((u, w))
+			}
+			_ => None,
+		};
+
+		for tri_id in star.iter() {
+			self.triangles.remove(tri_id);
+			self.neighbours.remove(tri_id);
+		}
+		self.vertex_lookup.remove(&vertex_id);
+		self.vertex_triangles.remove(&vertex_id);
+		self.merged_points
+			.retain(|_, survivor| *survivor != vertex_id);
+		self.constrained_edges
+			.retain(|(a, b)| *a != vertex_id && *b != vertex_id);
+		// new triangles keyed by each of their edges
+		let mut new_edges = BTreeMap::new();
+		let mut created = vec![];
+		for [a, b, c] in new_triangles {
+			let tri_id = self.take_triangle_id();
+			self.triangles.insert(tri_id, TriangleNode2d::new(a, b, c));
+			self.neighbours.insert(tri_id, [None; 3]);
+			for (i, edge) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+				new_edges.insert(edge, (tri_id, i));
+			}
+			created.push(tri_id);
+		}
+		// link the new triangles to each other and to the triangles around
+		// the hole, a boundary edge no longer used sits on the hull
+		for (a, b, outside) in link_edges.iter() {
+			if let Some(outside_id) = outside {
+				let new_id = new_edges.get(&(*a, *b)).map(|(tri_id, _)| *tri_id);
+				self.replace_neighbour(*outside_id, (*b, *a), new_id);
+				self.vertex_triangles.insert(*a, *outside_id);
+				self.vertex_triangles.insert(*b, *outside_id);
+			}
+		}
+		let links: BTreeMap<(usize, usize), Option<usize>> =
+			link_edges.iter().map(|(a, b, o)| ((*a, *b), *o)).collect();
+		for ((a, b), (tri_id, i)) in new_edges.iter() {
+			let neighbour = match new_edges.get(&(*b, *a)) {
+				Some((other_id, _)) => Some(*other_id),
+				None => links.get(&(*a, *b)).copied().flatten(),
+			};
+			if let Some(n) = self.neighbours.get_mut(tri_id) {
+				n[*i] = neighbour;
+			}
+			self.vertex_triangles.insert(*a, *tri_id);
+		}
+		// the hole is filled with Delaunay triangles, flipping only guards
+		// against ties between cocircular vertices
+		let mut removed = star;
+		let pending = created
+			.iter()
+			.flat_map(|tri_id| (0..3).map(move |i| (*tri_id, i)))
+			.collect();
+		self.legalise_edges(pending, &mut removed, &mut created);
+		if let Some((u, w)) = parent_segment {
+			let before: BTreeSet<usize> = self.triangles.keys().copied().collect();
+			// the segment was an edge before the vertex split it so it cannot
+			// cross another constrained edge, the hole has already been filled
+			// so a failure here cannot be reported without a broken mesh
+			self.insert_constraint(u, w)
+				.expect("the restored segment was an edge before the split");
+			for tri_id in before.iter() {
+				if !self.triangles.contains_key(tri_id) {
+					match created.iter().position(|id| id == tri_id) {
+						Some(index) => {
+							created.remove(index);
+						}
+						None => removed.push(*tri_id),
+					}
+				}
+			}
+			created.extend(
+				self.triangles
+					.keys()
+					.filter(|tri_id| !before.contains(tri_id))
+					.copied(),
 			);
+		}
+		Ok(RemoveResult {
+			vertex_id,
+			removed_triangles: removed,
+			created_triangles: created,
+		})
+	}
+	/// Find the outer hull of the triangulation as vertex IDs and edges in
+	/// anti-clockwise order, starting from the lowest vertex (the left most
+	/// if more than one share the lowest position).
+	///
+	/// Vertices that sit along a straight section of the hull are included.
+	/// After [Delaunay2d::discard_exterior_triangles] this is the outer
+	/// boundary of the remaining triangles which may not be convex
+	pub fn convex_hull(&self) -> ConvexHull2d {
+		let is_lower = |a: usize, b: usize| {
+			let (a, b) = (self.vertex_lookup[&a], self.vertex_lookup[&b]);
+			(a.y, a.x) < (b.y, b.x)
+		};
+		// the lowest vertex on an edge without a neighbour is always on the
+		// outer hull rather than around a hole
+		let mut start: Option<(usize, usize)> = None;
+		for (tri_id, neighbours) in self.neighbours.iter() {
+			let ids = self.triangles[tri_id].get_vertex_ids();
+			for (i, neighbour) in neighbours.iter().enumerate() {
+				if neighbour.is_none()
+					&& start.is_none_or(|(t, j)| {
+						is_lower(ids[i], self.triangles[&t].get_vertex_ids()[j])
+					}) {
+					start = Some((*tri_id, i));
+				}
+			}
+		}
+		let Some(start) = start else {
+			return ConvexHull2d::default();
+		};
+		let mut vertex_ids = vec![];
+		let mut current = start;
+		loop {
+			let (tri_id, i) = current;
+			vertex_ids.push(self.triangles[&tri_id].get_vertex_ids()[i]);
+			current = self.step_along_hull(current, true);
+			if current == start || vertex_ids.len() > self.vertex_lookup.len() {
+				break;
+			}
+		}
+		let edges = vertex_ids
+			.iter()
+			.zip(vertex_ids.iter().cycle().skip(1))
+			.map(|(a, b)| EdgeNode2d::new(*a, *b))
+			.collect();
+		ConvexHull2d { vertex_ids, edges }
+	}
+	/// Find where a point sits within the triangulation, either inside a
+	/// triangle, on an edge or on a vertex along with the barycentric weights
+	/// of the vertices around it. A point outside of the triangulation
+	/// reports the nearest hull edge.
+	///
+	/// The point is found by walking across the triangulation from its first
+	/// triangle, use [Delaunay2d::locate_from] to start the walk nearer to the
+	/// point. `None` if the point has a non-finite coordinate or there are no
+	/// triangles
+	pub fn locate(&self, point: Vec2) -> Option<Location> {
+		let start = *self.triangles.keys().next()?;
+		self.locate_from(point, start)
+	}
+	/// Find where a point sits within the triangulation, as with
+	/// [Delaunay2d::locate], walking from the `start_triangle`.
+	///
+	/// When locating many points close to each other starting from the
+	/// triangle of the previous [Location] keeps each walk short
+	pub fn locate_from(&self, point: Vec2, start_triangle: usize) -> Option<Location> {
+		if !point.is_finite() || self.triangles.is_empty() {
 			return None;
 		}
-		//TODO ensure no dupciates in points?
-		// find the dimensions of a plane that the points occupy
-		let (minimum_world_dimensions, maximum_world_dimensions) = compute_dimension_bounds(points);
-		// compute the vertices of a super triangle which encompassess all the points
-		let super_vertices =
-			compute_super_triangle(&minimum_world_dimensions, &maximum_world_dimensions);
-		// store vertices with a unique id
-		let mut vertex_lookup = BTreeMap::from([
-			(0, super_vertices[0]),
-			(1, super_vertices[1]),
-			(2, super_vertices[2]),
-		]);
-		// store a node representation of the triangle
-		let mut triangles = BTreeSet::from([TriangleNode2d::new(0, 1, 2)]);
-
-		// add each point at a time to the triangulation
-		for point in points {
-			// store the point with a unique ID
-			let new_point_id = vertex_lookup.len();
-			vertex_lookup.insert(new_point_id, *point);
-			// record triangles that are not delaunay
-			let bad_triangles = find_bad_triangles(point, &triangles, &vertex_lookup);
-
-			//TODO need to check for empty bad triangles?
-			//TODO in theory it means a point is duplicate in dataset
-			//TODO so we'd want to ignore it anyway...
-
-			if !bad_triangles.is_empty() {
-				// remove any bad triangles from the triangle list
-				triangles.retain(|t| !bad_triangles.contains(t));
-
-				// we have a polyhedral hole around the point,
-				// by using the known bad triangles we can join the point to
-				// each unique edge, thereby creating new triangles
-				// that can undergo triangulation
-				//
-				// store the edges of bad triangles
-				let mut edges = vec![];
-				// store duplicate edges
-				let mut duplicate_edges = vec![];
-
-				for bad in bad_triangles.iter() {
-					let bad_edges = bad.get_edges();
-					for bad_e in bad_edges {
-						if !edges.contains(&bad_e) {
-							edges.push(bad_e);
-						} else {
-							duplicate_edges.push(bad_e);
+		match self.walk_to_point(start_triangle, point) {
+			Ok(triangle_id) => {
+				let vertex_ids = *self.triangles[&triangle_id].get_vertex_ids();
+				let [a, b, c] = vertex_ids.map(|id| self.vertex_lookup[&id]);
+				if let Some(vertex_id) = vertex_ids
+					.iter()
+					.find(|id| self.vertex_lookup[*id] == point)
+				{
+					return Some(Location::Vertex {
+						triangle_id,
+						vertex_id: *vertex_id,
+					});
+				}
+				// the area opposite each vertex, zero when the point sits on
+				// the edge opposite the vertex
+				let areas = [
+					orient2d(b, c, point),
+					orient2d(c, a, point),
+					orient2d(a, b, point),
+				];
+				if let Some(i) = (0..3).find(|i| areas[(i + 2) % 3] == 0.0) {
+					let (start, end) = (vertex_ids[i], vertex_ids[(i + 1) % 3]);
+					let weights =
+						edge_weights(self.vertex_lookup[&start], self.vertex_lookup[&end], point);
+					return Some(Location::Edge {
+						triangle_id,
+						edge: EdgeNode2d::new(start, end),
+						weights,
+					});
+				}
+				let total: f64 = areas.iter().sum();
+				Some(Location::Triangle {
+					triangle_id,
+					vertex_ids,
+					weights: areas.map(|area| (area / total) as f32),
+				})
+			}
+			Err((hull_triangle, hull_edge)) => {
+				// the nearest position on the hull is always on an edge the
+				// point can see
+				let mut nearest = None;
+				for (tri_id, i) in self.find_visible_hull_edges(hull_triangle, hull_edge, point) {
+					let ids = self.triangles[&tri_id].get_vertex_ids();
+					let (start, end) = (ids[i], ids[(i + 1) % 3]);
+					let weights =
+						edge_weights(self.vertex_lookup[&start], self.vertex_lookup[&end], point);
+					let position = self.vertex_lookup[&start].as_dvec2() * f64::from(weights[0])
+						+ self.vertex_lookup[&end].as_dvec2() * f64::from(weights[1]);
+					let distance = position.distance_squared(point.as_dvec2());
+					if nearest.is_none_or(|(d, _)| distance < d) {
+						nearest = Some((
+							distance,
+							Location::OutsideHull {
+								triangle_id: tri_id,
+								edge: EdgeNode2d::new(start, end),
+								weights,
+							},
+						));
+					}
+				}
+				nearest.map(|(_, location)| location)
+			}
+		}
+	}
+	/// Get the IDs of the triangles across each edge of a triangle, in the
+	/// same order as [TriangleNode2d::get_edges]. An edge on the outer hull
+	/// has no neighbour and is `None`
+	pub fn triangle_neighbours(&self, triangle_id: usize) -> Option<&[Option<usize>; 3]> {
+		self.neighbours.get(&triangle_id)
+	}
+	/// Get the IDs of every triangle that uses a vertex, in anti-clockwise
+	/// order around the vertex. If the vertex sits on the outer hull the first
+	/// and last triangles each have an edge along the hull
+	pub fn vertex_star(&self, vertex_id: usize) -> Vec<usize> {
+		let Some(start) = self.vertex_triangles.get(&vertex_id) else {
+			return vec![];
+		};
+		// rotate clockwise until the hull is reached (or we arrive back at
+		// the start for an interior vertex) so the walk anti-clockwise visits
+		// every triangle
+		let mut first = *start;
+		while let Some(previous) = self.rotate_about_vertex(first, vertex_id, false) {
+			if previous == *start {
+				break;
+			}
+			first = previous;
+		}
+		let mut star = vec![first];
+		let mut current = first;
+		while let Some(next) = self.rotate_about_vertex(current, vertex_id, true) {
+			if next == first {
+				break;
+			}
+			star.push(next);
+			current = next;
+		}
+		star
+	}
+	/// Get the IDs of the triangles either side of an edge as
+	/// `[left, right]` when travelling from vertex a to vertex b of the edge.
+	/// A hull edge only has a triangle on one side, both are `None` if the
+	/// edge is not part of the triangulation
+	pub fn edge_triangles(&self, edge: &EdgeNode2d) -> [Option<usize>; 2] {
+		let a = edge.get_vertex_a_id();
+		let b = edge.get_vertex_b_id();
+		let mut sides = [None, None];
+		for tri_id in self.vertex_star(a) {
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			let i = ids.iter().position(|id| *id == a).unwrap();
+			// as vertices are anti-clockwise, b following a means the
+			// triangle is to the left of a->b
+			if ids[(i + 1) % 3] == b {
+				sides[0] = Some(tri_id);
+			} else if ids[(i + 2) % 3] == b {
+				sides[1] = Some(tri_id);
+			}
+		}
+		sides
+	}
+	/// Refine the triangulation into a quality mesh with Ruppert's algorithm,
+	/// new vertices are inserted until every triangle meets the
+	/// [RefinementBounds].
+	///
+	/// Constrained edges and hull edges are segments that must be kept. A
+	/// segment with a vertex inside the circle whose diameter is the segment
+	/// is encroached and split in two. Each triangle with too small an angle
+	/// or too large an area gains a vertex at its circumcentre, unless the
+	/// circumcentre would encroach a segment in which case the segment is
+	/// split instead. A split constrained edge is replaced by its two halves.
+	///
+	/// Fails if the bounds are invalid or the triangulation is a collinear
+	/// chain
+	pub fn refine(&mut self, bounds: &RefinementBounds) -> Result<RefineResult, MosaicError> {
+		if self.is_collinear() {
+			return Err(MosaicError::CollinearPoints);
+		}
+		if !(0.0..=MAXIMUM_REFINEMENT_ANGLE).contains(&bounds.minimum_angle) {
+			return Err(MosaicError::InvalidRefinement(
+				"minimum angle is out of range",
+			));
+		}
+		let is_valid_area = |area: f32| area.is_finite() && area > 0.0;
+		if bounds.maximum_area.is_some_and(|area| !is_valid_area(area)) {
+			return Err(MosaicError::InvalidRefinement(
+				"maximum area must be positive",
+			));
+		}
+		let mut regions = vec![];
+		for (polygon, area) in bounds.region_maximum_areas.iter() {
+			if !is_valid_area(*area) {
+				return Err(MosaicError::InvalidRefinement(
+					"maximum area must be positive",
+				));
+			}
+			if polygon.len() < 3 || polygon.iter().any(|p| !p.is_finite()) {
+				return Err(MosaicError::InvalidRefinement(
+					"region needs at least three finite vertices",
+				));
+			}
+			let mut polygon = polygon.clone();
+			let twice_area: f32 = polygon
+				.iter()
+				.zip(polygon.iter().cycle().skip(1))
+				.map(|(a, b)| a.perp_dot(*b))
+				.sum();
+			if twice_area < 0.0 {
+				polygon.reverse();
+			}
+			let edges = polygon
+				.iter()
+				.zip(polygon.iter().cycle().skip(1))
+				.map(|(a, b)| (*a, *b))
+				.collect();
+			regions.push((edges, *area as f64));
+		}
+		let limits = QualityLimits {
+			radius_edge_ratio: match (bounds.minimum_angle as f64).to_radians().sin() {
+				sine if sine > 0.0 => 1.0 / (2.0 * sine),
+				_ => f64::INFINITY,
+			},
+			maximum_area: bounds.maximum_area.map(f64::from),
+			regions,
+		};
+		// each vertex added along a segment and the ends of the segment it
+		// was first split from
+		let mut origins = BTreeMap::new();
+		let mut added_vertices = vec![];
+		let mut segments: Vec<(usize, usize)> = self.constrained_edges.iter().copied().collect();
+		for (tri_id, neighbours) in self.neighbours.iter() {
+			let ids = self.triangles[tri_id].get_vertex_ids();
+			for i in (0..3).filter(|i| neighbours[*i].is_none()) {
+				segments.push((ids[i], ids[(i + 1) % 3]));
+			}
+		}
+		let mut poor: VecDeque<usize> = self.triangles.keys().copied().collect();
+		loop {
+			// encroached segments are split before any triangle is improved
+			if let Some((a, b)) = segments.pop() {
+				if self.is_segment(a, b) && self.is_segment_encroached(a, b) {
+					if added_vertices.len() >= bounds.vertex_limit {
+						break;
+					}
+					if let Some((vertex_id, created)) = self.split_segment(a, b, &mut origins) {
+						added_vertices.push(vertex_id);
+						self.queue_refinement(&created, &mut segments, &mut poor);
+					}
+				}
+				continue;
+			}
+			let Some(tri_id) = poor.pop_front() else {
+				return Ok(RefineResult {
+					added_vertices,
+					is_complete: true,
+				});
+			};
+			if !self.triangles.contains_key(&tri_id)
+				|| !self.is_triangle_poor(tri_id, &limits, &origins)
+			{
+				continue;
+			}
+			let [a, b, c] = self.triangles[&tri_id]
+				.get_vertex_ids()
+				.map(|id| self.vertex_lookup[&id].as_dvec2());
+			let Some(centre) = circumcentre(a, b, c)
+				.map(|centre| centre.as_vec2())
+				.filter(|centre| centre.is_finite())
+			else {
+				continue;
+			};
+			let encroached = match self.walk_within_segments(tri_id, centre) {
+				Ok(containing) => {
+					let ids = self.triangles[&containing].get_vertex_ids();
+					if ids.iter().any(|id| self.vertex_lookup[id] == centre) {
+						// too small to improve at f32 precision
+						continue;
+					}
+					let encroached = self.find_encroached_segments(containing, centre);
+					if encroached.is_empty() {
+						if added_vertices.len() >= bounds.vertex_limit {
+							break;
+						}
+						let vertex_id = self.point_count;
+						self.point_count += 1;
+						self.vertex_lookup.insert(vertex_id, centre);
+						let (_, created) = self.insert_vertex(vertex_id, containing);
+						added_vertices.push(vertex_id);
+						self.queue_refinement(&created, &mut segments, &mut poor);
+						continue;
+					}
+					encroached
+				}
+				Err(segment) => vec![segment],
+			};
+			let mut is_split = false;
+			for (a, b) in encroached {
+				if added_vertices.len() >= bounds.vertex_limit {
+					break;
+				}
+				if self.is_segment(a, b)
+					&& let Some((vertex_id, created)) = self.split_segment(a, b, &mut origins)
+				{
+					added_vertices.push(vertex_id);
+					self.queue_refinement(&created, &mut segments, &mut poor);
+					is_split = true;
+				}
+			}
+			// a triangle that survives the splits is tried again
+			if is_split {
+				poor.push_back(tri_id);
+			}
+		}
+		Ok(RefineResult {
+			added_vertices,
+			is_complete: false,
+		})
+	}
+	/// From a triangle find the next triangle around a vertex, either
+	/// anti-clockwise or clockwise. `None` if the hull is reached
+	fn rotate_about_vertex(
+		&self,
+		triangle_id: usize,
+		vertex_id: usize,
+		anti_clockwise: bool,
+	) -> Option<usize> {
+		let ids = self.triangles.get(&triangle_id)?.get_vertex_ids();
+		let i = ids.iter().position(|id| *id == vertex_id)?;
+		// the edge leading into the vertex is shared with the next triangle
+		// anti-clockwise, the edge leading out with the next clockwise
+		let edge_index = if anti_clockwise { (i + 2) % 3 } else { i };
+		self.neighbours.get(&triangle_id)?[edge_index]
+	}
+	/// Walk across the triangulation from the `start` triangle towards
+	/// `point` and return the triangle that contains it. A point on an edge or
+	/// vertex may return any triangle touching it.
+	///
+	/// If the point lies outside the triangulation the error holds the hull
+	/// triangle and the index of the hull edge that the walk stepped over
+	fn walk_to_point(&self, start: usize, point: Vec2) -> Result<usize, (usize, usize)> {
+		let mut current = if self.triangles.contains_key(&start) {
+			start
+		} else {
+			// an empty triangulation has no hull to step over
+			*self.triangles.keys().next().ok_or((start, 0))?
+		};
+		// a visibility walk always terminates in a Delaunay triangulation, the
+		// step limit only guards against a broken triangulation
+		for step in 0..=self.triangles.len() {
+			let ids = self.triangles[&current].get_vertex_ids();
+			let mut next = None;
+			// vary the first edge tested on each step so the walk cannot cycle
+			for offset in 0..3 {
+				let i = (step + offset) % 3;
+				let a = self.vertex_lookup[&ids[i]];
+				let b = self.vertex_lookup[&ids[(i + 1) % 3]];
+				// a point to the right of an edge is beyond it
+				if orient2d(a, b, point) < 0.0 {
+					next = Some(self.neighbours[&current][i].ok_or((current, i))?);
+					break;
+				}
+			}
+			match next {
+				Some(tri_id) => current = tri_id,
+				None => return Ok(current),
+			}
+		}
+		warn!(
+			"Walk to point {} did not terminate, scanning instead",
+			point
+		);
+		let mut hull_edge = (current, 0);
+		for (tri_id, tri) in self.triangles.iter() {
+			let ids = tri.get_vertex_ids();
+			let mut is_within = true;
+			for i in 0..3 {
+				let a = self.vertex_lookup[&ids[i]];
+				let b = self.vertex_lookup[&ids[(i + 1) % 3]];
+				if orient2d(a, b, point) < 0.0 {
+					is_within = false;
+					if self.neighbours[tri_id][i].is_none() {
+						hull_edge = (*tri_id, i);
+					}
+				}
+			}
+			if is_within {
+				return Ok(*tri_id);
+			}
+		}
+		Err(hull_edge)
+	}
+	/// Is the `point` strictly within the circumcircle of a triangle, uses the
+	/// exact [incircle] predicate
+	fn is_point_within_circumcircle(&self, triangle_id: usize, point: Vec2) -> bool {
+		let [a, b, c] = self.triangles[&triangle_id]
+			.get_vertex_ids()
+			.map(|id| self.vertex_lookup[&id]);
+		// triangles are stored anti-clockwise so a positive result is inside
+		incircle(a, b, c, point) > 0.0
+	}
+	/// Insert a vertex already stored in the vertex lookup into the
+	/// triangulation, the walk to find it begins from the `start` triangle.
+	///
+	/// Triangles whose circumcircle contains the vertex are found by spreading
+	/// out from the triangle containing it through the adjacency, they form a
+	/// cavity that is removed and refilled with triangles joining the vertex
+	/// to each edge of the cavity boundary. A vertex outside of the
+	/// triangulation is instead joined to each hull edge it can see and edges
+	/// are flipped until the triangulation is Delaunay again.
+	///
+	/// Returns the IDs of the removed and created triangles
+	fn insert_vertex(&mut self, vertex_id: usize, start: usize) -> (Vec<usize>, Vec<usize>) {
+		let point = self.vertex_lookup[&vertex_id];
+		match self.walk_to_point(start, point) {
+			Ok(containing) => {
+				// grow the cavity, recording the edges around its boundary
+				// along with the triangle on the far side of each
+				let mut cavity = vec![containing];
+				let mut in_cavity = BTreeSet::from([containing]);
+				let mut boundary = vec![];
+				// the cavity cannot spread across a constrained edge unless the
+				// vertex sits on it, in which case the edge is split in two
+				let mut split_edges = vec![];
+				let mut next = 0;
+				while let Some(tri_id) = cavity.get(next).copied() {
+					next += 1;
+					let ids = *self.triangles[&tri_id].get_vertex_ids();
+					let neighbours = self.neighbours[&tri_id];
+					for (i, neighbour) in neighbours.iter().enumerate() {
+						let (a, b) = (ids[i], ids[(i + 1) % 3]);
+						match neighbour {
+							Some(n) if in_cavity.contains(n) => {}
+							Some(n)
+								if self.is_point_within_circumcircle(*n, point)
+									&& (!self.is_constrained(a, b)
+										|| orient2d(
+											self.vertex_lookup[&a],
+											self.vertex_lookup[&b],
+											point,
+										) == 0.0) =>
+							{
+								if self.is_constrained(a, b) {
+									split_edges.push((a, b));
+								}
+								in_cavity.insert(*n);
+								cavity.push(*n);
+							}
+							None if self.is_constrained(a, b)
+								&& orient2d(
+									self.vertex_lookup[&a],
+									self.vertex_lookup[&b],
+									point,
+								) == 0.0 =>
+							{
+								// a hull edge the vertex sits on
+								split_edges.push((a, b));
+								boundary.push((a, b, None));
+							}
+							_ => boundary.push((a, b, *neighbour)),
+						}
+					}
+				}
+				for (a, b) in split_edges {
+					self.constrained_edges.remove(&(a.min(b), a.max(b)));
+					for end in [a, b] {
+						self.constrained_edges
+							.insert((end.min(vertex_id), end.max(vertex_id)));
+					}
+				}
+				let created = self.join_edges_to_vertex(vertex_id, boundary);
+				for tri_id in cavity.iter() {
+					self.triangles.remove(tri_id);
+					self.neighbours.remove(tri_id);
+				}
+				(cavity, created)
+			}
+			Err((hull_triangle, hull_edge)) => {
+				let visible = self.find_visible_hull_edges(hull_triangle, hull_edge, point);
+				// each visible edge is reversed so the new triangles on the
+				// outside of the hull are anti-clockwise
+				let edges = visible
+					.iter()
+					.map(|(tri_id, i)| {
+						let ids = self.triangles[tri_id].get_vertex_ids();
+						(ids[(i + 1) % 3], ids[*i], Some(*tri_id))
+					})
+					.collect();
+				let mut created = self.join_edges_to_vertex(vertex_id, edges);
+				let mut removed = vec![];
+				// the old hull edges may no longer be Delaunay
+				let pending = created.iter().map(|tri_id| (*tri_id, 0)).collect();
+				self.legalise_edges(pending, &mut removed, &mut created);
+				(removed, created)
+			}
+		}
+	}
+	/// Create a triangle joining each edge to a vertex and link them to each
+	/// other and to the triangle on the far side of each edge. Edges are
+	/// `(a, b, far side triangle)` and must be anti-clockwise about the vertex.
+	///
+	/// An edge on the hull that the vertex sits along is skipped as it cannot
+	/// form a triangle, the vertex instead becomes part of the hull.
+	///
+	/// Returns the IDs of the new triangles
+	fn join_edges_to_vertex(
+		&mut self,
+		vertex_id: usize,
+		edges: Vec<(usize, usize, Option<usize>)>,
+	) -> Vec<usize> {
+		let point = self.vertex_lookup[&vertex_id];
+		let mut created = Vec::with_capacity(edges.len());
+		// new triangles keyed by the first and second vertex of their edge
+		// so they can be linked to each other
+		let mut starting_at = BTreeMap::new();
+		let mut ending_at = BTreeMap::new();
+		let edges = edges.into_iter().filter(|(a, b, outside)| {
+			outside.is_some()
+				|| orient2d(self.vertex_lookup[a], self.vertex_lookup[b], point) != 0.0
+		});
+		for (a, b, outside) in edges.collect::<Vec<_>>() {
+			let tri_id = self.take_triangle_id();
+			self.triangles
+				.insert(tri_id, TriangleNode2d::new(a, b, vertex_id));
+			self.neighbours.insert(tri_id, [outside, None, None]);
+			// point the triangle beyond the edge at the new triangle
+			if let Some(outside_id) = outside {
+				self.replace_neighbour(outside_id, (b, a), Some(tri_id));
+			}
+			starting_at.insert(a, tri_id);
+			ending_at.insert(b, tri_id);
+			created.push(tri_id);
+		}
+		for tri_id in created.iter() {
+			let [a, b, _] = *self.triangles[tri_id].get_vertex_ids();
+			if let Some(n) = self.neighbours.get_mut(tri_id) {
+				n[1] = starting_at.get(&b).copied();
+				n[2] = ending_at.get(&a).copied();
+			}
+			for id in [a, b, vertex_id] {
+				self.vertex_triangles.insert(id, *tri_id);
+			}
+		}
+		created
+	}
+	/// From a hull edge that `point` lies beyond follow the hull in both
+	/// directions collecting every hull edge the point can see, the edges are
+	/// `(triangle ID, edge index)` ordered clockwise along the hull
+	fn find_visible_hull_edges(
+		&self,
+		triangle_id: usize,
+		edge_index: usize,
+		point: Vec2,
+	) -> Vec<(usize, usize)> {
+		let is_visible = |(tri_id, i): (usize, usize)| {
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			let a = self.vertex_lookup[&ids[i]];
+			let b = self.vertex_lookup[&ids[(i + 1) % 3]];
+			orient2d(a, b, point) < 0.0
+		};
+		let start = (triangle_id, edge_index);
+		let mut visible = vec![start];
+		// hull edges before the starting edge
+		let mut current = start;
+		loop {
+			current = self.step_along_hull(current, false);
+			if current == start || !is_visible(current) {
+				break;
+			}
+			visible.push(current);
+		}
+		visible.reverse();
+		// hull edges after the starting edge
+		let mut current = start;
+		loop {
+			current = self.step_along_hull(current, true);
+			if current == start || visible.contains(&current) || !is_visible(current) {
+				break;
+			}
+			visible.insert(0, current);
+		}
+		visible
+	}
+	/// From a hull edge, given as `(triangle ID, edge index)`, find the next
+	/// hull edge anti-clockwise around the hull or the previous one
+	pub(crate) fn step_along_hull(
+		&self,
+		hull_edge: (usize, usize),
+		anti_clockwise: bool,
+	) -> (usize, usize) {
+		let (mut tri_id, edge_index) = hull_edge;
+		let ids = self.triangles[&tri_id].get_vertex_ids();
+		// the next edge starts where this one ends, the previous edge ends
+		// where this one starts
+		let vertex = if anti_clockwise {
+			ids[(edge_index + 1) % 3]
+		} else {
+			ids[edge_index]
+		};
+		while let Some(next) = self.rotate_about_vertex(tri_id, vertex, !anti_clockwise) {
+			tri_id = next;
+		}
+		let ids = self.triangles[&tri_id].get_vertex_ids();
+		let i = ids.iter().position(|id| *id == vertex).unwrap();
+		if anti_clockwise {
+			(tri_id, i)
+		} else {
+			(tri_id, (i + 2) % 3)
+		}
+	}
+	/// Flip each `(triangle ID, edge index)` edge whose neighbouring triangles
+	/// are not Delaunay, and in turn the edges around any flipped edge, until
+	/// every edge is Delaunay. Constrained edges are never flipped.
+	///
+	/// Flipped triangles are replaced by new IDs, the IDs of replaced
+	/// triangles are added to `removed` unless they were in `created` in which
+	/// case they are taken out of it. Returns the number of flips made
+	fn legalise_edges(
+		&mut self,
+		mut pending: Vec<(usize, usize)>,
+		removed: &mut Vec<usize>,
+		created: &mut Vec<usize>,
+	) -> usize {
+		let mut flips = 0;
+		while let Some((tri_id, edge_index)) = pending.pop() {
+			let Some(other_id) = self
+				.neighbours
+				.get(&tri_id)
+				.and_then(|neighbours| neighbours[edge_index])
+			else {
+				continue;
+			};
+			let Some([first, second]) = self.flip_if_illegal(tri_id, edge_index) else {
+				continue;
+			};
+			flips += 1;
+			// the edges around the new pair of triangles may now be illegal
+			pending.extend([(first, 0), (first, 1), (second, 0), (second, 1)]);
+			for old in [tri_id, other_id] {
+				if let Some(i) = created.iter().position(|id| *id == old) {
+					created.swap_remove(i);
+				} else {
+					removed.push(old);
+				}
+			}
+			created.extend([first, second]);
+		}
+		flips
+	}
+	/// Flip an edge of a triangle if it is not Delaunay, the vertex across it
+	/// sits within the circumcircle of the triangle. Constrained and hull
+	/// edges are never flipped, nor are edges of triangles that no longer
+	/// exist.
+	///
+	/// Returns the IDs of the two new triangles if the edge was flipped
+	fn flip_if_illegal(&mut self, triangle_id: usize, edge_index: usize) -> Option<[usize; 2]> {
+		let ids = self.triangles.get(&triangle_id)?.get_vertex_ids();
+		let other_id = self.neighbours[&triangle_id][edge_index]?;
+		if self.is_constrained(ids[edge_index], ids[(edge_index + 1) % 3]) {
+			return None;
+		}
+		let other_ids = self.triangles[&other_id].get_vertex_ids();
+		let opposite = other_ids.iter().find(|id| !ids.contains(id))?;
+		if !self.is_point_within_circumcircle(triangle_id, self.vertex_lookup[opposite]) {
+			return None;
+		}
+		Some(self.flip_edge(triangle_id, edge_index))
+	}
+	/// Flip an edge shared by two triangles so that it joins the vertices
+	/// opposite it instead, the two triangles are replaced by two new ones
+	/// whose IDs are returned.
+	///
+	/// The quadrilateral formed by the two triangles must be convex
+	fn flip_edge(&mut self, triangle_id: usize, edge_index: usize) -> [usize; 2] {
+		let ids = *self.triangles[&triangle_id].get_vertex_ids();
+		let neighbours = self.neighbours[&triangle_id];
+		let other_id = neighbours[edge_index].unwrap();
+		let other_ids = *self.triangles[&other_id].get_vertex_ids();
+		let other_neighbours = self.neighbours[&other_id];
+		// the edge runs a->b in the triangle and b->a in the other one
+		let a = ids[edge_index];
+		let b = ids[(edge_index + 1) % 3];
+		let c = ids[(edge_index + 2) % 3];
+		let j = other_ids.iter().position(|id| *id == b).unwrap();
+		let d = other_ids[(j + 2) % 3];
+		let across_bc = neighbours[(edge_index + 1) % 3];
+		let across_ca = neighbours[(edge_index + 2) % 3];
+		let across_ad = other_neighbours[(j + 1) % 3];
+		let across_db = other_neighbours[(j + 2) % 3];
+		for id in [triangle_id, other_id] {
+			self.triangles.remove(&id);
+			self.neighbours.remove(&id);
+		}
+		let first = self.take_triangle_id();
+		let second = self.take_triangle_id();
+		self.triangles.insert(first, TriangleNode2d::new(c, a, d));
+		self.neighbours
+			.insert(first, [across_ca, across_ad, Some(second)]);
+		self.triangles.insert(second, TriangleNode2d::new(d, b, c));
+		self.neighbours
+			.insert(second, [across_db, across_bc, Some(first)]);
+		// point the surrounding triangles at the new ones
+		for (across, edge, new_id) in [
+			(across_ca, (c, a), first),
+			(across_ad, (a, d), first),
+			(across_db, (d, b), second),
+			(across_bc, (b, c), second),
+		] {
+			if let Some(across) = across {
+				self.replace_neighbour(across, (edge.1, edge.0), Some(new_id));
+			}
+		}
+		for id in [a, c, d] {
+			self.vertex_triangles.insert(id, first);
+		}
+		self.vertex_triangles.insert(b, second);
+		[first, second]
+	}
+	/// Link the edge `a->b` of a triangle to a new neighbouring triangle, or
+	/// to nothing if the edge is now on the hull
+	fn replace_neighbour(
+		&mut self,
+		triangle_id: usize,
+		edge: (usize, usize),
+		neighbour: Option<usize>,
+	) {
+		let Some(tri) = self.triangles.get(&triangle_id) else {
+			return;
+		};
+		let ids = tri.get_vertex_ids();
+		if let Some(i) = (0..3).find(|i| ids[*i] == edge.0 && ids[(i + 1) % 3] == edge.1)
+			&& let Some(n) = self.neighbours.get_mut(&triangle_id)
+		{
+			n[i] = neighbour;
+		}
+	}
+	/// Triangulate the hole left by removing a vertex, the `polygon` vertices
+	/// surround the hole anti-clockwise. An open polygon is the chain left
+	/// by removing a hull vertex, the hole is then the area between the
+	/// chain and its convex hull that the removed triangles covered.
+	///
+	/// Each step cuts off an ear, three consecutive vertices forming an
+	/// anti-clockwise triangle, whose circumcircle holds no other polygon
+	/// vertex, these are exactly the Delaunay triangles of the hole. The ear
+	/// of an open polygon must not reach past the removed vertex, otherwise
+	/// it could cover a notch or hole the triangulation does not. Returns the
+	/// triangles along with the polygon vertices left once no more ears can
+	/// be cut off
+	fn triangulate_hole(
+		&self,
+		polygon: &[usize],
+		removed_vertex: usize,
+		is_closed: bool,
+	) -> (Vec<[usize; 3]>, Vec<usize>) {
+		let removed = self.vertex_lookup[&removed_vertex];
+		let mut polygon = polygon.to_vec();
+		let mut triangles = vec![];
+		while polygon.len() >= 3 {
+			let n = polygon.len();
+			let ear_at = |i: usize| [polygon[i], polygon[(i + 1) % n], polygon[(i + 2) % n]];
+			let ear_count = if is_closed { n } else { n - 2 };
+			let ears: Vec<usize> = (0..ear_count)
+				.filter(|i| {
+					let [a, b, c] = ear_at(*i).map(|id| self.vertex_lookup[&id]);
+					orient2d(a, b, c) > 0.0 && (is_closed || orient2d(a, c, removed) >= 0.0)
+				})
+				.collect();
+			let is_empty = |i: &&usize, within: &dyn Fn(Vec2, Vec2, Vec2, Vec2) -> bool| {
+				let ear = ear_at(**i);
+				let [a, b, c] = ear.map(|id| self.vertex_lookup[&id]);
+				polygon
+					.iter()
+					.filter(|id| !ear.contains(id))
+					.all(|id| !within(a, b, c, self.vertex_lookup[id]))
+			};
+			// prefer a Delaunay ear, otherwise any ear that holds no other
+			// vertex is valid and later flipping makes it Delaunay
+			let Some(ear) =
+				ears.iter()
+					.find(|i| is_empty(i, &|a, b, c, p| incircle(a, b, c, p) > 0.0))
+					.or_else(|| {
+						ears.iter().find(|i| {
+							is_empty(i, &|a, b, c, p| {
+								orient2d(a, b, p) >= 0.0
+									&& orient2d(b, c, p) >= 0.0 && orient2d(c, a, p) >= 0.0
+							})
+						})
+					})
+			else {
+				break;
+			};
+			triangles.push(ear_at(*ear));
+			polygon.remove((ear + 1) % n);
+		}
+		(triangles, polygon)
+	}
+	/// Whether the edge between two vertices is constrained
+	fn is_constrained(&self, a: usize, b: usize) -> bool {
+		self.constrained_edges.contains(&(a.min(b), a.max(b)))
+	}
+	/// Follow the segment from vertex `a` towards vertex `b` recording each
+	/// edge it crosses as `(right, left)` vertex IDs relative to the direction
+	/// of the segment. The trace ends at `b` or at a vertex the segment passes
+	/// through, which is returned alongside the crossed edges.
+	///
+	/// Fails if the segment crosses a constrained edge or leaves the
+	/// triangulation
+	fn trace_segment(
+		&self,
+		a: usize,
+		b: usize,
+	) -> Result<(Vec<(usize, usize)>, usize), MosaicError> {
+		let start = self.vertex_lookup[&a];
+		let end = self.vertex_lookup[&b];
+		// find the triangle around `a` that the segment heads into
+		let mut entry = None;
+		for tri_id in self.vertex_star(a) {
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			let i = ids.iter().position(|id| *id == a).unwrap();
+			let (right, left) = (ids[(i + 1) % 3], ids[(i + 2) % 3]);
+			for vertex in [right, left] {
+				let position = self.vertex_lookup[&vertex];
+				if orient2d(start, end, position) == 0.0
+					&& (position - start).dot(end - start) > 0.0
+				{
+					return Ok((vec![], vertex));
+				}
+			}
+			if orient2d(start, end, self.vertex_lookup[&right]) < 0.0
+				&& orient2d(start, end, self.vertex_lookup[&left]) > 0.0
+			{
+				entry = Some((tri_id, right, left));
+				break;
+			}
+		}
+		let Some((mut tri_id, mut right, mut left)) = entry else {
+			return Err(MosaicError::InvalidConstraint(
+				"segment leaves the triangulation",
+			));
+		};
+		let mut crossed = vec![];
+		loop {
+			if self.is_constrained(right, left) {
+				return Err(MosaicError::InvalidConstraint("segments cross each other"));
+			}
+			crossed.push((right, left));
+			// within each triangle the segment leaves through the edge that
+			// runs right to left
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			let i = ids.iter().position(|id| *id == right).unwrap();
+			let Some(next) = self.neighbours[&tri_id][i] else {
+				return Err(MosaicError::InvalidConstraint(
+					"segment leaves the triangulation",
+				));
+			};
+			let opposite = *self.triangles[&next]
+				.get_vertex_ids()
+				.iter()
+				.find(|id| **id != right && **id != left)
+				.unwrap();
+			if opposite == b {
+				return Ok((crossed, b));
+			}
+			let side = orient2d(start, end, self.vertex_lookup[&opposite]);
+			if side == 0.0 {
+				return Ok((crossed, opposite));
+			} else if side > 0.0 {
+				left = opposite;
+			} else {
+				right = opposite;
+			}
+			tri_id = next;
+		}
+	}
+	/// Make the edge between two vertices part of the triangulation and mark it
+	/// as constrained, the segment between them must not pass through another
+	/// vertex or cross a constrained edge.
+	///
+	/// Edges crossing the segment are flipped until none remain, then the new
+	/// edges are flipped until the triangulation is Delaunay again
+	fn enforce_edge(&mut self, a: usize, b: usize) {
+		let Ok((crossed, _)) = self.trace_segment(a, b) else {
+			return;
+		};
+		let start = self.vertex_lookup[&a];
+		let end = self.vertex_lookup[&b];
+		let mut crossing: VecDeque<(usize, usize)> = crossed.into();
+		let mut new_edges = vec![];
+		while let Some((u, w)) = crossing.pop_front() {
+			let Some(tri_id) = self.edge_triangles(&EdgeNode2d::new(u, w))[0] else {
+				continue;
+			};
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			let i = ids.iter().position(|id| *id == u).unwrap();
+			let c = ids[(i + 2) % 3];
+			let Some(other_id) = self.neighbours[&tri_id][i] else {
+				continue;
+			};
+			let d = *self.triangles[&other_id]
+				.get_vertex_ids()
+				.iter()
+				.find(|id| **id != u && **id != w)
+				.unwrap();
+			let [pu, pw, pc, pd] = [u, w, c, d].map(|id| self.vertex_lookup[&id]);
+			// the edge can only be flipped when the two triangles form a
+			// convex quadrilateral, otherwise come back to it later
+			if do_segments_cross(pu, pw, pc, pd) {
+				self.flip_edge(tri_id, i);
+				if ![a, b].contains(&c)
+					&& ![a, b].contains(&d)
+					&& do_segments_cross(start, end, pc, pd)
+				{
+					crossing.push_back((c, d));
+				} else {
+					new_edges.push((c, d));
+				}
+			} else {
+				crossing.push_back((u, w));
+			}
+		}
+		self.constrained_edges.insert((a.min(b), a.max(b)));
+		let pending = new_edges
+			.iter()
+			.filter_map(|(c, d)| {
+				let tri_id = self.edge_triangles(&EdgeNode2d::new(*c, *d))[0]?;
+				let ids = self.triangles[&tri_id].get_vertex_ids();
+				let i = ids.iter().position(|id| id == c)?;
+				Some((tri_id, i))
+			})
+			.collect();
+		self.legalise_edges(pending, &mut vec![], &mut vec![]);
+	}
+	/// Whether the edge between two vertices is a segment that refinement
+	/// must keep, either a constrained edge or an edge on the hull
+	fn is_segment(&self, a: usize, b: usize) -> bool {
+		if self.is_constrained(a, b) {
+			return true;
+		}
+		matches!(
+			self.edge_triangles(&EdgeNode2d::new(a, b)),
+			[Some(_), None] | [None, Some(_)]
+		)
+	}
+	/// Whether the vertex opposite a segment in either of its triangles sits
+	/// within the diametral circle of the segment. In a constrained
+	/// triangulation no other vertex can encroach a segment unless one of
+	/// these does
+	fn is_segment_encroached(&self, a: usize, b: usize) -> bool {
+		let (pa, pb) = (self.vertex_lookup[&a], self.vertex_lookup[&b]);
+		self.edge_triangles(&EdgeNode2d::new(a, b))
+			.iter()
+			.flatten()
+			.filter_map(|tri_id| {
+				let ids = self.triangles[tri_id].get_vertex_ids();
+				ids.iter().find(|id| **id != a && **id != b).copied()
+			})
+			.any(|apex| is_within_diametral_circle(pa, pb, self.vertex_lookup[&apex]))
+	}
+	/// Whether a triangle has an angle smaller than allowed or an area larger
+	/// than allowed. A small angle between two segments is part of the shape
+	/// being triangulated and cannot be improved so is allowed, as is a small
+	/// angle opposite an edge joining two vertices that split segments
+	/// meeting at less than 60 degrees.
+	///
+	/// `origins` holds the ends of the segment each split vertex lies on
+	fn is_triangle_poor(
+		&self,
+		triangle_id: usize,
+		limits: &QualityLimits,
+		origins: &BTreeMap<usize, (usize, usize)>,
+	) -> bool {
+		let ids = self.triangles[&triangle_id].get_vertex_ids();
+		let [a, b, c] = ids.map(|id| self.vertex_lookup[&id].as_dvec2());
+		let area = (b - a).perp_dot(c - a) / 2.0;
+		let centroid = ((a + b + c) / 3.0).as_vec2();
+		let maximum_area = limits
+			.regions
+			.iter()
+			.filter(|(edges, _)| is_vertex_within_polygon(&centroid, edges))
+			.map(|(_, area)| *area)
+			.chain(limits.maximum_area)
+			.reduce(f64::min);
+		if maximum_area.is_some_and(|maximum| area > maximum) {
+			return true;
+		}
+		// the smallest angle sits opposite the shortest edge, its sine
+		// relates the circumradius to the edge
+		let lengths = [a.distance(b), b.distance(c), c.distance(a)];
+		let shortest = (0..3)
+			.min_by(|i, j| lengths[*i].total_cmp(&lengths[*j]))
+			.unwrap_or_default();
+		let circumradius = lengths.iter().product::<f64>() / (4.0 * area);
+		if circumradius / lengths[shortest] <= limits.radius_edge_ratio {
+			return false;
+		}
+		let (p, q) = (ids[shortest], ids[(shortest + 1) % 3]);
+		let apex = ids[(shortest + 2) % 3];
+		if self.is_segment(apex, p) && self.is_segment(apex, q) {
+			return false;
+		}
+		// splitting at powers of two leaves such vertices the same distance
+		// from where their segments meet, so the edge cannot be improved
+		let (Some(s), Some(t)) = (origins.get(&p), origins.get(&q)) else {
+			return true;
+		};
+		let shared = [s.0, s.1].into_iter().find(|id| *id == t.0 || *id == t.1);
+		match shared {
+			Some(v) if s != t => {
+				let far = |(x, y): (usize, usize)| if x == v { y } else { x };
+				let v_position = self.vertex_lookup[&v];
+				let along_s = self.vertex_lookup[&far(*s)] - v_position;
+				let along_t = self.vertex_lookup[&far(*t)] - v_position;
+				along_s.angle_to(along_t).abs().to_degrees() >= 60.0
+			}
+			_ => true,
+		}
+	}
+	/// Walk in a straight line from the centroid of the `start` triangle to
+	/// `point` and return the triangle containing it. The walk stops if it
+	/// would cross a segment, the error holds the segment crossed
+	fn walk_within_segments(&self, start: usize, point: Vec2) -> Result<usize, (usize, usize)> {
+		let [a, b, c] = self.triangles[&start]
+			.get_vertex_ids()
+			.map(|id| self.vertex_lookup[&id].as_dvec2());
+		let origin = ((a + b + c) / 3.0).as_vec2();
+		let mut current = start;
+		for _ in 0..=self.triangles.len() {
+			let ids = self.triangles[&current].get_vertex_ids();
+			// the line leaves through an edge the point lies beyond which has
+			// its first vertex to the right of the line and its second to
+			// the left
+			let mut exit = None;
+			for i in 0..3 {
+				let a = self.vertex_lookup[&ids[i]];
+				let b = self.vertex_lookup[&ids[(i + 1) % 3]];
+				if orient2d(a, b, point) < 0.0 {
+					exit = Some(i);
+					if orient2d(origin, point, a) <= 0.0 && orient2d(origin, point, b) >= 0.0 {
+						break;
+					}
+				}
+			}
+			let Some(i) = exit else {
+				return Ok(current);
+			};
+			let (a, b) = (ids[i], ids[(i + 1) % 3]);
+			match self.neighbours[&current][i] {
+				Some(next) if !self.is_constrained(a, b) => current = next,
+				_ => return Err((a, b)),
+			}
+		}
+		self.walk_to_point(current, point).map_err(|(tri_id, i)| {
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			(ids[i], ids[(i + 1) % 3])
+		})
+	}
+	/// Find the segments whose diametral circle contains `point` among the
+	/// segments around the triangles that inserting the point would replace
+	fn find_encroached_segments(&self, containing: usize, point: Vec2) -> Vec<(usize, usize)> {
+		let mut cavity = vec![containing];
+		let mut in_cavity = BTreeSet::from([containing]);
+		let mut encroached = vec![];
+		let mut next = 0;
+		while let Some(tri_id) = cavity.get(next).copied() {
+			next += 1;
+			let ids = self.triangles[&tri_id].get_vertex_ids();
+			for (i, neighbour) in self.neighbours[&tri_id].iter().enumerate() {
+				let (a, b) = (ids[i], ids[(i + 1) % 3]);
+				match neighbour {
+					Some(n) if !self.is_constrained(a, b) => {
+						if !in_cavity.contains(n) && self.is_point_within_circumcircle(*n, point) {
+							in_cavity.insert(*n);
+							cavity.push(*n);
+						}
+					}
+					_ => {
+						if is_within_diametral_circle(
+							self.vertex_lookup[&a],
+							self.vertex_lookup[&b],
+							point,
+						) && !encroached.contains(&(a, b))
+						{
+							encroached.push((a, b));
 						}
 					}
 				}
-				// strip out duplicates as they must lie across the polyhedral hole
-				edges.retain(|e| !duplicate_edges.contains(e));
-
-				// create new triangles from the edges and store them
-				for edge in edges {
-					let a = new_point_id;
-					let b = edge.get_vertex_a_id();
-					let c = edge.get_vertex_b_id();
-					let mut new_tri = TriangleNode2d::new(a, b, c);
-					if !new_tri.is_degenerate(&vertex_lookup) {
-						new_tri.sort_vertices_anti_clockwise(&vertex_lookup);
-						triangles.insert(new_tri);
-					}
+			}
+		}
+		encroached
+	}
+	/// Split a segment in two with a new vertex, each triangle beside the
+	/// segment is split in two and edges are flipped until the triangulation
+	/// is Delaunay again. A segment with one end that is not already in
+	/// `origins`, so was not added by splitting, is split at a power of two
+	/// distance from that end. Segments meeting at a small angle are then
+	/// split at the same distances rather than endlessly encroaching each
+	/// other. The new vertex is added to `origins` with the ends of the
+	/// segment it was first split from.
+	///
+	/// Returns the new vertex ID and the IDs of the created triangles, `None`
+	/// if the segment is too short to split at f32 precision
+	fn split_segment(
+		&mut self,
+		a: usize,
+		b: usize,
+		origins: &mut BTreeMap<usize, (usize, usize)>,
+	) -> Option<(usize, Vec<usize>)> {
+		let (pa, pb) = (self.vertex_lookup[&a], self.vertex_lookup[&b]);
+		let length = pa.as_dvec2().distance(pb.as_dvec2());
+		let shell = 2.0_f64.powf((length / 2.0).log2().round()) / length;
+		let t = match (origins.contains_key(&a), origins.contains_key(&b)) {
+			(false, true) => shell,
+			(true, false) => 1.0 - shell,
+			_ => 0.5,
+		};
+		let point = pa.as_dvec2().lerp(pb.as_dvec2(), t).as_vec2();
+		if point == pa || point == pb {
+			return None;
+		}
+		// the triangle to the left has the edge running a->b, the one to the
+		// right b->a
+		let [left, right] = self.edge_triangles(&EdgeNode2d::new(a, b));
+		let mut sides = vec![];
+		for (tri_id, start) in [(left, a), (right, b)] {
+			if let Some(tri_id) = tri_id {
+				let ids = self.triangles[&tri_id].get_vertex_ids();
+				sides.push((tri_id, ids.iter().position(|id| *id == start)?));
+			}
+		}
+		// the point may sit a fraction off the segment, it must still split
+		// each triangle into two anti-clockwise triangles
+		for (tri_id, i) in sides.iter() {
+			let ids = self.triangles[tri_id].get_vertex_ids();
+			let [u, w, c] = [0, 1, 2].map(|j| self.vertex_lookup[&ids[(i + j) % 3]]);
+			if orient2d(u, point, c) <= 0.0 || orient2d(point, w, c) <= 0.0 {
+				return None;
+			}
+		}
+		let vertex_id = self.point_count;
+		self.point_count += 1;
+		self.vertex_lookup.insert(vertex_id, point);
+		let origin = origins
+			.get(&a)
+			.or(origins.get(&b))
+			.copied()
+			.unwrap_or((a.min(b), a.max(b)));
+		origins.insert(vertex_id, origin);
+		let mut removed = vec![];
+		let mut created = vec![];
+		let mut halves = vec![];
+		for (tri_id, i) in sides {
+			let ids = *self.triangles[&tri_id].get_vertex_ids();
+			let neighbours = self.neighbours[&tri_id];
+			let (u, w, c) = (ids[i], ids[(i + 1) % 3], ids[(i + 2) % 3]);
+			let (across_wc, across_cu) = (neighbours[(i + 1) % 3], neighbours[(i + 2) % 3]);
+			self.triangles.remove(&tri_id);
+			self.neighbours.remove(&tri_id);
+			let first = self.take_triangle_id();
+			let second = self.take_triangle_id();
+			self.triangles
+				.insert(first, TriangleNode2d::new(u, vertex_id, c));
+			self.neighbours
+				.insert(first, [None, Some(second), across_cu]);
+			self.triangles
+				.insert(second, TriangleNode2d::new(vertex_id, w, c));
+			self.neighbours
+				.insert(second, [None, across_wc, Some(first)]);
+			if let Some(across) = across_cu {
+				self.replace_neighbour(across, (u, c), Some(first));
+			}
+			if let Some(across) = across_wc {
+				self.replace_neighbour(across, (c, w), Some(second));
+			}
+			for id in [u, c] {
+				self.vertex_triangles.insert(id, first);
+			}
+			for id in [vertex_id, w] {
+				self.vertex_triangles.insert(id, second);
+			}
+			removed.push(tri_id);
+			created.extend([first, second]);
+			halves.push((first, second));
+		}
+		// the halves either side meet across the split segment
+		if let [(first_left, second_left), (first_right, second_right)] = halves[..] {
+			for (x, y) in [(first_left, second_right), (second_left, first_right)] {
+				if let Some(n) = self.neighbours.get_mut(&x) {
+					n[0] = Some(y);
+				}
+				if let Some(n) = self.neighbours.get_mut(&y) {
+					n[0] = Some(x);
 				}
 			}
 		}
-
-		// remove any triangles that use vertices of the starting super-triangle - these were not real points in the data set, merely an initialisation to kick start triangulation
-		let mut count: usize = 0;
-		let mut final_triangles = BTreeMap::new();
-		for tri in triangles {
-			// IDs of the starting vertices
-			let super_a = 0;
-			let super_b = 1;
-			let super_c = 2;
-			if !tri.get_vertex_ids().contains(&super_a)
-				&& !tri.get_vertex_ids().contains(&super_b)
-				&& !tri.get_vertex_ids().contains(&super_c)
-			{
-				final_triangles.insert(count, tri);
-				count += 1;
+		if self.constrained_edges.remove(&(a.min(b), a.max(b))) {
+			for end in [a, b] {
+				self.constrained_edges
+					.insert((end.min(vertex_id), end.max(vertex_id)));
+			}
+		}
+		let pending = halves
+			.iter()
+			.flat_map(|(first, second)| [(*first, 2), (*second, 1)])
+			.collect();
+		self.legalise_edges(pending, &mut removed, &mut created);
+		Some((vertex_id, created))
+	}
+	/// Queue the created triangles to be checked for quality, and any
+	/// segments around them to be checked for encroachment
+	fn queue_refinement(
+		&self,
+		created: &[usize],
+		segments: &mut Vec<(usize, usize)>,
+		poor: &mut VecDeque<usize>,
+	) {
+		for tri_id in created {
+			let Some(tri) = self.triangles.get(tri_id) else {
+				continue;
+			};
+			let ids = tri.get_vertex_ids();
+			for i in 0..3 {
+				let (a, b) = (ids[i], ids[(i + 1) % 3]);
+				if self.neighbours[tri_id][i].is_none() || self.is_constrained(a, b) {
+					segments.push((a, b));
+				}
 			}
+			poor.push_back(*tri_id);
+		}
+	}
+	/// Take the ID to give the next triangle created, IDs are never reused
+	fn take_triangle_id(&mut self) -> usize {
+		self.next_triangle_id += 1;
+		self.next_triangle_id - 1
+	}
+	/// Triangulate every vertex in the vertex lookup, the triangulation must
+	/// not yet hold any triangles and the vertices must not all be collinear.
+	///
+	/// The first triangle is formed from the first vertices in insertion
+	/// order, every other vertex is then inserted in turn. The walk to each
+	/// vertex starts from the last triangle created as consecutive vertices
+	/// are close together
+	fn triangulate_vertices(&mut self) {
+		let order = compute_insertion_order(&self.vertex_lookup);
+		let (a, b) = (order[0], order[1]);
+		let Some(c) = order[2..].iter().copied().find(|c| {
+			orient2d(
+				self.vertex_lookup[&a],
+				self.vertex_lookup[&b],
+				self.vertex_lookup[c],
+			) != 0.0
+		}) else {
+			return;
+		};
+		// store the first triangle anti-clockwise
+		let first = if orient2d(
+			self.vertex_lookup[&a],
+			self.vertex_lookup[&b],
+			self.vertex_lookup[&c],
+		) > 0.0
+		{
+			TriangleNode2d::new(a, b, c)
+		} else {
+			TriangleNode2d::new(b, a, c)
+		};
+		let first_id = self.take_triangle_id();
+		self.triangles.insert(first_id, first);
+		self.neighbours.insert(first_id, [None; 3]);
+		for id in [a, b, c] {
+			self.vertex_triangles.insert(id, first_id);
 		}
-		// remove the super triangle vertices from the vertex lookup
-		vertex_lookup.remove(&0);
-		vertex_lookup.remove(&1);
-		vertex_lookup.remove(&2);
 
-		if !final_triangles.is_empty() {
-			Some(Delaunay2d {
-				triangles: final_triangles,
-				vertex_lookup,
-			})
+		self.last_triangle = first_id;
+		for index in order.into_iter().filter(|id| ![a, b, c].contains(id)) {
+			let (_, created) = self.insert_vertex(index, self.last_triangle);
+			if let Some(id) = created.last() {
+				self.last_triangle = *id;
+			}
+		}
+		self.compact_triangle_ids();
+	}
+	/// Add a point to a collinear chain, the point is merged into a vertex
+	/// within the merge tolerance of it. If the point sits off the line of the chain then the
+	/// vertices are triangulated
+	fn insert_into_chain(&mut self, point_index: usize, point: Vec2) -> InsertResult {
+		let mut result = InsertResult {
+			point_index,
+			vertex_id: point_index,
+			removed_triangles: vec![],
+			created_triangles: vec![],
+		};
+		if let Some(existing) = self.find_merge_vertex(point, None) {
+			self.merged_points.insert(point_index, existing);
+			result.vertex_id = existing;
+			return result;
+		}
+		let first = self.vertex_lookup[&self.collinear_chain[0]];
+		let last = self.vertex_lookup[&self.collinear_chain[self.collinear_chain.len() - 1]];
+		self.vertex_lookup.insert(point_index, point);
+		if first == last || orient2d(first, last, point) == 0.0 {
+			self.collinear_chain.push(point_index);
+			self.sort_collinear_chain();
 		} else {
-			warn!("No triangulation found");
-			None
+			self.collinear_chain.clear();
+			self.triangulate_vertices();
+			result.created_triangles = self.triangles.keys().copied().collect();
 		}
+		result
 	}
-	/// Get a refernce to the map of unqiuely ID'ed triangles
-	pub fn get_triangles(&self) -> &BTreeMap<usize, TriangleNode2d> {
-		&self.triangles
+	/// Find the vertex nearest to `point` if it is within the merge
+	/// tolerance, the walk to it begins from `start_vertex` if given
+	fn find_merge_vertex(&self, point: Vec2, start_vertex: Option<usize>) -> Option<usize> {
+		let (vertex_id, distance_squared) =
+			self.vertices_by_distance(point, start_vertex)?.next()?;
+		(distance_squared <= (self.merge_tolerance as f64).powi(2)).then_some(vertex_id)
 	}
-	/// Get a refernce to the map of vertex IDs and their position
-	pub fn get_vertex_lookup(&self) -> &BTreeMap<usize, Vec2> {
-		&self.vertex_lookup
+	/// Order the collinear chain along its line. Sorting by `x` and then `y`
+	/// orders points along any line, including a vertical one
+	fn sort_collinear_chain(&mut self) {
+		let lookup = &self.vertex_lookup;
+		self.collinear_chain.sort_by(|a, b| {
+			let (a, b) = (lookup[a], lookup[b]);
+			a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+		});
+	}
+	/// Re-ID the triangles from zero so their IDs are contiguous
+	fn compact_triangle_ids(&mut self) {
+		let new_ids: BTreeMap<usize, usize> = self
+			.triangles
+			.keys()
+			.enumerate()
+			.map(|(new_id, old_id)| (*old_id, new_id))
+			.collect();
+		let mut triangles = BTreeMap::new();
+		let mut neighbours = BTreeMap::new();
+		let mut vertex_triangles = BTreeMap::new();
+		for (old_id, new_id) in new_ids.iter() {
+			let tri = self.triangles[old_id];
+			for vertex in tri.get_vertex_ids() {
+				vertex_triangles.entry(*vertex).or_insert(*new_id);
+			}
+			triangles.insert(*new_id, tri);
+			let links = self.neighbours[old_id].map(|n| n.map(|n| new_ids[&n]));
+			neighbours.insert(*new_id, links);
+		}
+		self.next_triangle_id = triangles.len();
+		self.last_triangle = new_ids
+			.get(&self.last_triangle)
+			.copied()
+			.unwrap_or_default();
+		self.triangles = triangles;
+		self.neighbours = neighbours;
+		self.vertex_triangles = vertex_triangles;
+	}
+}
+
+/// Number of rounds in the biased randomised insertion order, each round
+/// holds about half as many points as the round after it
+const INSERTION_ROUNDS: u32 = 16;
+
+/// Number of cells along each side of the grid that points are snapped to
+/// when computing their position along a Hilbert curve
+const HILBERT_SIDE: u32 = 1 << 16;
+
+/// Order vertices for insertion into a triangulation.
+///
+/// This follows a Biased Randomised Insertion Order (BRIO), points are
+/// pseudo-randomly split into rounds of doubling size which keeps the
+/// triangulation balanced as it grows, and within each round points are
+/// sorted along a Hilbert curve so that each point is close to the one before
+/// it and the walk to find it is short
+fn compute_insertion_order(vertices: &BTreeMap<usize, Vec2>) -> Vec<usize> {
+	let points: Vec<Vec2> = vertices.values().copied().collect();
+	let (min, max) = compute_dimension_bounds(&points);
+	let (min, max) = (min.as_dvec2(), max.as_dvec2());
+	// points sharing a coordinate leave no extent along that axis
+	let extent = (max - min).max(DVec2::splat(f64::MIN_POSITIVE));
+	let scale = f64::from(HILBERT_SIDE - 1);
+	let mut keys: Vec<(u32, u64, usize)> = vertices
+		.iter()
+		.map(|(index, point)| {
+			// each halving of the chance moves a point into an earlier round
+			let round = INSERTION_ROUNDS
+				- scramble(*index as u64)
+					.trailing_zeros()
+					.min(INSERTION_ROUNDS);
+			let cell =
+				((point.as_dvec2() - min) / extent * scale).clamp(DVec2::ZERO, DVec2::splat(scale));
+			(round, hilbert_index(cell.x as u32, cell.y as u32), *index)
+		})
+		.collect();
+	keys.sort_unstable();
+	keys.into_iter().map(|(_, _, index)| index).collect()
+}
+
+/// Find the distance along a Hilbert curve filling a grid of [HILBERT_SIDE]
+/// cells that passes through the cell at `x` and `y`
+fn hilbert_index(mut x: u32, mut y: u32) -> u64 {
+	let mut index = 0;
+	let mut side = HILBERT_SIDE / 2;
+	while side > 0 {
+		let right = u32::from(x & side > 0);
+		let top = u32::from(y & side > 0);
+		index += u64::from(side) * u64::from(side) * u64::from((3 * right) ^ top);
+		// rotate the quadrant so the curve within it joins up with the next
+		if top == 0 {
+			if right == 1 {
+				x = HILBERT_SIDE - 1 - x;
+				y = HILBERT_SIDE - 1 - y;
+			}
+			std::mem::swap(&mut x, &mut y);
+		}
+		side /= 2;
+	}
+	index
+}
+
+/// Mix the bits of a value (SplitMix64) giving a repeatable pseudo-random
+/// number without needing a random number generator
+fn scramble(value: u64) -> u64 {
+	let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^ (z >> 31)
+}
+
+/// Weights of `a` and `b` giving the position along the segment `a-b` nearest
+/// to `point`
+fn edge_weights(a: Vec2, b: Vec2, point: Vec2) -> [f32; 2] {
+	let (a, b, point) = (a.as_dvec2(), b.as_dvec2(), point.as_dvec2());
+	let t = ((point - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+	[(1.0 - t) as f32, t as f32]
+}
+
+/// Whether `point` sits strictly within the circle whose diameter is the
+/// segment `a-b`
+fn is_within_diametral_circle(a: Vec2, b: Vec2, point: Vec2) -> bool {
+	let point = point.as_dvec2();
+	(a.as_dvec2() - point).dot(b.as_dvec2() - point) < 0.0
+}
+
+/// Centre of the circle through three points, `None` if they are collinear
+pub(crate) fn circumcentre(a: DVec2, b: DVec2, c: DVec2) -> Option<DVec2> {
+	let (ab, ac) = (b - a, c - a);
+	let denominator = 2.0 * ab.perp_dot(ac);
+	if denominator == 0.0 {
+		return None;
 	}
+	let offset = DVec2::new(
+		ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+		ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+	) / denominator;
+	Some(a + offset)
+}
+
+/// Whether the segments `a-b` and `c-d` cross at a single point that is not an
+/// end of either segment
+fn do_segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+	let opposite = |x: f64, y: f64| (x > 0.0 && y < 0.0) || (x < 0.0 && y > 0.0);
+	opposite(orient2d(a, b, c), orient2d(a, b, d)) && opposite(orient2d(c, d, a), orient2d(c, d, b))
+}
+
+/// Checks whether every point sits along a single line, this includes the
+/// case of all points sitting on top of each other
+fn are_points_collinear(points: &[Vec2]) -> bool {
+	let origin = points[0];
+	// find a second point distinct from the first to define the line
+	let Some(other) = points.iter().find(|p| **p != origin) else {
+		return true;
+	};
+	// any point off the line means a triangle can be formed
+	!points.iter().any(|p| orient2d(origin, *other, *p) != 0.0)
 }
 
 /// Find the minimum `x-y` and maximum `x-y` of a plane that contains all points
 fn compute_dimension_bounds(points: &[Vec2]) -> (Vec2, Vec2) {
-	let mut minimum_world_dimensions = Vec2::ZERO;
-	let mut maximum_world_dimensions = Vec2::ZERO;
+	let first = points.first().copied().unwrap_or_default();
+	let mut minimum_world_dimensions = first;
+	let mut maximum_world_dimensions = first;
 	for point in points.iter() {
 		if point.x < minimum_world_dimensions.x {
 			minimum_world_dimensions.x = point.x;
@@ -170,142 +2657,8 @@ fn compute_dimension_bounds(points: &[Vec2]) -> (Vec2, Vec2) {
 			maximum_world_dimensions.y = point.y;
 		}
 	}
-	// ensure points are within and not ON the bounardy
-	(
-		minimum_world_dimensions - Vec2::ONE,
-		maximum_world_dimensions + Vec2::ONE,
-	)
-}
-/// Triangulation requires a super triangle that encompasses all data points as
-/// well as any possible circumcircles bewteen the data points themselves.
-///
-/// To achieve this we construct a super plane from the minimum and maximum
-/// dimension bounds of the data points and use this plane to build a triangle
-fn compute_super_triangle(
-	minimum_world_dimensions: &Vec2,
-	maximum_world_dimensions: &Vec2,
-) -> [Vec2; 3] {
-	// define vertices of a rectangular plane
-	let top_left = Vec2::new(minimum_world_dimensions.x, maximum_world_dimensions.y);
-	let top_right = Vec2::new(maximum_world_dimensions.x, maximum_world_dimensions.y);
-	let bottom_left = Vec2::new(minimum_world_dimensions.x, minimum_world_dimensions.y);
-	let bottom_right = Vec2::new(maximum_world_dimensions.x, minimum_world_dimensions.y);
-	// define 4 triangles using each edge of the plane and a new midpoint along the edge that's offset to be slightly inside the area of the plane
-	let top_midpoint_offset = ((top_left + top_right) / 2.0) - Vec2::Y;
-	let tri_1 = [top_midpoint_offset, top_left, top_right];
-
-	let left_midpoint_offset = ((top_left + bottom_left) / 2.0) + Vec2::X;
-	let tri_2 = [top_left, bottom_left, left_midpoint_offset];
-
-	let bottom_midpoint_offset = ((bottom_left + bottom_right) / 2.0) + Vec2::Y;
-	let tri_3 = [bottom_left, bottom_midpoint_offset, bottom_right];
-
-	let right_midpoint_offset = ((top_right + bottom_right) / 2.0) - Vec2::X;
-	let tri_4 = [top_right, right_midpoint_offset, bottom_right];
-
-	// find the circumcircles of each triangle
-	let a = Circumcircle::new(tri_1[0], tri_1[1], tri_1[2]).unwrap();
-	let b = Circumcircle::new(tri_2[0], tri_2[1], tri_2[2]).unwrap();
-	let c = Circumcircle::new(tri_3[0], tri_3[1], tri_3[2]).unwrap();
-	let d = Circumcircle::new(tri_4[0], tri_4[1], tri_4[2]).unwrap();
-
-	// using centres and radii construct the bounds of a super plane that encloses all possible cirumcentres
-	let new_min = Vec2::new(
-		b.get_centre().x - b.get_radius_sqaured().sqrt(),
-		c.get_centre().y - c.get_radius_sqaured().sqrt(),
-	);
-	let new_max = Vec2::new(
-		d.get_centre().x + d.get_radius_sqaured().sqrt(),
-		a.get_centre().y + a.get_radius_sqaured().sqrt(),
-	);
-
-	compute_super_triangle_from_plane(&new_min, &new_max)
-}
-
-/// Compute the vertices of a triangle that encompasses all points, to ensure all points are contained we use the boundaries of a plane
-fn compute_super_triangle_from_plane(
-	minimum_world_dimensions: &Vec2,
-	maximum_world_dimensions: &Vec2,
-) -> [Vec2; 3] {
-	// we place an imaginary triangle over the plane so that all cell origins lie within it
-	// plane looks like:
-	// _________
-	// |       |
-	// |_______|
-	// for the furthest point of the super triangle imagine the bottom two plane
-	// vertices being joined to a vertex to make a triangle
-	// __________
-	// |        |
-	// |________|
-	//  \      /
-	//   \    /
-	//    \  /
-	//     \/
-	// by computing this furthest point we have one vertex of the super triangle:
-	let bottom_left = minimum_world_dimensions;
-	let bottom_right = Vec2::new(maximum_world_dimensions.x, minimum_world_dimensions.y);
-	let x = bottom_left.x + (bottom_right.x - bottom_left.x) / 2.0;
-	// we actually scale it away from the corners of the plane by a factor of 2 as if the plane is wide but thin then a very acute super triangle is produced which can cause holes in the triangualtion (all triangles formed with super verts that get removed at the end) when the data set is very small
-	let y = minimum_world_dimensions.y - (maximum_world_dimensions.y - minimum_world_dimensions.y);
-	let sup_triangle_vert_a = Vec2::new(x, y);
-	// by treating the maximum y of the plane as a striahgt line parallel to x we can
-	// take line equations from the furthest point sup_triangle_vert_a with the
-	// two conrers that helped construct and idenitfy where those lines cross the straight
-	// parallel line to determine the two remaining vertices of the super triangle
-	//________________________________
-	//     b\  __________  /c
-	//       \ |        | /
-	//        \|________|/
-	//          \       /
-	//           \     /
-	//            \   /
-	//             \a/
-
-	let gradient_b =
-		(sup_triangle_vert_a.y - bottom_left.y) / (sup_triangle_vert_a.x - bottom_left.x);
-	let intercept_b = bottom_left.y - gradient_b * bottom_left.x;
-	// using y=mx + c we can find the point of y = max (plus a bit of wiggle room) for x giving us another
-	// vertex of the super triangle
-	let sup_triangle_vert_b = Vec2::new(
-		(maximum_world_dimensions.y - intercept_b) / gradient_b,
-		maximum_world_dimensions.y,
-	);
-	// repeat for the final vertex
-	let gradient_c =
-		(sup_triangle_vert_a.y - bottom_right.y) / (sup_triangle_vert_a.x - bottom_right.x);
-	let intercept_c = bottom_right.y - gradient_c * bottom_right.x;
-	let sup_triangle_vert_c = Vec2::new(
-		(maximum_world_dimensions.y - intercept_c) / gradient_c,
-		maximum_world_dimensions.y,
-	);
-	// we now have the vertices of a triangle that contains all cell origins
-	[
-		sup_triangle_vert_a,
-		sup_triangle_vert_b,
-		sup_triangle_vert_c,
-	]
-}
-
-/// Search through triangles and identify any that do not qualify as Delaunay with respect to `point`
-fn find_bad_triangles(
-	point: &Vec2,
-	triangles: &BTreeSet<TriangleNode2d>,
-	vertex_lookup: &BTreeMap<usize, Vec2>,
-) -> BTreeSet<TriangleNode2d> {
-	let mut set = BTreeSet::new();
-	// check if the point lies within the circumcircle of a triangle
-	for tri in triangles.iter() {
-		if let Some(circumcircle) = tri.compute_circumcircle(vertex_lookup)
-			&& circumcircle.is_point_within_circle(point)
-		{
-			// if a point is within then it is not a delaunay triangle,
-			// record this triangle for removal
-			set.insert(*tri);
-		}
-	}
-	set
+	(minimum_world_dimensions, maximum_world_dimensions)
 }
-
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -318,25 +2671,32 @@ mod tests {
 			Vec2::new(32.0, -3.0),
 		];
 		let (min_bounds, max_bounds) = compute_dimension_bounds(&points);
-		assert_eq!(Vec2::new(-24.0, -12.0), min_bounds);
-		assert_eq!(Vec2::new(51.0, 46.0), max_bounds);
+		assert_eq!(Vec2::new(-23.0, -11.0), min_bounds);
+		assert_eq!(Vec2::new(50.0, 45.0), max_bounds);
+		// bounds hug points far from the origin
+		let offset: Vec<Vec2> = points.iter().map(|p| p + Vec2::splat(1e6)).collect();
+		let (min_bounds, max_bounds) = compute_dimension_bounds(&offset);
+		assert_eq!(Vec2::new(-23.0, -11.0) + Vec2::splat(1e6), min_bounds);
+		assert_eq!(Vec2::new(50.0, 45.0) + Vec2::splat(1e6), max_bounds);
 	}
 	#[test]
-	fn super_trinagle() {
-		let minimum_world_dimensions = Vec2::new(-100.0, -200.0);
-		let maximum_world_dimensions = Vec2::new(100.0, 200.0);
-		let s = compute_super_triangle(&minimum_world_dimensions, &maximum_world_dimensions);
-		let a = Vec2::new(0.0, -30600.0);
-		let b = Vec2::new(-80199.99, 10200.0);
-		let c = Vec2::new(80199.99, 10200.0);
-		assert_eq!([a, b, c], s);
+	fn collinear() {
+		let points = vec![
+			Vec2::new(-5.0, -5.0),
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 10.0),
+		];
+		assert!(are_points_collinear(&points));
+		let points = vec![
+			Vec2::new(-5.0, -5.0),
+			Vec2::new(0.0, 1.0),
+			Vec2::new(10.0, 10.0),
+		];
+		assert!(!are_points_collinear(&points));
 	}
 	#[test]
 	fn edge_count() {
-		// if the super triangle is too small then the computed
-		// triangulation only ends up with triangles joining data
-		// points to super triangle vertices.
-		// This test uses a small triangle and ensures that one
+		// this test uses a small triangle and ensures that one
 		// final triangle is computed between the points
 		let points = vec![
 			Vec2::new(-50.0, 0.0),
@@ -346,16 +2706,1168 @@ mod tests {
 		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
 		// should only be 1 triangle
 		assert_eq!(1, data.triangles.len());
+		// vertex IDs match the index of each point
+		assert_eq!(
+			vec![&0, &1, &2],
+			data.get_vertex_lookup().keys().collect::<Vec<_>>()
+		);
 	}
 	#[test]
 	fn delaunay_too_few_points() {
 		let points = vec![Vec2::new(50.0, 0.0), Vec2::new(-50.0, 0.0)];
 		let result = Delaunay2d::compute_triangulation_2d(&points);
-		assert!(result.is_none());
+		assert_eq!(
+			Some(MosaicError::InsufficientPoints {
+				required: 3,
+				supplied: 2
+			}),
+			result.err()
+		);
 	}
 	#[test]
 	fn triangulation_is_none() {
 		let points = vec![Vec2::ZERO; 3];
-		assert!(Delaunay2d::compute_triangulation_2d(&points).is_none());
+		assert_eq!(
+			Some(MosaicError::CollinearPoints),
+			Delaunay2d::compute_triangulation_2d(&points).err()
+		);
+	}
+	#[test]
+	fn duplicates_merged() {
+		let points = vec![
+			Vec2::new(-50.0, 0.0),
+			Vec2::new(0.0, 50.0),
+			Vec2::new(-50.0, 0.0),
+			Vec2::new(50.0, 0.0),
+			Vec2::new(50.0, 0.1),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(2, data.get_triangles().len());
+		assert_eq!(4, data.get_vertex_lookup().len());
+		// 2 is a duplicate of the first vertex
+		assert_eq!(&BTreeMap::from([(2, 0)]), data.get_merged_points());
+		assert_eq!(Some(0), data.get_vertex_id(2));
+		assert_eq!(Some(4), data.get_vertex_id(4));
+
+		let data = Delaunay2d::compute_triangulation_2d_with_merge_tolerance(&points, 0.5).unwrap();
+		assert_eq!(1, data.get_triangles().len());
+		assert_eq!(3, data.get_vertex_lookup().len());
+		// 4 is close enough to merge with the third unique vertex
+		assert_eq!(&BTreeMap::from([(2, 0), (4, 3)]), data.get_merged_points());
+		assert_eq!(Some(3), data.get_vertex_id(4));
+		assert_eq!(None, data.get_vertex_id(5));
+	}
+	#[test]
+	fn cocircular_grid() {
+		// every square of a grid is cocircular so any diagonal is valid,
+		// each square must still be split into exactly 2 triangles
+		for offset in [Vec2::ZERO, Vec2::splat(100_000.0)] {
+			let mut points = vec![];
+			for x in 0..5 {
+				for y in 0..5 {
+					points.push(offset + Vec2::new(x as f32, y as f32) * 10.0);
+				}
+			}
+			let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+			assert_eq!(32, data.get_triangles().len());
+		}
+	}
+	#[test]
+	fn adjacency() {
+		// a square split into 4 triangles around its centre
+		let points = vec![
+			Vec2::new(-10.0, -10.0),
+			Vec2::new(10.0, -10.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(-10.0, 10.0),
+			Vec2::new(0.0, 0.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(4, data.get_triangles().len());
+		for (tri_id, tri) in data.get_triangles().iter() {
+			let neighbours = data.triangle_neighbours(*tri_id).unwrap();
+			for (edge, neighbour) in tri.get_edges().iter().zip(neighbours.iter()) {
+				// edges through the centre are shared, the others are hull
+				let is_internal = edge.get_vertex_a_id() == 4 || edge.get_vertex_b_id() == 4;
+				assert_eq!(is_internal, neighbour.is_some());
+				if let Some(n) = neighbour {
+					assert!(data.get_triangles()[n].get_edges().contains(edge));
+				}
+			}
+		}
+		// the centre is surrounded by every triangle in anti-clockwise order
+		let star = data.vertex_star(4);
+		assert_eq!(4, star.len());
+		for pair in star.windows(2) {
+			let neighbours = data.triangle_neighbours(pair[0]).unwrap();
+			assert!(neighbours.contains(&Some(pair[1])));
+		}
+		let corner = |id: usize| {
+			let tri = data.get_triangles()[&id];
+			let ids = tri.get_vertex_ids();
+			ids.iter().map(|v| points[*v]).sum::<Vec2>() / 3.0
+		};
+		for pair in star.windows(2) {
+			let a = corner(pair[0]);
+			let b = corner(pair[1]);
+			assert!(a.perp_dot(b) > 0.0);
+		}
+		// a corner of the square only touches 2 triangles
+		assert_eq!(2, data.vertex_star(0).len());
+		assert!(data.vertex_star(9).is_empty());
+		// internal edges have a triangle on either side, hull edges one
+		let [left, right] = data.edge_triangles(&EdgeNode2d::new(0, 4));
+		assert!(left.is_some() && right.is_some());
+		assert_ne!(left, right);
+		let [left, right] = data.edge_triangles(&EdgeNode2d::new(0, 1));
+		assert!(left.is_some() && right.is_none());
+		let [left, right] = data.edge_triangles(&EdgeNode2d::new(1, 0));
+		assert!(left.is_none() && right.is_some());
+		assert_eq!([None, None], data.edge_triangles(&EdgeNode2d::new(0, 2)));
+	}
+	#[test]
+	fn non_finite_point() {
+		let points = vec![
+			Vec2::new(-50.0, 0.0),
+			Vec2::new(0.0, f32::NAN),
+			Vec2::new(50.0, 0.0),
+		];
+		assert_eq!(
+			Some(MosaicError::NonFiniteCoordinate { index: 1 }),
+			Delaunay2d::compute_triangulation_2d(&points).err()
+		);
+	}
+	/// Pseudo-random points spread over a square
+	fn scattered_points(count: usize) -> Vec<Vec2> {
+		(0..count as u64)
+			.map(|i| {
+				let bits = scramble(i);
+				Vec2::new(
+					(bits & 0xFFFFF) as f32 / 100.0,
+					((bits >> 32) & 0xFFFFF) as f32 / 100.0,
+				)
+			})
+			.collect()
+	}
+	#[test]
+	fn insertion_order() {
+		let points = scattered_points(1000);
+		let mut vertices: BTreeMap<usize, Vec2> = points.into_iter().enumerate().collect();
+		vertices.remove(&3);
+		vertices.remove(&500);
+		let mut order = compute_insertion_order(&vertices);
+		assert_eq!(998, order.len());
+		// the early rounds are small and scattered, later points follow the curve
+		assert_ne!(0, order[0]);
+		order.sort();
+		order.dedup();
+		assert_eq!(998, order.len());
+		assert!(!order.contains(&3) && !order.contains(&500));
+	}
+	#[test]
+	fn hilbert_curve_is_continuous() {
+		// neighbouring steps along the curve are neighbouring cells
+		let side = 8;
+		let mut cells = vec![];
+		for x in 0..side {
+			for y in 0..side {
+				// scale up so the cells sit within the top level quadrants
+				let scale = HILBERT_SIDE / side;
+				cells.push((hilbert_index(x * scale, y * scale), x as i32, y as i32));
+			}
+		}
+		cells.sort();
+		for pair in cells.windows(2) {
+			let step = (pair[0].1 - pair[1].1).abs() + (pair[0].2 - pair[1].2).abs();
+			assert_eq!(1, step);
+		}
+	}
+	/// Check that no vertex sits within the circumcircle of a triangle and
+	/// that the adjacency is consistent
+	fn assert_is_delaunay(data: &Delaunay2d) {
+		let lookup = data.get_vertex_lookup();
+		for tri in data.get_triangles().values() {
+			let [a, b, c] = tri.get_vertex_ids().map(|id| lookup[&id]);
+			assert!(orient2d(a, b, c) > 0.0);
+			for (id, point) in lookup.iter() {
+				if !tri.get_vertex_ids().contains(id) {
+					assert!(incircle(a, b, c, *point) <= 0.0);
+				}
+			}
+		}
+		// adjacency is consistent in both directions
+		for (tri_id, neighbours) in data.neighbours.iter() {
+			for n in neighbours.iter().flatten() {
+				assert!(data.neighbours[n].contains(&Some(*tri_id)));
+			}
+		}
+		for (vertex, tri_id) in data.vertex_triangles.iter() {
+			assert!(
+				data.get_triangles()[tri_id]
+					.get_vertex_ids()
+					.contains(vertex)
+			);
+		}
+		assert_eq!(lookup.len(), data.vertex_triangles.len());
+	}
+	#[test]
+	fn empty_circumcircles() {
+		let points = scattered_points(500);
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert!(data.get_merged_points().is_empty());
+		// every point has a vertex
+		assert_eq!(500, data.get_vertex_lookup().len());
+		assert_is_delaunay(&data);
+	}
+	#[test]
+	fn supplied_order_does_not_matter() {
+		let points = scattered_points(300);
+		let reversed: Vec<Vec2> = points.iter().rev().copied().collect();
+		let as_set = |data: &Delaunay2d, map: &dyn Fn(usize) -> usize| {
+			data.get_triangles()
+				.values()
+				.map(|tri| {
+					let mut ids = tri.get_vertex_ids().map(map);
+					ids.sort();
+					ids
+				})
+				.collect::<BTreeSet<_>>()
+		};
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let data_reversed = Delaunay2d::compute_triangulation_2d(&reversed).unwrap();
+		assert_eq!(
+			as_set(&data, &|id| id),
+			as_set(&data_reversed, &|id| points.len() - 1 - id)
+		);
+	}
+	#[test]
+	fn insert_points() {
+		let mut points = scattered_points(250);
+		// some points fall outside of the hull
+		points.extend([
+			Vec2::new(-500.0, 5000.0),
+			Vec2::new(20000.0, 20000.0),
+			Vec2::new(5000.0, -1.0),
+		]);
+		let mut data = Delaunay2d::compute_triangulation_2d(&points[0..200]).unwrap();
+		for (index, point) in points.iter().enumerate().skip(200) {
+			let before = data.get_triangles().clone();
+			let result = data.insert_point(*point).unwrap();
+			assert_eq!(index, result.get_point_index());
+			assert_eq!(index, result.get_vertex_id());
+			assert!(!result.get_created_triangles().is_empty());
+			for id in result.get_removed_triangles() {
+				assert!(before.contains_key(id));
+				assert!(!data.get_triangles().contains_key(id));
+			}
+			for id in result.get_created_triangles() {
+				assert!(!before.contains_key(id));
+				assert!(data.get_triangles()[id].get_vertex_ids().contains(&index));
+			}
+			// everything else is untouched
+			let untouched = before.len() - result.get_removed_triangles().len();
+			assert_eq!(
+				untouched + result.get_created_triangles().len(),
+				data.get_triangles().len()
+			);
+		}
+		assert_is_delaunay(&data);
+		let as_set = |data: &Delaunay2d| {
+			data.get_triangles()
+				.values()
+				.map(|tri| {
+					let mut ids = *tri.get_vertex_ids();
+					ids.sort();
+					ids
+				})
+				.collect::<BTreeSet<_>>()
+		};
+		let rebuilt = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(as_set(&rebuilt), as_set(&data));
+	}
+	#[test]
+	fn insert_point_on_hull_and_duplicate() {
+		let points = vec![
+			Vec2::new(-10.0, -10.0),
+			Vec2::new(10.0, -10.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(-10.0, 10.0),
+			Vec2::new(0.0, 0.0),
+		];
+		let mut data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		// along the bottom edge of the square splits its triangle in two
+		let result = data.insert_point(Vec2::new(0.0, -10.0)).unwrap();
+		assert_eq!(5, result.get_vertex_id());
+		assert_eq!(5, data.get_triangles().len());
+		assert_is_delaunay(&data);
+		let star = data.vertex_star(5);
+		assert_eq!(2, star.len());
+		assert_eq!(None, data.edge_triangles(&EdgeNode2d::new(0, 1))[0]);
+		assert!(data.edge_triangles(&EdgeNode2d::new(0, 5))[0].is_some());
+		// on top of an existing vertex is merged
+		let result = data.insert_point(Vec2::new(10.0, 10.0)).unwrap();
+		assert!(result.is_merged());
+		assert_eq!(6, result.get_point_index());
+		assert_eq!(2, result.get_vertex_id());
+		assert!(result.get_created_triangles().is_empty());
+		assert_eq!(Some(2), data.get_vertex_id(6));
+		// in line with a hull edge but beyond it
+		data.insert_point(Vec2::new(20.0, -10.0)).unwrap();
+		assert_eq!(6, data.get_triangles().len());
+		assert_is_delaunay(&data);
+		assert_eq!(
+			Some(MosaicError::NonFiniteCoordinate { index: 8 }),
+			data.insert_point(Vec2::new(f32::INFINITY, 0.0)).err()
+		);
+	}
+	#[test]
+	fn insert_point_within_merge_tolerance() {
+		let points = vec![
+			Vec2::new(-10.0, -10.0),
+			Vec2::new(10.0, -10.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(-10.0, 10.0),
+			Vec2::new(0.0, 0.0),
+		];
+		let mut data =
+			Delaunay2d::compute_triangulation_2d_with_merge_tolerance(&points, 0.5).unwrap();
+		// inside the triangulation merges into the nearest vertex
+		let result = data.insert_point(Vec2::new(0.3, -0.2)).unwrap();
+		assert!(result.is_merged());
+		assert_eq!(4, result.get_vertex_id());
+		// just beyond the hull merges into the hull vertex
+		let result = data.insert_point(Vec2::new(10.4, 10.0)).unwrap();
+		assert!(result.is_merged());
+		assert_eq!(2, result.get_vertex_id());
+		assert_eq!(4, data.get_triangles().len());
+		// beyond the tolerance becomes a vertex
+		let result = data.insert_point(Vec2::new(0.0, 0.6)).unwrap();
+		assert!(!result.is_merged());
+		assert_eq!(7, result.get_vertex_id());
+		assert_is_delaunay(&data);
+		// without a tolerance only exact duplicates merge
+		let mut data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert!(!data.insert_point(Vec2::new(0.3, -0.2)).unwrap().is_merged());
+	}
+	/// Triangles described by the positions of their vertices so that
+	/// triangulations with different IDs can be compared
+	fn triangle_positions(data: &Delaunay2d) -> BTreeSet<[[u32; 2]; 3]> {
+		data.get_triangles()
+			.values()
+			.map(|tri| {
+				let mut corners = tri.get_vertex_ids().map(|id| {
+					let p = data.get_vertex_lookup()[&id];
+					[p.x.to_bits(), p.y.to_bits()]
+				});
+				corners.sort();
+				corners
+			})
+			.collect()
+	}
+	#[test]
+	fn remove_vertices() {
+		let points = scattered_points(200);
+		let mut data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		// include the left most point which sits on the hull
+		let left_most = (0..points.len())
+			.min_by(|a, b| points[*a].x.total_cmp(&points[*b].x))
+			.unwrap();
+		let mut removals: Vec<usize> = (0..60).collect();
+		removals.push(left_most);
+		for vertex_id in removals.iter() {
+			let before = data.get_triangles().clone();
+			let result = data.remove_vertex(*vertex_id).unwrap();
+			assert_eq!(*vertex_id, result.get_vertex_id());
+			for id in result.get_removed_triangles() {
+				assert!(before.contains_key(id));
+				assert!(!data.get_triangles().contains_key(id));
+			}
+			for id in result.get_created_triangles() {
+				assert!(!before.contains_key(id));
+				assert!(data.get_triangles().contains_key(id));
+			}
+			assert!(data.vertex_star(*vertex_id).is_empty());
+			assert_is_delaunay(&data);
+		}
+		let remaining: Vec<Vec2> = data.get_vertex_lookup().values().copied().collect();
+		let rebuilt = Delaunay2d::compute_triangulation_2d(&remaining).unwrap();
+		assert_eq!(triangle_positions(&rebuilt), triangle_positions(&data));
+	}
+	#[test]
+	fn remove_vertex_failures() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(20.0, 0.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(10.0, 0.0),
+		];
+		let mut data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(
+			Some(MosaicError::UnknownVertex { vertex_id: 7 }),
+			data.remove_vertex(7).err()
+		);
+		// the remaining points would all be in a line
+		assert_eq!(
+			Some(MosaicError::CollinearPoints),
+			data.remove_vertex(3).err()
+		);
+		assert_eq!(2, data.get_triangles().len());
+		assert_is_delaunay(&data);
+		// the duplicate of the removed vertex goes with it
+		assert_eq!(Some(1), data.get_vertex_id(4));
+		let result = data.remove_vertex(1).unwrap();
+		assert_eq!(2, result.get_removed_triangles().len());
+		assert_eq!(1, result.get_created_triangles().len());
+		assert_eq!(None, data.get_vertex_id(4));
+		assert_is_delaunay(&data);
+		assert_eq!(
+			Some(MosaicError::InsufficientPoints {
+				required: 3,
+				supplied: 2
+			}),
+			data.remove_vertex(0).err()
+		);
+	}
+	#[test]
+	fn from_triangulation() {
+		// a grid with jittered interior points, each square split along the
+		// same diagonal
+		let side = 12;
+		let mut vertices = vec![];
+		for row in 0..side {
+			for column in 0..side {
+				let mut point = Vec2::new(column as f32, row as f32) * 10.0;
+				if (1..side - 1).contains(&row) && (1..side - 1).contains(&column) {
+					let bits = scramble((row * side + column) as u64);
+					point += Vec2::new((bits & 0xFF) as f32, ((bits >> 32) & 0xFF) as f32) / 64.0;
+				}
+				vertices.push(point);
+			}
+		}
+		let mut triangles = vec![];
+		for row in 0..side - 1 {
+			for column in 0..side - 1 {
+				let corner = row * side + column;
+				// alternate the winding, either is accepted
+				triangles.push([corner, corner + 1, corner + side + 1]);
+				triangles.push([corner, corner + side + 1, corner + side]);
+			}
+		}
+		let (data, flips) = Delaunay2d::from_triangulation(&vertices, &triangles).unwrap();
+		assert!(flips > 0);
+		assert_is_delaunay(&data);
+		let expected = Delaunay2d::compute_triangulation_2d(&vertices).unwrap();
+		assert_eq!(triangle_positions(&expected), triangle_positions(&data));
+		// a Delaunay triangulation needs no flips
+		let delaunay_triangles: Vec<[usize; 3]> = data
+			.get_triangles()
+			.values()
+			.map(|tri| *tri.get_vertex_ids())
+			.collect();
+		let (_, flips) = Delaunay2d::from_triangulation(&vertices, &delaunay_triangles).unwrap();
+		assert_eq!(0, flips);
+		// unusable triangulations
+		let square = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(0.0, 10.0),
+			Vec2::new(20.0, 0.0),
+			Vec2::new(20.0, 10.0),
+			Vec2::new(0.0, 20.0),
+		];
+		let invalid = |count: usize, triangles: &[[usize; 3]]| match Delaunay2d::from_triangulation(
+			&square[..count],
+			triangles,
+		) {
+			Err(MosaicError::InvalidTriangulation(reason)) => reason,
+			_ => panic!("triangulation should be invalid"),
+		};
+		assert_eq!(
+			"a triangle refers to a vertex that does not exist",
+			invalid(4, &[[0, 1, 9]])
+		);
+		assert_eq!("a triangle has no area", invalid(5, &[[0, 1, 4]]));
+		assert_eq!("triangles overlap", invalid(3, &[[0, 1, 2], [0, 2, 1]]));
+		assert_eq!(
+			"triangles overlap",
+			invalid(4, &[[0, 1, 2], [0, 1, 3], [0, 2, 3]])
+		);
+		assert_eq!(
+			"a vertex is not part of any triangle",
+			invalid(4, &[[0, 1, 3]])
+		);
+		assert_eq!(
+			"the triangles do not form a single region",
+			invalid(7, &[[0, 1, 3], [1, 4, 5], [3, 2, 6]])
+		);
+	}
+	/// A concave outline is kept as constrained edges while the interior is
+	/// flipped
+	#[test]
+	fn from_concave_triangulation() {
+		// an L shape cut from a jittered grid, each square split along the
+		// same diagonal
+		let side = 12;
+		let in_shape = |row: usize, column: usize| row <= side / 2 || column <= side / 2;
+		let mut ids = BTreeMap::new();
+		let mut vertices = vec![];
+		for row in 0..side {
+			for column in 0..side {
+				if !in_shape(row, column) {
+					continue;
+				}
+				let mut point = Vec2::new(column as f32, row as f32) * 10.0;
+				let is_outline = row == 0
+					|| column == 0 || row == side - 1
+					|| column == side - 1
+					|| (row >= side / 2 && column == side / 2)
+					|| (column >= side / 2 && row == side / 2);
+				if !is_outline {
+					let bits = scramble((row * side + column) as u64);
+					point += Vec2::new((bits & 0xFF) as f32, ((bits >> 32) & 0xFF) as f32) / 64.0;
+				}
+				ids.insert((row, column), vertices.len());
+				vertices.push(point);
+			}
+		}
+		let mut triangles = vec![];
+		for row in 0..side - 1 {
+			for column in 0..side - 1 {
+				if !in_shape(row + 1, column + 1) {
+					continue;
+				}
+				let [a, b, c, d] = [
+					ids[&(row, column)],
+					ids[&(row, column + 1)],
+					ids[&(row + 1, column + 1)],
+					ids[&(row + 1, column)],
+				];
+				triangles.push([a, b, c]);
+				triangles.push([a, c, d]);
+			}
+		}
+		let (data, flips) = Delaunay2d::from_triangulation(&vertices, &triangles).unwrap();
+		assert!(flips > 0);
+		assert_eq!(triangles.len(), data.get_triangles().len());
+		assert_is_constrained_delaunay(&data);
+		// every outline edge is constrained and nothing covers the notch
+		let outline_length = 4 * (side - 1);
+		assert_eq!(outline_length, data.get_constrained_edges().len());
+		for edge in data.get_constrained_edges() {
+			assert!(data.edge_triangles(&edge).contains(&None));
+		}
+		let lookup = data.get_vertex_lookup();
+		let notch = Vec2::new(80.0, 80.0);
+		for tri in data.get_triangles().values() {
+			let [a, b, c] = tri.get_vertex_ids().map(|id| lookup[&id]);
+			assert!(
+				orient2d(a, b, notch) < 0.0
+					|| orient2d(b, c, notch) < 0.0
+					|| orient2d(c, a, notch) < 0.0
+			);
+		}
+		// a convex outline is not constrained
+		let corner = [vertices[0], vertices[1], vertices[side]];
+		let (data, _) = Delaunay2d::from_triangulation(&corner, &[[0, 1, 2]]).unwrap();
+		assert!(data.get_constrained_edges().is_empty());
+		// a point beyond the U cannot be joined to the outline without the new
+		// triangles overlapping the arms, one within it is inserted
+		let vertices = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(3.0, 0.0),
+			Vec2::new(3.0, 3.0),
+			Vec2::new(2.0, 3.0),
+			Vec2::new(2.0, 1.0),
+			Vec2::new(1.0, 1.0),
+			Vec2::new(1.0, 3.0),
+			Vec2::new(0.0, 3.0),
+		];
+		let triangles = [
+			[0, 1, 4],
+			[0, 4, 5],
+			[1, 2, 4],
+			[2, 3, 4],
+			[0, 5, 7],
+			[5, 6, 7],
+		];
+		let (mut data, _) = Delaunay2d::from_triangulation(&vertices, &triangles).unwrap();
+		let before = data.get_triangles().clone();
+		assert_eq!(
+			Err(MosaicError::OutsideTriangulation { index: 8 }),
+			data.insert_point(Vec2::new(-5.0, 1.5)).map(|_| ())
+		);
+		assert_eq!(&before, data.get_triangles());
+		assert_eq!(
+			8,
+			data.insert_point(Vec2::new(2.7, 2.0))
+				.unwrap()
+				.get_vertex_id()
+		);
+		// the triangles still cover the U exactly once
+		let lookup = data.get_vertex_lookup();
+		let area: f32 = data
+			.get_triangles()
+			.values()
+			.map(|tri| {
+				let [a, b, c] = tri.get_vertex_ids().map(|id| lookup[&id]);
+				(b - a).perp_dot(c - a) / 2.0
+			})
+			.sum();
+		assert_eq!(7.0, area);
+		assert_eq!(triangles.len() + 2, data.get_triangles().len());
+		assert_is_constrained_delaunay(&data);
+	}
+	/// Check that every edge is constrained or locally Delaunay, and that
+	/// the adjacency is consistent
+	fn assert_is_constrained_delaunay(data: &Delaunay2d) {
+		let lookup = data.get_vertex_lookup();
+		for (tri_id, tri) in data.get_triangles().iter() {
+			let ids = tri.get_vertex_ids();
+			let [a, b, c] = ids.map(|id| lookup[&id]);
+			assert!(orient2d(a, b, c) > 0.0);
+			for (i, neighbour) in data.neighbours[tri_id].iter().enumerate() {
+				let Some(n) = neighbour else {
+					continue;
+				};
+				assert!(data.neighbours[n].contains(&Some(*tri_id)));
+				let edge = EdgeNode2d::new(ids[i], ids[(i + 1) % 3]);
+				if !data.is_edge_constrained(&edge) {
+					let other = data.get_triangles()[n].get_vertex_ids();
+					let opposite = other.iter().find(|id| !ids.contains(id)).unwrap();
+					assert!(incircle(a, b, c, lookup[opposite]) <= 0.0);
+				}
+			}
+		}
+		for edge in data.get_constrained_edges() {
+			assert_ne!([None, None], data.edge_triangles(&edge));
+		}
+	}
+	#[test]
+	fn constrained_segments() {
+		let mut points = scattered_points(200);
+		let left_most = (0..points.len())
+			.min_by(|a, b| points[*a].x.total_cmp(&points[*b].x))
+			.unwrap();
+		let right_most = (0..points.len())
+			.max_by(|a, b| points[*a].x.total_cmp(&points[*b].x))
+			.unwrap();
+		// a segment passing exactly through a point is split at the point
+		points.extend([
+			Vec2::new(-1000.0, -1000.0),
+			Vec2::new(5000.0, -1000.0),
+			Vec2::new(11000.0, -1000.0),
+		]);
+		let segments = [[left_most, right_most], [200, 202]];
+		let data = Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(right_most, left_most)));
+		assert!(!data.is_edge_constrained(&EdgeNode2d::new(200, 202)));
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(200, 201)));
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(201, 202)));
+		assert_eq!(3, data.get_constrained_edges().len());
+		assert_is_constrained_delaunay(&data);
+		// a crossing segment is rejected without changing anything
+		let mut data = data;
+		let bottom = (0..200)
+			.min_by(|a, b| points[*a].y.total_cmp(&points[*b].y))
+			.unwrap();
+		let top = (0..200)
+			.max_by(|a, b| points[*a].y.total_cmp(&points[*b].y))
+			.unwrap();
+		let before = data.get_triangles().clone();
+		assert_eq!(
+			Some(MosaicError::InvalidConstraint("segments cross each other")),
+			data.insert_constraint(bottom, top).err()
+		);
+		assert_eq!(&before, data.get_triangles());
+		assert_eq!(
+			Some(MosaicError::InvalidConstraint("segment has no length")),
+			data.insert_constraint(bottom, bottom).err()
+		);
+		assert_eq!(
+			Some(MosaicError::UnknownVertex { vertex_id: 500 }),
+			data.insert_constraint(bottom, 500).err()
+		);
+		// inserting and removing points keeps the constraints
+		for point in scattered_points(260).iter().skip(200) {
+			data.insert_point(*point).unwrap();
+		}
+		let on_segment = data
+			.insert_point(Vec2::new(2000.0, -1000.0))
+			.unwrap()
+			.get_vertex_id();
+		assert!(!data.is_edge_constrained(&EdgeNode2d::new(200, 201)));
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(200, on_segment)));
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(on_segment, 201)));
+		for vertex_id in 0..20 {
+			if ![left_most, right_most, bottom].contains(&vertex_id) {
+				data.remove_vertex(vertex_id).unwrap();
+			}
+		}
+		assert_eq!(4, data.get_constrained_edges().len());
+		assert_is_constrained_delaunay(&data);
+	}
+	#[test]
+	fn remove_constrained_vertices() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(100.0, 0.0),
+			Vec2::new(100.0, 100.0),
+			Vec2::new(0.0, 100.0),
+			Vec2::new(50.0, 0.0),
+			Vec2::new(50.0, 50.0),
+			Vec2::new(30.0, 70.0),
+		];
+		let mut data =
+			Delaunay2d::compute_constrained_triangulation_2d(&points, &[[0, 1], [5, 6]]).unwrap();
+		assert_eq!(3, data.get_constrained_edges().len());
+		// removing the vertex that split a segment restores the segment
+		data.remove_vertex(4).unwrap();
+		assert!(data.is_edge_constrained(&EdgeNode2d::new(0, 1)));
+		assert_is_constrained_delaunay(&data);
+		// removing the end of a segment forgets the segment
+		data.remove_vertex(6).unwrap();
+		assert_eq!(vec![EdgeNode2d::new(0, 1)], data.get_constrained_edges());
+		assert_is_constrained_delaunay(&data);
+		assert!(data.refine(&RefinementBounds::new(20.0)).is_ok());
+	}
+	#[test]
+	fn remove_vertex_beside_notch() {
+		// a U shape whose notch is discarded, with a thick right arm
+		let u_shape = |interior: &[Vec2]| {
+			let mut points = vec![
+				Vec2::new(0.0, 0.0),
+				Vec2::new(60.0, 0.0),
+				Vec2::new(60.0, 30.0),
+				Vec2::new(20.0, 30.0),
+				Vec2::new(20.0, 10.0),
+				Vec2::new(10.0, 10.0),
+				Vec2::new(10.0, 30.0),
+				Vec2::new(0.0, 30.0),
+			];
+			let segments: Vec<[usize; 2]> = (0..points.len())
+				.map(|i| [i, (i + 1) % points.len()])
+				.collect();
+			points.extend_from_slice(interior);
+			let mut data =
+				Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+			data.discard_exterior_triangles();
+			data
+		};
+		// the inner corner of the notch, the hole it leaves is not filled
+		// across the notch
+		let mut data = u_shape(&[Vec2::new(20.0, 3.0), Vec2::new(35.0, 12.0)]);
+		data.remove_vertex(4).unwrap();
+		for tri in data.get_triangles().values() {
+			let [a, b, c] = tri.get_vertex_ids().map(|id| data.get_vertex_lookup()[&id]);
+			for notch in [
+				Vec2::new(15.0, 20.0),
+				Vec2::new(19.0, 12.0),
+				Vec2::new(11.0, 28.0),
+			] {
+				assert!(
+					orient2d(a, b, notch) < 0.0
+						|| orient2d(b, c, notch) < 0.0
+						|| orient2d(c, a, notch) < 0.0
+				);
+			}
+		}
+		assert_is_constrained_delaunay(&data);
+		// without the interior points the bottom corner would join the
+		// triangles either side of it by a single point
+		let mut data = u_shape(&[]);
+		let triangles = data.get_triangles().clone();
+		assert_eq!(
+			Err(MosaicError::PinchedVertex { vertex_id: 1 }),
+			data.remove_vertex(4).map(|_| ())
+		);
+		assert_eq!(&triangles, data.get_triangles());
+	}
+	#[test]
+	fn polygon_with_hole() {
+		let points = vec![
+			// outer square
+			Vec2::new(0.0, 0.0),
+			Vec2::new(100.0, 0.0),
+			Vec2::new(100.0, 100.0),
+			Vec2::new(0.0, 100.0),
+			// hole
+			Vec2::new(40.0, 40.0),
+			Vec2::new(60.0, 40.0),
+			Vec2::new(60.0, 60.0),
+			Vec2::new(40.0, 60.0),
+			// outside of the square
+			Vec2::new(150.0, 50.0),
+			Vec2::new(50.0, -30.0),
+			// within the polygon
+			Vec2::new(20.0, 80.0),
+			// within the hole
+			Vec2::new(50.0, 50.0),
+		];
+		let segments = [
+			[0, 1],
+			[1, 2],
+			[2, 3],
+			[3, 0],
+			[4, 5],
+			[5, 6],
+			[6, 7],
+			[7, 4],
+		];
+		let mut data =
+			Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+		assert_is_constrained_delaunay(&data);
+		let removed = data.discard_exterior_triangles();
+		assert!(!removed.is_empty());
+		for id in removed {
+			assert!(!data.get_triangles().contains_key(&id));
+		}
+		let area: f32 = data
+			.get_triangles()
+			.values()
+			.map(|tri| {
+				let [a, b, c] = tri.get_vertex_ids().map(|id| points[id]);
+				(b - a).perp_dot(c - a) / 2.0
+			})
+			.sum();
+		assert_eq!(100.0 * 100.0 - 20.0 * 20.0, area);
+		// only the points outside the polygon or within the hole are lost
+		assert_eq!(
+			vec![0, 1, 2, 3, 4, 5, 6, 7, 10],
+			data.get_vertex_lookup().keys().copied().collect::<Vec<_>>()
+		);
+		assert_eq!(8, data.get_constrained_edges().len());
+		assert_is_constrained_delaunay(&data);
+		// points within the hole or outside of the square cannot be inserted
+		// without crossing the remaining triangles
+		for outside in [Vec2::new(50.0, 45.0), Vec2::new(150.0, 50.0)] {
+			assert_eq!(
+				Err(MosaicError::OutsideTriangulation { index: 12 }),
+				data.insert_point(outside).map(|_| ())
+			);
+		}
+		assert_eq!(
+			12,
+			data.insert_point(Vec2::new(80.0, 20.0))
+				.unwrap()
+				.get_vertex_id()
+		);
+		let lookup = data.get_vertex_lookup();
+		let area: f32 = data
+			.get_triangles()
+			.values()
+			.map(|tri| {
+				let [a, b, c] = tri.get_vertex_ids().map(|id| lookup[&id]);
+				(b - a).perp_dot(c - a) / 2.0
+			})
+			.sum();
+		assert_eq!(100.0 * 100.0 - 20.0 * 20.0, area);
+		assert_is_constrained_delaunay(&data);
+	}
+	#[test]
+	fn convex_hull() {
+		let points = vec![
+			Vec2::new(10.0, 10.0),
+			Vec2::new(-10.0, 10.0),
+			Vec2::new(0.0, 0.0),
+			Vec2::new(-10.0, -10.0),
+			Vec2::new(10.0, -10.0),
+			Vec2::new(0.0, -10.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let hull = data.convex_hull();
+		// the point along the bottom edge is included
+		assert_eq!(&[3, 5, 4, 0, 1], hull.get_vertex_ids());
+		assert_eq!(5, hull.get_edges().len());
+		assert_eq!(3, hull.get_edges()[0].get_vertex_a_id());
+		assert_eq!(5, hull.get_edges()[0].get_vertex_b_id());
+		assert_eq!(1, hull.get_edges()[4].get_vertex_a_id());
+		assert_eq!(3, hull.get_edges()[4].get_vertex_b_id());
+
+		let points = scattered_points(300);
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let hull = data.convex_hull();
+		let lowest = (0..points.len())
+			.min_by(|a, b| points[*a].y.total_cmp(&points[*b].y))
+			.unwrap();
+		assert_eq!(lowest, hull.get_vertex_ids()[0]);
+		// every point is on or to the left of every hull edge
+		for edge in hull.get_edges() {
+			let a = points[edge.get_vertex_a_id()];
+			let b = points[edge.get_vertex_b_id()];
+			assert!(points.iter().all(|p| orient2d(a, b, *p) >= 0.0));
+		}
+	}
+	#[test]
+	fn collinear_chain() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(20.0, 10.0),
+			Vec2::new(10.0, 5.0),
+			Vec2::new(10.0, 5.0),
+			Vec2::new(-10.0, -5.0),
+		];
+		assert_eq!(
+			Some(MosaicError::CollinearPoints),
+			Delaunay2d::compute_triangulation_2d(&points).err()
+		);
+		let mut data = Delaunay2d::compute_triangulation_2d_allow_collinear(&points).unwrap();
+		assert!(data.is_collinear());
+		assert!(data.get_triangles().is_empty());
+		assert_eq!(&[4, 0, 2, 1], data.get_collinear_chain());
+		assert_eq!(Some(2), data.get_vertex_id(3));
+		assert!(data.convex_hull().get_vertex_ids().is_empty());
+		assert_eq!(
+			Err(MosaicError::CollinearPoints),
+			data.insert_constraint(0, 1)
+		);
+		// points along the line extend the chain
+		let result = data.insert_point(Vec2::new(30.0, 15.0)).unwrap();
+		assert_eq!(5, result.get_vertex_id());
+		assert!(result.get_created_triangles().is_empty());
+		assert_eq!(&[4, 0, 2, 1, 5], data.get_collinear_chain());
+		data.remove_vertex(0).unwrap();
+		assert_eq!(&[4, 2, 1, 5], data.get_collinear_chain());
+		// a point off the line allows triangles to be formed
+		let result = data.insert_point(Vec2::new(0.0, 10.0)).unwrap();
+		assert!(!data.is_collinear());
+		assert_eq!(3, data.get_triangles().len());
+		assert_eq!(3, result.get_created_triangles().len());
+		assert_is_delaunay(&data);
+		// any number of points is accepted
+		let data = Delaunay2d::compute_triangulation_2d_allow_collinear(&[Vec2::ONE]).unwrap();
+		assert_eq!(&[0], data.get_collinear_chain());
+		assert_eq!(
+			Some(MosaicError::InsufficientPoints {
+				required: 1,
+				supplied: 0
+			}),
+			Delaunay2d::compute_triangulation_2d_allow_collinear(&[]).err()
+		);
+		let data =
+			Delaunay2d::compute_triangulation_2d_allow_collinear(&[Vec2::ZERO, Vec2::X, Vec2::Y])
+				.unwrap();
+		assert!(!data.is_collinear());
+		assert_eq!(1, data.get_triangles().len());
+	}
+	#[test]
+	fn any_spread_of_points() {
+		// the flat wide data set from docs/notes.txt which a super triangle
+		// left empty
+		let points = vec![
+			Vec2::new(0.0, 50.0),
+			Vec2::new(-50.0, 0.0),
+			Vec2::new(50.0, 0.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(1, data.get_triangles().len());
+		// a long thin zig-zag
+		let points: Vec<Vec2> = (0..200)
+			.map(|i| Vec2::new(i as f32 * 1000.0, (i % 2) as f32 * 0.5))
+			.collect();
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		assert_eq!(198, data.get_triangles().len());
+		assert_eq!(200, data.convex_hull().get_vertex_ids().len());
+		// huge, tiny and offset coordinates
+		for (scale, offset) in [
+			(1.0e30, Vec2::ZERO),
+			(1.0e-30, Vec2::ZERO),
+			(1.0, Vec2::splat(1.0e7)),
+		] {
+			let points: Vec<Vec2> = scattered_points(300)
+				.iter()
+				.map(|p| *p * scale + offset)
+				.collect();
+			let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+			assert_is_delaunay(&data);
+			// every vertex is used and the triangles cover the whole hull
+			let hull = data.convex_hull();
+			let vertex_count = data.get_vertex_lookup().len();
+			assert_eq!(
+				2 * vertex_count - 2 - hull.get_vertex_ids().len(),
+				data.get_triangles().len()
+			);
+			let lookup = data.get_vertex_lookup();
+			for edge in hull.get_edges() {
+				let a = lookup[&edge.get_vertex_a_id()];
+				let b = lookup[&edge.get_vertex_b_id()];
+				assert!(lookup.values().all(|p| orient2d(a, b, *p) >= 0.0));
+			}
+		}
+	}
+	#[test]
+	fn locate() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(0.0, 10.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let weighted = |point: Vec2| {
+			let mut weighted = data.locate(point).unwrap().get_weighted_vertices();
+			weighted.sort_by_key(|(id, _)| *id);
+			weighted
+		};
+		let location = data.locate(Vec2::new(2.0, 3.0)).unwrap();
+		assert!(matches!(location, Location::Triangle { .. }));
+		for ((id, weight), expected) in weighted(Vec2::new(2.0, 3.0)).iter().zip([0.5, 0.2, 0.3]) {
+			assert!((weight - expected).abs() < 1e-6, "vertex {}", id);
+		}
+		assert!(matches!(
+			data.locate(Vec2::new(5.0, 0.0)),
+			Some(Location::Edge {
+				weights: [0.5, 0.5],
+				..
+			})
+		));
+		assert!(matches!(
+			data.locate(Vec2::new(10.0, 0.0)),
+			Some(Location::Vertex { vertex_id: 1, .. })
+		));
+		// outside the hull the nearest edge is reported
+		let location = data.locate(Vec2::new(10.0, 10.0)).unwrap();
+		assert!(location.is_outside_hull());
+		assert_eq!(vec![(1, 0.5), (2, 0.5)], weighted(Vec2::new(10.0, 10.0)));
+		assert_eq!(
+			vec![(0, 1.0)],
+			weighted(Vec2::new(-5.0, -5.0))
+				.into_iter()
+				.filter(|(_, w)| *w > 0.0)
+				.collect::<Vec<_>>()
+		);
+		assert_eq!(None, data.locate(Vec2::NAN));
+
+		let data = Delaunay2d::compute_triangulation_2d(&scattered_points(500)).unwrap();
+		let lookup = data.get_vertex_lookup();
+		let hull = data.convex_hull();
+		let mut start = 0;
+		for query in scattered_points(700)
+			.iter()
+			.skip(500)
+			.map(|p| *p * 1.2 - 1000.0)
+		{
+			let location = data.locate_from(query, start).unwrap();
+			start = location.get_triangle_id();
+			let position: Vec2 = location
+				.get_weighted_vertices()
+				.iter()
+				.map(|(id, weight)| lookup[id] * *weight)
+				.sum();
+			if location.is_outside_hull() {
+				// no hull vertex is nearer than the reported position
+				let distance = position.distance(query);
+				assert!(
+					hull.get_vertex_ids()
+						.iter()
+						.all(|id| lookup[id].distance(query) >= distance - 0.01)
+				);
+			} else {
+				assert!(position.distance(query) < 0.01);
+			}
+		}
+	}
+	/// Smallest angle of a triangle in degrees
+	fn minimum_angle(data: &Delaunay2d, tri: &TriangleNode2d) -> f64 {
+		let [a, b, c] = tri
+			.get_vertex_ids()
+			.map(|id| data.get_vertex_lookup()[&id].as_dvec2());
+		[(a, b, c), (b, c, a), (c, a, b)]
+			.iter()
+			.map(|(p, q, r)| (q - p).angle_to(r - p).abs().to_degrees())
+			.fold(f64::MAX, f64::min)
+	}
+	#[test]
+	fn refinement() {
+		let points = vec![
+			// outer square
+			Vec2::new(0.0, 0.0),
+			Vec2::new(100.0, 0.0),
+			Vec2::new(100.0, 100.0),
+			Vec2::new(0.0, 100.0),
+			// hole
+			Vec2::new(40.0, 40.0),
+			Vec2::new(60.0, 40.0),
+			Vec2::new(60.0, 60.0),
+			Vec2::new(40.0, 60.0),
+		];
+		let segments = [
+			[0, 1],
+			[1, 2],
+			[2, 3],
+			[3, 0],
+			[4, 5],
+			[5, 6],
+			[6, 7],
+			[7, 4],
+		];
+		let mut data =
+			Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+		data.discard_exterior_triangles();
+		let region = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(30.0, 0.0),
+			Vec2::new(30.0, 30.0),
+			Vec2::new(0.0, 30.0),
+		];
+		let bounds = RefinementBounds::new(30.0)
+			.with_maximum_area(100.0)
+			.with_region_maximum_area(region.clone(), 10.0);
+		let result = data.refine(&bounds).unwrap();
+		assert!(result.is_complete());
+		assert!(!result.get_added_vertices().is_empty());
+		for id in result.get_added_vertices() {
+			assert!(*id >= 8);
+			assert!(data.get_vertex_lookup().contains_key(id));
+		}
+		let region_edges: Vec<(Vec2, Vec2)> = region
+			.iter()
+			.zip(region.iter().cycle().skip(1))
+			.map(|(a, b)| (*a, *b))
+			.collect();
+		let mut total_area = 0.0;
+		for tri in data.get_triangles().values() {
+			let [a, b, c] = tri.get_vertex_ids().map(|id| data.get_vertex_lookup()[&id]);
+			let area = (b - a).perp_dot(c - a) / 2.0;
+			total_area += area;
+			assert!(minimum_angle(&data, tri) >= 30.0 - 1e-3);
+			assert!(area <= 100.0);
+			if is_vertex_within_polygon(&((a + b + c) / 3.0), &region_edges) {
+				assert!(area <= 10.0);
+			}
+		}
+		assert!((100.0 * 100.0 - 20.0 * 20.0 - total_area).abs() < 0.1);
+		// the polygon outline is kept, split into pieces
+		assert!(data.get_constrained_edges().len() > 8);
+		assert_is_constrained_delaunay(&data);
+		// scattered points keep their hull as the boundary
+		let mut data = Delaunay2d::compute_triangulation_2d(&scattered_points(300)).unwrap();
+		let result = data.refine(&RefinementBounds::new(25.0)).unwrap();
+		assert!(result.is_complete());
+		for tri in data.get_triangles().values() {
+			assert!(minimum_angle(&data, tri) >= 25.0 - 1e-3);
+		}
+		assert!(data.get_constrained_edges().is_empty());
+		assert_is_delaunay(&data);
+		// stopping early
+		let mut data = Delaunay2d::compute_triangulation_2d(&scattered_points(300)).unwrap();
+		let result = data
+			.refine(&RefinementBounds::new(25.0).with_vertex_limit(5))
+			.unwrap();
+		assert!(!result.is_complete());
+		assert_eq!(5, result.get_added_vertices().len());
+		assert_eq!(
+			Some(MosaicError::InvalidRefinement(
+				"minimum angle is out of range"
+			)),
+			data.refine(&RefinementBounds::new(40.0)).err()
+		);
+		assert_eq!(
+			Some(MosaicError::InvalidRefinement(
+				"maximum area must be positive"
+			)),
+			data.refine(&RefinementBounds::new(20.0).with_maximum_area(0.0))
+				.err()
+		);
 	}
 }
@@ -0,0 +1,443 @@
+//! Interpolate values attached to the supplied points anywhere across a
+//! Delaunay Triangulation.
+//!
+//! Each supplied point can carry a value, such as a height, temperature or
+//! colour, and these values are stored in a slice in the same order as the
+//! points so the value of a vertex is found with its ID. A position within a
+//! triangle takes a blend of the values of the triangle vertices weighted by
+//! the barycentric coordinates of the position, see [Delaunay2d::locate].
+//!
+//! Linear interpolation has creases along the triangle edges. Natural
+//! neighbour interpolation is smooth, a position is treated as if it were
+//! inserted into the triangulation and the Voronoi Cell it would gain is
+//! shared out between the cells it takes area from. The vertices of those
+//! cells are the natural neighbours of the position and each is weighted by
+//! the area taken from its cell (Sibson) or by the length of the edge between
+//! its cell and the new cell relative to its distance (Laplace), see
+//! [NaturalNeighbourWeighting].
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::{math::DVec2, prelude::*};
+
+use crate::{
+	mosaic_2d::delaunay::{Delaunay2d, Location, circumcentre},
+	prelude::incircle,
+};
+
+/// A value that can be linearly blended with another, allowing it to be
+/// interpolated across a triangulation
+pub trait Lerp: Clone {
+	/// Blend between `self` and `other`, a `t` of `0.0` gives `self` and a
+	/// `t` of `1.0` gives `other`. This is not named `lerp` to avoid clashing
+	/// with the `lerp` methods of Bevy's math types
+	fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+	fn interpolate(&self, other: &Self, t: f32) -> Self {
+		self + (other - self) * t
+	}
+}
+
+impl Lerp for f64 {
+	fn interpolate(&self, other: &Self, t: f32) -> Self {
+		self + (other - self) * f64::from(t)
+	}
+}
+
+impl Lerp for Vec2 {
+	fn interpolate(&self, other: &Self, t: f32) -> Self {
+		Vec2::lerp(*self, *other, t)
+	}
+}
+
+impl Lerp for Vec3 {
+	fn interpolate(&self, other: &Self, t: f32) -> Self {
+		Vec3::lerp(*self, *other, t)
+	}
+}
+
+impl Lerp for Vec4 {
+	fn interpolate(&self, other: &Self, t: f32) -> Self {
+		Vec4::lerp(*self, *other, t)
+	}
+}
+
+/// How the natural neighbours of a position are weighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NaturalNeighbourWeighting {
+	/// Each neighbour is weighted by the area of its Voronoi Cell that a
+	/// cell at the position would take
+	#[default]
+	Sibson,
+	/// Each neighbour is weighted by the length of the Voronoi edge it would
+	/// share with a cell at the position divided by its distance from the
+	/// position, this is quicker to compute than [NaturalNeighbourWeighting::Sibson]
+	Laplace,
+}
+
+impl Delaunay2d {
+	/// Linearly interpolate the `values` of the vertices of the triangle
+	/// containing `point`. The value of each vertex is found at its ID in
+	/// `values`, i.e `values` are in the same order as the supplied points.
+	///
+	/// `None` if the point is outside of the triangulation or a vertex has
+	/// no value
+	pub fn interpolate_linear<T: Lerp>(&self, point: Vec2, values: &[T]) -> Option<T> {
+		let location = self.locate(point)?;
+		if location.is_outside_hull() {
+			return None;
+		}
+		blend_weighted(location.get_weighted_vertices(), values)
+	}
+	/// Linearly interpolate the `values` of the vertices at each of the
+	/// `points`, see [Delaunay2d::interpolate_linear]. Each point is found by
+	/// walking from the previous one so points near each other are found
+	/// quickly
+	pub fn interpolate_linear_points<T: Lerp>(
+		&self,
+		points: &[Vec2],
+		values: &[T],
+	) -> Vec<Option<T>> {
+		let mut start = self.get_triangles().keys().next().copied();
+		points
+			.iter()
+			.map(|point| {
+				let location = self.locate_from(*point, start?)?;
+				start = Some(location.get_triangle_id());
+				if location.is_outside_hull() {
+					return None;
+				}
+				blend_weighted(location.get_weighted_vertices(), values)
+			})
+			.collect()
+	}
+	/// Linearly interpolate the `values` of the vertices across a regular
+	/// grid of `size` samples spanning from `min` to `max`, see
+	/// [Delaunay2d::interpolate_linear].
+	///
+	/// The samples include the corners of the grid and are ordered row by
+	/// row from `min.y` with `x` increasing along each row. A grid with a
+	/// single sample along an axis is sampled at `min` for that axis
+	pub fn interpolate_linear_grid<T: Lerp>(
+		&self,
+		min: Vec2,
+		max: Vec2,
+		size: UVec2,
+		values: &[T],
+	) -> Vec<Option<T>> {
+		let step = (max - min) / (size.max(UVec2::splat(2)) - UVec2::ONE).as_vec2();
+		let mut points = Vec::with_capacity((size.x * size.y) as usize);
+		for row in 0..size.y {
+			for column in 0..size.x {
+				points.push(min + UVec2::new(column, row).as_vec2() * step);
+			}
+		}
+		self.interpolate_linear_points(&points, values)
+	}
+	/// Find the natural neighbours of `point` and their weights, keyed by
+	/// vertex ID which is also the ID of each neighbour's Voronoi Cell. The
+	/// weights are positive and sum to one.
+	///
+	/// The point is treated as if it were inserted into the triangulation,
+	/// the vertices of the triangles whose circumcircle contains it are its
+	/// natural neighbours and they are weighted by what the point's new
+	/// Voronoi Cell takes from theirs, see [NaturalNeighbourWeighting]. A
+	/// point on a vertex only has that vertex as a neighbour and a point on
+	/// the hull is weighted by its position along the hull edge.
+	///
+	/// `None` if the point is outside of the triangulation, where its Voronoi
+	/// Cell would be unbounded
+	pub fn natural_neighbour_weights(
+		&self,
+		point: Vec2,
+		weighting: NaturalNeighbourWeighting,
+	) -> Option<BTreeMap<usize, f32>> {
+		let start = match self.locate(point)? {
+			Location::OutsideHull { .. } => return None,
+			Location::Vertex { vertex_id, .. } => return Some(BTreeMap::from([(vertex_id, 1.0)])),
+			location @ Location::Edge { edge, .. }
+				if self.edge_triangles(&edge).contains(&None) =>
+			{
+				return Some(location.get_weighted_vertices().into_iter().collect());
+			}
+			location => location.get_triangle_id(),
+		};
+		let lookup = self.get_vertex_lookup();
+		let triangles = self.get_triangles();
+		// the triangles whose circumcircle contains the point would be
+		// replaced by its insertion
+		let mut cavity = BTreeSet::from([start]);
+		let mut pending = vec![start];
+		while let Some(tri_id) = pending.pop() {
+			for neighbour in self.triangle_neighbours(tri_id)?.iter().flatten() {
+				let [a, b, c] = triangles[neighbour].get_vertex_ids().map(|id| lookup[&id]);
+				if !cavity.contains(neighbour) && incircle(a, b, c, point) > 0.0 {
+					cavity.insert(*neighbour);
+					pending.push(*neighbour);
+				}
+			}
+		}
+		// the edges around the cavity are joined to the point, each edge is
+		// keyed by its first vertex along with the cavity triangle it
+		// belongs to
+		let mut boundary = BTreeMap::new();
+		for tri_id in cavity.iter() {
+			let ids = triangles[tri_id].get_vertex_ids();
+			for (i, neighbour) in self.triangle_neighbours(*tri_id)?.iter().enumerate() {
+				if neighbour.is_none_or(|n| !cavity.contains(&n)) {
+					boundary.insert(ids[i], (ids[(i + 1) % 3], *tri_id));
+				}
+			}
+		}
+		// walk the boundary anti-clockwise from any of its vertices
+		let first = *boundary.keys().next()?;
+		let mut ring = vec![first];
+		while let Some((next, _)) = boundary.get(&ring[ring.len() - 1])
+			&& *next != first
+			&& ring.len() <= boundary.len()
+		{
+			ring.push(*next);
+		}
+		let q = point.as_dvec2();
+		let position = |id: &usize| lookup[id].as_dvec2();
+		// vertices of the point's new cell, each is the circumcentre of the
+		// new triangle joining a boundary edge to the point
+		let new_centres: Vec<DVec2> = ring
+			.iter()
+			.zip(ring.iter().cycle().skip(1))
+			.map(|(a, b)| circumcentre(position(a), position(b), q))
+			.collect::<Option<_>>()?;
+		let n = ring.len();
+		let mut weights = BTreeMap::new();
+		for (i, vertex_id) in ring.iter().enumerate() {
+			// the new cell's edge shared with this neighbour
+			let (start_centre, end_centre) = (new_centres[(i + n - 1) % n], new_centres[i]);
+			let weight = match weighting {
+				NaturalNeighbourWeighting::Sibson => {
+					// the area taken is bounded by the new edge and the old
+					// cell vertices, the circumcentres of the cavity
+					// triangles around the neighbour
+					let mut polygon = vec![start_centre, end_centre];
+					let previous = ring[(i + n - 1) % n];
+					let mut tri_id = boundary[vertex_id].1;
+					for _ in 0..=cavity.len() {
+						let [a, b, c] = triangles[&tri_id].get_vertex_ids().map(|id| position(&id));
+						polygon.push(circumcentre(a, b, c)?);
+						let ids = triangles[&tri_id].get_vertex_ids();
+						let j = ids.iter().position(|id| id == vertex_id)?;
+						// step anti-clockwise around the neighbour until the
+						// edge leading into it is on the boundary
+						if ids[(j + 2) % 3] == previous {
+							break;
+						}
+						tri_id = self.triangle_neighbours(tri_id)?[(j + 2) % 3]?;
+					}
+					polygon_area(&polygon)
+				}
+				NaturalNeighbourWeighting::Laplace => {
+					start_centre.distance(end_centre) / q.distance(position(vertex_id))
+				}
+			};
+			weights.insert(*vertex_id, weight);
+		}
+		let total: f64 = weights.values().sum();
+		if total <= 0.0 || !total.is_finite() {
+			return None;
+		}
+		Some(
+			weights
+				.into_iter()
+				.map(|(id, weight)| (id, (weight / total) as f32))
+				.filter(|(_, weight)| *weight > 0.0)
+				.collect(),
+		)
+	}
+	/// Interpolate the `values` of the natural neighbours of `point`, see
+	/// [Delaunay2d::natural_neighbour_weights]. The value of each vertex is
+	/// found at its ID in `values`, i.e `values` are in the same order as the
+	/// supplied points.
+	///
+	/// `None` if the point is outside of the triangulation or a neighbour has
+	/// no value
+	pub fn interpolate_natural_neighbour<T: Lerp>(
+		&self,
+		point: Vec2,
+		values: &[T],
+		weighting: NaturalNeighbourWeighting,
+	) -> Option<T> {
+		let weights = self.natural_neighbour_weights(point, weighting)?;
+		blend_weighted(weights, values)
+	}
+}
+
+/// Blend the values of vertices by their weights, `None` if a vertex has no
+/// value
+fn blend_weighted<T: Lerp>(
+	weighted_vertices: impl IntoIterator<Item = (usize, f32)>,
+	values: &[T],
+) -> Option<T> {
+	// fold each vertex into the running blend in turn, the weight of the
+	// running blend is the total weight of the vertices within it
+	let mut blend: Option<(T, f32)> = None;
+	for (vertex_id, weight) in weighted_vertices {
+		let value = values.get(vertex_id)?;
+		blend = Some(match blend {
+			None => (value.clone(), weight),
+			Some((current, total)) => {
+				let combined = total + weight;
+				if combined > 0.0 {
+					(current.interpolate(value, weight / combined), combined)
+				} else {
+					(current, combined)
+				}
+			}
+		});
+	}
+	blend.map(|(value, _)| value)
+}
+
+/// Area of a simple polygon whose vertices are in order, in either direction
+fn polygon_area(polygon: &[DVec2]) -> f64 {
+	let twice_area: f64 = polygon
+		.iter()
+		.zip(polygon.iter().cycle().skip(1))
+		.map(|(a, b)| a.perp_dot(*b))
+		.sum();
+	twice_area.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn linear_interpolation() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(0.0, 10.0),
+			Vec2::new(0.0, 0.0),
+		];
+		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		// a plane is reproduced exactly
+		let plane = |p: Vec2| 2.0 * p.x - p.y + 3.0;
+		let heights: Vec<f32> = points.iter().map(|p| plane(*p)).collect();
+		for point in [
+			Vec2::new(2.0, 7.0),
+			Vec2::new(5.0, 5.0),
+			Vec2::new(10.0, 3.0),
+			Vec2::new(0.0, 0.0),
+		] {
+			let height = delaunay.interpolate_linear(point, &heights).unwrap();
+			assert!((plane(point) - height).abs() < 1e-4);
+		}
+		assert_eq!(
+			None,
+			delaunay.interpolate_linear(Vec2::new(11.0, 5.0), &heights)
+		);
+		// a vertex without a value cannot be interpolated
+		assert_eq!(
+			None,
+			delaunay.interpolate_linear(Vec2::new(5.0, 5.0), &heights[..2])
+		);
+		// any value that can be blended
+		let colours: Vec<Vec3> = points.iter().map(|p| p.extend(1.0) / 10.0).collect();
+		let colour = delaunay
+			.interpolate_linear(Vec2::new(5.0, 2.5), &colours)
+			.unwrap();
+		assert!(colour.distance(Vec3::new(0.5, 0.25, 0.1)) < 1e-6);
+
+		let grid = delaunay.interpolate_linear_grid(
+			Vec2::new(-5.0, 0.0),
+			Vec2::new(10.0, 10.0),
+			UVec2::new(4, 3),
+			&heights,
+		);
+		assert_eq!(12, grid.len());
+		// the first column is outside of the triangulation
+		for (i, sample) in grid.iter().enumerate() {
+			let point = Vec2::new(-5.0 + (i % 4) as f32 * 5.0, (i / 4) as f32 * 5.0);
+			if i % 4 == 0 {
+				assert_eq!(None, *sample);
+			} else {
+				assert!((plane(point) - sample.unwrap()).abs() < 1e-4);
+			}
+		}
+	}
+	#[test]
+	fn natural_neighbour_weights() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(0.0, 10.0),
+		];
+		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		// the centre takes an equal share of each cell
+		for weighting in [
+			NaturalNeighbourWeighting::Sibson,
+			NaturalNeighbourWeighting::Laplace,
+		] {
+			let weights = delaunay
+				.natural_neighbour_weights(Vec2::splat(5.0), weighting)
+				.unwrap();
+			assert_eq!(4, weights.len());
+			for weight in weights.values() {
+				assert!((weight - 0.25).abs() < 1e-6);
+			}
+		}
+		let sibson = NaturalNeighbourWeighting::Sibson;
+		assert_eq!(
+			Some(BTreeMap::from([(2, 1.0)])),
+			delaunay.natural_neighbour_weights(Vec2::splat(10.0), sibson)
+		);
+		assert_eq!(
+			Some(BTreeMap::from([(0, 0.75), (1, 0.25)])),
+			delaunay.natural_neighbour_weights(Vec2::new(2.5, 0.0), sibson)
+		);
+		assert_eq!(
+			None,
+			delaunay.natural_neighbour_weights(Vec2::new(-1.0, 5.0), sibson)
+		);
+
+		// a plane is reproduced from scattered points
+		let points: Vec<Vec2> = (0..200u64)
+			.map(|i| {
+				let x = (i * 7919 % 211) as f32;
+				let y = (i * 104729 % 223) as f32;
+				Vec2::new(x, y)
+			})
+			.collect();
+		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let plane = |p: Vec2| 0.5 * p.x - 2.0 * p.y + 7.0;
+		let heights: Vec<f32> = points.iter().map(|p| plane(*p)).collect();
+		for weighting in [
+			NaturalNeighbourWeighting::Sibson,
+			NaturalNeighbourWeighting::Laplace,
+		] {
+			for query in [
+				Vec2::new(100.5, 100.25),
+				Vec2::new(40.3, 170.9),
+				Vec2::new(150.0, 33.3),
+			] {
+				let weights = delaunay
+					.natural_neighbour_weights(query, weighting)
+					.unwrap();
+				assert!(weights.len() >= 3);
+				let total: f32 = weights.values().sum();
+				assert!((1.0 - total).abs() < 1e-5);
+				// the weighted neighbours are centred on the point
+				let centre: Vec2 = weights.iter().map(|(id, w)| points[*id] * *w).sum();
+				assert!(centre.distance(query) < 1e-2);
+				let height = delaunay
+					.interpolate_natural_neighbour(query, &heights, weighting)
+					.unwrap();
+				assert!((plane(query) - height).abs() < 1e-2);
+			}
+		}
+	}
+}
@@ -2,50 +2,68 @@
 //!
 //!
 
+use std::collections::BTreeMap;
+
 use bevy::math::Vec2;
 
+use crate::error::{MosaicError, MosaicStage};
+
+pub mod alpha_shape;
 pub mod circumcircle;
 pub mod delaunay;
 pub mod edge_node2d;
+pub mod interpolation;
+pub mod nearest;
+pub mod proximity;
 pub mod triangle_node2d;
 pub mod voronoi;
 
 /// Defines the Delaunay-Voronoi dual
 pub struct Mosaic2d {
 	/// Generated Delaunay Triangulation
-	delaunay: Option<delaunay::Delaunay2d>,
+	delaunay: delaunay::Delaunay2d,
 	/// Generated Voronoi Tesselation
-	voronoi: Option<voronoi::Voronoi2d>,
+	voronoi: voronoi::Voronoi2d,
 }
 
 impl Mosaic2d {
 	/// Generate the Delaunay and Voronoi for a series of 2d points
-	pub fn new(data_points: &Vec<Vec2>) -> Self {
-		if let Some(delaunay) = delaunay::Delaunay2d::compute_triangulation_2d(data_points) {
-			if let Some(voronoi) = voronoi::Voronoi2d::from_delaunay_2d(&delaunay) {
-				Mosaic2d {
-					delaunay: Some(delaunay),
-					voronoi: Some(voronoi),
-				}
-			} else {
-				Mosaic2d {
-					delaunay: Some(delaunay),
-					voronoi: None,
-				}
-			}
-		} else {
-			Mosaic2d {
-				delaunay: None,
-				voronoi: None,
-			}
-		}
+	///
+	/// If either fails to be computed the error is wrapped in
+	/// [MosaicError::Stage] to identify which one failed
+	pub fn new(data_points: &[Vec2]) -> Result<Self, MosaicError> {
+		let delaunay = delaunay::Delaunay2d::compute_triangulation_2d(data_points)
+			.map_err(|e| e.at_stage(MosaicStage::Delaunay))?;
+		let voronoi = voronoi::Voronoi2d::from_delaunay_2d(&delaunay)
+			.map_err(|e| e.at_stage(MosaicStage::Voronoi))?;
+		Ok(Mosaic2d { delaunay, voronoi })
+	}
+	/// Get the computed Delaunay Triangulation
+	pub fn get_delaunay(&self) -> &delaunay::Delaunay2d {
+		&self.delaunay
+	}
+	/// Get the computed Voronoi Tesselation
+	pub fn get_voronoi(&self) -> &voronoi::Voronoi2d {
+		&self.voronoi
 	}
-	/// Get the computed Delaunay Triangulation, if it exists
-	pub fn get_delaunay(&self) -> Option<&delaunay::Delaunay2d> {
-		self.delaunay.as_ref()
+	/// Find the Voronoi Cell generated by the supplied point at `point_index`.
+	///
+	/// Points merged as duplicates resolve to the cell of the point they were
+	/// merged into. A point on the outer edge of the data set has an open
+	/// cell, see [voronoi::VoronoiCell2d::is_open]. `None` is returned if
+	/// the point does not exist
+	pub fn get_cell_for_point(&self, point_index: usize) -> Option<&voronoi::VoronoiCell2d> {
+		let vertex_id = self.delaunay.get_vertex_id(point_index)?;
+		self.voronoi.get_cells().get(&vertex_id)
 	}
-	/// Get the computed Voronoi Tesselation, if it exists
-	pub fn get_voronoi(&self) -> Option<&voronoi::Voronoi2d> {
-		self.voronoi.as_ref()
+	/// Find the Voronoi Cells that a cell generated at `point` would take area
+	/// from along with the weight of each cell, keyed by cell ID. See
+	/// [delaunay::Delaunay2d::natural_neighbour_weights]
+	pub fn natural_neighbour_weights(
+		&self,
+		point: Vec2,
+		weighting: interpolation::NaturalNeighbourWeighting,
+	) -> Option<BTreeMap<usize, f32>> {
+		self.delaunay.natural_neighbour_weights(point, weighting)
 	}
 }
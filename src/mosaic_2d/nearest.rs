@@ -0,0 +1,487 @@
+//! Find the vertices nearest to a position by walking across a Delaunay
+//! Triangulation rather than checking every vertex.
+//!
+//! From any vertex that is not the nearest to a position there is an edge
+//! leading to a vertex that is closer as long as the triangulation is
+//! Delaunay, so stepping to ever closer neighbours quickly ends at or beside
+//! the nearest vertex. From there triangles are opened in order of their
+//! distance from the position and each vertex reached queues the triangles
+//! around it. A vertex is returned once no queued triangle is closer than
+//! it, so vertices are found in order of their distance even where
+//! constrained edges leave triangles that are not Delaunay. A query only
+//! visits the triangles around the walk and the vertices it returns, when
+//! many positions are queried close together the walk can begin from the
+//! previous answer, see [Delaunay2d::nearest_vertex_from].
+//!
+//! Once exterior triangles have been discarded, see
+//! [Delaunay2d::discard_exterior_triangles], the closest vertex may sit
+//! across a notch or hole in the outline. Opening a triangle on the outline
+//! queues the triangles along that loop of the outline so the search can
+//! cross the gap.
+//!
+//! The vertices of a collinear chain are searched along their line.
+//!
+
+use std::{
+	cmp::Reverse,
+	collections::{BTreeSet, BinaryHeap},
+};
+
+use bevy::{math::DVec2, prelude::*};
+
+use crate::mosaic_2d::delaunay::Delaunay2d;
+
+impl Delaunay2d {
+	/// Find the ID of the vertex nearest to `point`. `None` if the point has
+	/// a non-finite coordinate
+	pub fn nearest_vertex(&self, point: Vec2) -> Option<usize> {
+		Some(self.vertices_by_distance(point, None)?.next()?.0)
+	}
+	/// Find the ID of the vertex nearest to `point`, the walk to it begins
+	/// from `start_vertex`. A vertex close to the point, such as the answer
+	/// to a previous query nearby, makes for a short walk
+	pub fn nearest_vertex_from(&self, point: Vec2, start_vertex: usize) -> Option<usize> {
+		Some(
+			self.vertices_by_distance(point, Some(start_vertex))?
+				.next()?
+				.0,
+		)
+	}
+	/// Find the ID of the vertex nearest to each point, each walk begins from
+	/// the previous answer so points close together in the list are quick to
+	/// find
+	pub fn nearest_vertices_to_points(&self, points: &[Vec2]) -> Vec<Option<usize>> {
+		let mut previous = None;
+		points
+			.iter()
+			.map(|point| {
+				let nearest = self
+					.vertices_by_distance(*point, previous)
+					.and_then(|mut vertices| vertices.next())
+					.map(|(vertex_id, _)| vertex_id);
+				previous = nearest.or(previous);
+				nearest
+			})
+			.collect()
+	}
+	/// Find the IDs of the `k` vertices nearest to `point`, nearest first.
+	/// Fewer are returned if the triangulation has fewer vertices
+	pub fn k_nearest_vertices(&self, point: Vec2, k: usize) -> Vec<usize> {
+		let Some(vertices) = self.vertices_by_distance(point, None) else {
+			return vec![];
+		};
+		vertices.take(k).map(|(vertex_id, _)| vertex_id).collect()
+	}
+	/// Find the IDs of the vertices no further than `radius` from `point`,
+	/// nearest first
+	pub fn vertices_within_radius(&self, point: Vec2, radius: f32) -> Vec<usize> {
+		if radius < 0.0 {
+			return vec![];
+		}
+		let Some(vertices) = self.vertices_by_distance(point, None) else {
+			return vec![];
+		};
+		let radius_squared = (radius as f64).powi(2);
+		vertices
+			.take_while(|(_, distance_squared)| *distance_squared <= radius_squared)
+			.map(|(vertex_id, _)| vertex_id)
+			.collect()
+	}
+	/// Visit vertices in order of their distance from `point`, yielding each
+	/// vertex ID and its squared distance. The walk to the nearest vertex
+	/// begins from `start_vertex` if it exists. `None` if the point has a
+	/// non-finite coordinate
+	pub(crate) fn vertices_by_distance(
+		&self,
+		point: Vec2,
+		start_vertex: Option<usize>,
+	) -> Option<NearestVertices<'_>> {
+		if !point.is_finite() {
+			return None;
+		}
+		let mut nearest = NearestVertices {
+			delaunay: self,
+			point: point.as_dvec2(),
+			queue: BinaryHeap::new(),
+			visited: BTreeSet::new(),
+			triangle_queue: BinaryHeap::new(),
+			reached_triangles: BTreeSet::new(),
+			outline_edges: BTreeSet::new(),
+		};
+		if self.is_collinear() {
+			// the chain is ordered along its line so the vertices either side
+			// of the point along the line are found by a binary search
+			let chain = self.get_collinear_chain();
+			let first = self.get_vertex_lookup()[chain.first()?].as_dvec2();
+			let last = self.get_vertex_lookup()[chain.last()?].as_dvec2();
+			let along = |position: DVec2| (position - first).dot(last - first);
+			let target = along(nearest.point);
+			let index =
+				chain.partition_point(|id| along(self.get_vertex_lookup()[id].as_dvec2()) < target);
+			let seeds = [index.checked_sub(1), Some(index)].into_iter().flatten();
+			for node in seeds.filter(|node| *node < chain.len()) {
+				nearest.push(node);
+			}
+		} else {
+			let start = match start_vertex {
+				Some(vertex_id) if self.get_vertex_lookup().contains_key(&vertex_id) => vertex_id,
+				_ => {
+					let location = self.locate(point)?;
+					self.get_triangles()[&location.get_triangle_id()].get_vertex_ids()[0]
+				}
+			};
+			nearest.push(nearest.walk_to_nearest(start));
+		}
+		Some(nearest)
+	}
+}
+
+/// Visits the vertices of a triangulation in order of their distance from a
+/// point, see [Delaunay2d::vertices_by_distance]
+pub(crate) struct NearestVertices<'a> {
+	/// The triangulation being searched
+	delaunay: &'a Delaunay2d,
+	/// Position the distances are measured from
+	point: DVec2,
+	/// Vertices reached but not yet visited keyed by their squared distance.
+	/// The bits of a positive float sort in the same order as the float
+	/// itself so they are used as the key. For a collinear chain vertices are
+	/// stored by their index along the chain
+	queue: BinaryHeap<Reverse<(u64, usize)>>,
+	/// Vertices, or chain indices, that have been added to the queue
+	visited: BTreeSet<usize>,
+	/// Triangles reached but not yet opened keyed by their squared distance
+	triangle_queue: BinaryHeap<Reverse<(u64, usize)>>,
+	/// Triangles that have been added to the triangle queue
+	reached_triangles: BTreeSet<usize>,
+	/// Outline edges, as `(triangle ID, edge index)`, whose loop of the
+	/// outline has been queued
+	outline_edges: BTreeSet<(usize, usize)>,
+}
+
+impl NearestVertices<'_> {
+	/// Squared distance from the point to a vertex, or chain index
+	fn distance_squared(&self, node: usize) -> f64 {
+		let vertex_id = if self.delaunay.is_collinear() {
+			self.delaunay.get_collinear_chain()[node]
+		} else {
+			node
+		};
+		self.delaunay.get_vertex_lookup()[&vertex_id]
+			.as_dvec2()
+			.distance_squared(self.point)
+	}
+	/// Squared distance from the point to the nearest position within a
+	/// triangle, zero if the point is inside it
+	fn triangle_distance_squared(&self, triangle_id: usize) -> f64 {
+		let lookup = self.delaunay.get_vertex_lookup();
+		let corners = self.delaunay.get_triangles()[&triangle_id]
+			.get_vertex_ids()
+			.map(|id| lookup[&id].as_dvec2());
+		let edges = [0, 1, 2].map(|i| (corners[i], corners[(i + 1) % 3]));
+		if edges
+			.iter()
+			.all(|(a, b)| (*b - *a).perp_dot(self.point - *a) >= 0.0)
+		{
+			return 0.0;
+		}
+		edges
+			.iter()
+			.map(|(a, b)| {
+				let along = *b - *a;
+				let t = ((self.point - *a).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+				(*a + along * t).distance_squared(self.point)
+			})
+			.fold(f64::INFINITY, f64::min)
+	}
+	/// Add a vertex, or chain index, to the queue unless it has already been
+	/// reached. The triangles around a vertex are queued alongside it
+	fn push(&mut self, node: usize) {
+		if self.visited.insert(node) {
+			let key = self.distance_squared(node).to_bits();
+			self.queue.push(Reverse((key, node)));
+			if !self.delaunay.is_collinear() {
+				for triangle_id in self.delaunay.vertex_star(node) {
+					self.push_triangle(triangle_id);
+				}
+			}
+		}
+	}
+	/// Add a triangle to the triangle queue unless it has already been reached
+	fn push_triangle(&mut self, triangle_id: usize) {
+		if self.reached_triangles.insert(triangle_id) {
+			let key = self.triangle_distance_squared(triangle_id).to_bits();
+			self.triangle_queue.push(Reverse((key, triangle_id)));
+		}
+	}
+	/// Queue the vertices of a triangle. Only a constrained triangulation can
+	/// have an outline that is not convex, in which case an edge on the
+	/// outline queues the triangles along its loop of the outline
+	fn open_triangle(&mut self, triangle_id: usize) {
+		for vertex_id in *self.delaunay.get_triangles()[&triangle_id].get_vertex_ids() {
+			self.push(vertex_id);
+		}
+		if !self.delaunay.has_constrained_edges() {
+			return;
+		}
+		let Some(neighbours) = self.delaunay.triangle_neighbours(triangle_id).copied() else {
+			return;
+		};
+		for (i, neighbour) in neighbours.iter().enumerate() {
+			if neighbour.is_some() || self.outline_edges.contains(&(triangle_id, i)) {
+				continue;
+			}
+			let start = (triangle_id, i);
+			let mut current = start;
+			while self.outline_edges.insert(current) {
+				self.push_triangle(current.0);
+				current = self.delaunay.step_along_hull(current, true);
+			}
+			debug_assert!(self.outline_edges.contains(&start));
+		}
+	}
+	/// IDs of the vertices joined to a vertex by an edge, or the indices
+	/// either side of a chain index
+	fn neighbours(&self, node: usize) -> Vec<usize> {
+		if self.delaunay.is_collinear() {
+			let length = self.delaunay.get_collinear_chain().len();
+			return [node.checked_sub(1), Some(node + 1)]
+				.into_iter()
+				.flatten()
+				.filter(|index| *index < length)
+				.collect();
+		}
+		let triangles = self.delaunay.get_triangles();
+		self.delaunay
+			.vertex_star(node)
+			.iter()
+			.flat_map(|tri_id| *triangles[tri_id].get_vertex_ids())
+			.filter(|id| *id != node)
+			.collect()
+	}
+	/// From a vertex step to whichever neighbour is closest to the point
+	/// until no neighbour is closer, the last vertex is the nearest
+	fn walk_to_nearest(&self, start: usize) -> usize {
+		let mut current = start;
+		let mut best = self.distance_squared(start);
+		loop {
+			let mut next = None;
+			for neighbour in self.neighbours(current) {
+				let distance = self.distance_squared(neighbour);
+				if distance < best {
+					best = distance;
+					next = Some(neighbour);
+				}
+			}
+			match next {
+				Some(vertex_id) => current = vertex_id,
+				None => return current,
+			}
+		}
+	}
+}
+
+impl Iterator for NearestVertices<'_> {
+	type Item = (usize, f64);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.delaunay.is_collinear() {
+			let Reverse((key, node)) = self.queue.pop()?;
+			for neighbour in self.neighbours(node) {
+				self.push(neighbour);
+			}
+			return Some((
+				self.delaunay.get_collinear_chain()[node],
+				f64::from_bits(key),
+			));
+		}
+		// open every triangle no further away than the nearest queued vertex,
+		// any vertex closer than it is then queued too
+		while let Some(Reverse((triangle_key, triangle_id))) = self.triangle_queue.peek().copied() {
+			if self
+				.queue
+				.peek()
+				.is_some_and(|Reverse((key, _))| *key < triangle_key)
+			{
+				break;
+			}
+			self.triangle_queue.pop();
+			self.open_triangle(triangle_id);
+		}
+		let Reverse((key, node)) = self.queue.pop()?;
+		let vertex_id = if self.delaunay.is_collinear() {
+			self.delaunay.get_collinear_chain()[node]
+		} else {
+			node
+		};
+		Some((vertex_id, f64::from_bits(key)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mosaic_2d::voronoi::Voronoi2d;
+	use rand::{Rng, SeedableRng};
+	use rand_chacha::ChaCha20Rng;
+
+	#[test]
+	fn nearest_vertices() {
+		let mut rng = ChaCha20Rng::seed_from_u64(123456789);
+		let points: Vec<Vec2> = (0..500)
+			.map(|_| Vec2::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0)))
+			.collect();
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		// queries inside and around the points
+		let queries: Vec<Vec2> = (0..200)
+			.map(|_| {
+				Vec2::new(
+					rng.random_range(-200.0..1200.0),
+					rng.random_range(-200.0..1200.0),
+				)
+			})
+			.collect();
+		let by_distance = |query: Vec2| {
+			let mut ids: Vec<usize> = (0..points.len()).collect();
+			ids.sort_by(|a, b| {
+				let distance = |id: usize| points[id].as_dvec2().distance_squared(query.as_dvec2());
+				distance(*a).total_cmp(&distance(*b))
+			});
+			ids
+		};
+		let chained = data.nearest_vertices_to_points(&queries);
+		for (query, chained) in queries.iter().zip(chained) {
+			let expected = by_distance(*query);
+			assert_eq!(Some(expected[0]), data.nearest_vertex(*query));
+			assert_eq!(Some(expected[0]), chained);
+			assert_eq!(Some(expected[0]), data.nearest_vertex_from(*query, 42));
+			assert_eq!(expected[..10], data.k_nearest_vertices(*query, 10));
+			let within: Vec<usize> = expected
+				.iter()
+				.copied()
+				.filter(|id| points[*id].distance(*query) <= 150.0)
+				.collect();
+			assert_eq!(within, data.vertices_within_radius(*query, 150.0));
+		}
+		assert_eq!(500, data.k_nearest_vertices(Vec2::ZERO, 1000).len());
+		assert!(data.vertices_within_radius(Vec2::ZERO, -1.0).is_empty());
+		assert_eq!(None, data.nearest_vertex(Vec2::NAN));
+		// a collinear chain is searched along its line
+		let line = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(30.0, 30.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(20.0, 20.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d_allow_collinear(&line).unwrap();
+		assert_eq!(Some(3), data.nearest_vertex(Vec2::new(22.0, 17.0)));
+		assert_eq!(
+			vec![0, 2, 3],
+			data.k_nearest_vertices(Vec2::new(-5.0, 0.0), 3)
+		);
+		assert_eq!(
+			vec![1, 3],
+			data.vertices_within_radius(Vec2::new(40.0, 40.0), 30.0)
+		);
+		// only generating points with a cell are found by the tessellation
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let voronoi = Voronoi2d::from_delaunay_2d(&data).unwrap();
+		for query in queries.iter() {
+			let expected: Vec<usize> = by_distance(*query)
+				.into_iter()
+				.filter(|id| voronoi.get_cells().contains_key(id))
+				.collect();
+			let nearest = data.nearest_vertex(*query).unwrap();
+			assert_eq!(
+				voronoi.get_cells().get(&nearest).map(|_| nearest),
+				voronoi.nearest_cell(&data, *query)
+			);
+			assert_eq!(expected[..5], voronoi.k_nearest_cells(&data, *query, 5));
+			let within: Vec<usize> = expected
+				.iter()
+				.copied()
+				.filter(|id| points[*id].distance(*query) <= 150.0)
+				.collect();
+			assert_eq!(within, voronoi.cells_within_radius(&data, *query, 150.0));
+		}
+	}
+	#[test]
+	fn nearest_vertices_constrained() {
+		// a U shape whose notch is discarded, the tips of its arms are no
+		// longer joined so walking from one cannot reach the other
+		let points = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(30.0, 0.0),
+			Vec2::new(30.0, 30.0),
+			Vec2::new(20.0, 30.0),
+			Vec2::new(20.0, 10.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(10.0, 30.0),
+			Vec2::new(0.0, 30.0),
+		];
+		let segments: Vec<[usize; 2]> = (0..points.len())
+			.map(|i| [i, (i + 1) % points.len()])
+			.collect();
+		let mut data =
+			Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+		data.discard_exterior_triangles();
+		let query = Vec2::new(16.0, 28.0);
+		assert_eq!(Some(3), data.nearest_vertex_from(query, 6));
+		assert_eq!(vec![3, 6, 2], data.k_nearest_vertices(query, 3));
+		// a square with a square hole whose edges are constrained, the
+		// triangles beside the constraints are not Delaunay
+		let mut rng = ChaCha20Rng::seed_from_u64(987654321);
+		let mut points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(1000.0, 0.0),
+			Vec2::new(1000.0, 1000.0),
+			Vec2::new(0.0, 1000.0),
+			Vec2::new(300.0, 300.0),
+			Vec2::new(300.0, 700.0),
+			Vec2::new(700.0, 700.0),
+			Vec2::new(700.0, 300.0),
+		];
+		let in_hole = |point: Vec2| {
+			point.cmpgt(Vec2::splat(300.0)).all() && point.cmplt(Vec2::splat(700.0)).all()
+		};
+		while points.len() < 300 {
+			let point = Vec2::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0));
+			if !in_hole(point) {
+				points.push(point);
+			}
+		}
+		let segments = [
+			[0, 1],
+			[1, 2],
+			[2, 3],
+			[3, 0],
+			[4, 5],
+			[5, 6],
+			[6, 7],
+			[7, 4],
+		];
+		let mut data =
+			Delaunay2d::compute_constrained_triangulation_2d(&points, &segments).unwrap();
+		data.discard_exterior_triangles();
+		for _ in 0..200 {
+			let query = Vec2::new(
+				rng.random_range(-200.0..1200.0),
+				rng.random_range(-200.0..1200.0),
+			);
+			let mut expected: Vec<usize> = data.get_vertex_lookup().keys().copied().collect();
+			expected.sort_by(|a, b| {
+				let distance = |id: usize| points[id].as_dvec2().distance_squared(query.as_dvec2());
+				distance(*a).total_cmp(&distance(*b))
+			});
+			assert_eq!(Some(expected[0]), data.nearest_vertex(query));
+			assert_eq!(Some(expected[0]), data.nearest_vertex_from(query, 5));
+			assert_eq!(expected[..10], data.k_nearest_vertices(query, 10));
+			let within: Vec<usize> = expected
+				.iter()
+				.copied()
+				.filter(|id| points[*id].distance(query) <= 150.0)
+				.collect();
+			assert_eq!(within, data.vertices_within_radius(query, 150.0));
+		}
+	}
+}
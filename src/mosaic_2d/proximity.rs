@@ -0,0 +1,224 @@
+//! Proximity graphs joining each point to the points nearest to it, each is
+//! a subgraph of the Delaunay Triangulation so is found from its edges.
+//!
+//! From the sparsest to the densest:
+//! - the Euclidean minimum spanning tree is the shortest set of edges joining
+//!   every point without a loop
+//! - the relative neighbourhood graph keeps an edge if no other point is
+//!   closer to both of its ends than they are to each other
+//! - the Gabriel graph keeps an edge if no other point sits within the circle
+//!   whose diameter is the edge
+//!
+//! Every edge of one graph is also an edge of the next. If any point sits
+//! within the circle of a Delaunay edge then so does a vertex opposite it in
+//! one of the two triangles beside it, so Gabriel edges are found by testing
+//! just those two vertices. The triangles around each Gabriel edge are then
+//! searched for points closer to both of its ends. This relies on the
+//! triangulation being Delaunay, the graphs of a constrained triangulation
+//! may keep edges that break their rule.
+//!
+
+use std::collections::BTreeSet;
+
+use bevy::math::DVec2;
+
+use crate::{
+	mosaic_2d::{delaunay::Delaunay2d, edge_node2d::EdgeNode2d},
+	utilities::minimum_spanning_edges,
+};
+
+impl Delaunay2d {
+	/// Find the Euclidean minimum spanning tree, the edges are listed from the
+	/// shortest to the longest
+	pub fn minimum_spanning_tree(&self) -> Vec<EdgeNode2d> {
+		let edges = self
+			.edges_with_opposites()
+			.into_iter()
+			.map(|(a, b, _)| (self.position(a).distance_squared(self.position(b)), a, b))
+			.collect();
+		minimum_spanning_edges(edges)
+			.into_iter()
+			.map(|(a, b)| EdgeNode2d::new(a, b))
+			.collect()
+	}
+	/// Find the edges of the Gabriel graph, those with no other vertex within
+	/// the circle whose diameter is the edge
+	pub fn gabriel_graph(&self) -> Vec<EdgeNode2d> {
+		self.filter_edges(|a, b, opposite| (a - opposite).dot(b - opposite) > 0.0)
+	}
+	/// Find the edges of the relative neighbourhood graph, those with no other
+	/// vertex closer to both ends of the edge than they are to each other
+	pub fn relative_neighbourhood_graph(&self) -> Vec<EdgeNode2d> {
+		self.gabriel_graph()
+			.into_iter()
+			.filter(|edge| self.is_lune_empty(edge.get_vertex_a_id(), edge.get_vertex_b_id()))
+			.collect()
+	}
+	/// Keep each edge where `is_kept(a, b, opposite)` holds for the vertex
+	/// opposite it in each triangle beside it
+	fn filter_edges(&self, is_kept: impl Fn(DVec2, DVec2, DVec2) -> bool) -> Vec<EdgeNode2d> {
+		self.edges_with_opposites()
+			.into_iter()
+			.filter(|(a, b, opposites)| {
+				opposites
+					.iter()
+					.all(|id| is_kept(self.position(*a), self.position(*b), self.position(*id)))
+			})
+			.map(|(a, b, _)| EdgeNode2d::new(a, b))
+			.collect()
+	}
+	/// List each edge once with its smallest vertex ID first, along with the
+	/// vertices opposite it in the triangles beside it. The edges of a
+	/// collinear chain join each vertex to the next one along the line
+	fn edges_with_opposites(&self) -> Vec<(usize, usize, Vec<usize>)> {
+		if self.is_collinear() {
+			return self
+				.get_collinear_chain()
+				.windows(2)
+				.map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1]), vec![]))
+				.collect();
+		}
+		let mut edges = vec![];
+		for (tri_id, tri) in self.get_triangles().iter() {
+			let ids = tri.get_vertex_ids();
+			let neighbours = self.triangle_neighbours(*tri_id).unwrap();
+			for i in 0..3 {
+				// an edge between two triangles is listed by the one with
+				// the smaller ID
+				if neighbours[i].is_some_and(|n| n < *tri_id) {
+					continue;
+				}
+				let (a, b) = (ids[i], ids[(i + 1) % 3]);
+				let mut opposites = vec![ids[(i + 2) % 3]];
+				if let Some(n) = neighbours[i] {
+					let other = self.get_triangles()[&n].get_vertex_ids();
+					opposites.extend(other.iter().find(|id| **id != a && **id != b));
+				}
+				edges.push((a.min(b), a.max(b), opposites));
+			}
+		}
+		edges
+	}
+	/// Whether no vertex sits within the lune of an edge, the area closer to
+	/// both ends of the edge than they are to each other.
+	///
+	/// The lune is convex so the triangles overlapping it are joined by edges
+	/// that also overlap it, they are found by spreading out from the edge
+	fn is_lune_empty(&self, a: usize, b: usize) -> bool {
+		let (pa, pb) = (self.position(a), self.position(b));
+		let length = pa.distance_squared(pb);
+		let is_within =
+			|p: DVec2| pa.distance_squared(p) < length && pb.distance_squared(p) < length;
+		// an edge overlapping the lune comes within range of both ends
+		let is_overlapping = |u: DVec2, w: DVec2| {
+			distance_squared_to_segment(pa, u, w) < length
+				&& distance_squared_to_segment(pb, u, w) < length
+		};
+		let mut stack: Vec<usize> = self
+			.edge_triangles(&EdgeNode2d::new(a, b))
+			.into_iter()
+			.flatten()
+			.collect();
+		let mut visited: BTreeSet<usize> = stack.iter().copied().collect();
+		while let Some(tri_id) = stack.pop() {
+			let ids = self.get_triangles()[&tri_id].get_vertex_ids();
+			if ids.iter().any(|id| is_within(self.position(*id))) {
+				return false;
+			}
+			let neighbours = self.triangle_neighbours(tri_id).unwrap();
+			for i in 0..3 {
+				if let Some(n) = neighbours[i]
+					&& !visited.contains(&n)
+					&& is_overlapping(self.position(ids[i]), self.position(ids[(i + 1) % 3]))
+				{
+					visited.insert(n);
+					stack.push(n);
+				}
+			}
+		}
+		true
+	}
+	/// Position of a vertex in f64
+	fn position(&self, vertex_id: usize) -> DVec2 {
+		self.get_vertex_lookup()[&vertex_id].as_dvec2()
+	}
+}
+
+/// Squared distance from a point to the nearest position on the segment `a-b`
+fn distance_squared_to_segment(point: DVec2, a: DVec2, b: DVec2) -> f64 {
+	let t = ((point - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+	point.distance_squared(a.lerp(b, t))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy::prelude::*;
+	use rand::{Rng, SeedableRng};
+	use rand_chacha::ChaCha20Rng;
+
+	#[test]
+	fn proximity_graphs() {
+		for seed in 0..5 {
+			let mut rng = ChaCha20Rng::seed_from_u64(seed);
+			let points: Vec<Vec2> = (0..300)
+				.map(|_| Vec2::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0)))
+				.collect();
+			let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+			let gabriel = data.gabriel_graph();
+			let relative = data.relative_neighbourhood_graph();
+			let tree = data.minimum_spanning_tree();
+			// compare against testing every point
+			let distance = |a: usize, b: usize| points[a].as_dvec2().distance(points[b].as_dvec2());
+			for a in 0..points.len() {
+				for b in (a + 1)..points.len() {
+					let edge = EdgeNode2d::new(a, b);
+					let others = (0..points.len()).filter(|c| *c != a && *c != b);
+					let is_gabriel = others.clone().all(|c| {
+						let (pa, pb, pc) = (points[a], points[b], points[c]);
+						(pa - pc).as_dvec2().dot((pb - pc).as_dvec2()) > 0.0
+					});
+					let is_relative = others
+						.clone()
+						.all(|c| distance(a, c).max(distance(b, c)) >= distance(a, b));
+					assert_eq!(is_gabriel, gabriel.contains(&edge));
+					assert_eq!(is_relative, relative.contains(&edge));
+				}
+			}
+			// the tree joins every point with the same length as Prim's algorithm
+			assert_eq!(points.len() - 1, tree.len());
+			let mut joined = vec![0];
+			let mut prim_length = 0.0;
+			while joined.len() < points.len() {
+				let (length, next) = (0..points.len())
+					.filter(|b| !joined.contains(b))
+					.map(|b| {
+						let nearest = joined
+							.iter()
+							.map(|a| distance(*a, b))
+							.fold(f64::MAX, f64::min);
+						(nearest, b)
+					})
+					.min_by(|x, y| x.0.total_cmp(&y.0))
+					.unwrap();
+				prim_length += length;
+				joined.push(next);
+			}
+			let tree_length: f64 = tree
+				.iter()
+				.map(|edge| distance(edge.get_vertex_a_id(), edge.get_vertex_b_id()))
+				.sum();
+			assert!((prim_length - tree_length).abs() < 1e-6);
+			for edge in tree.iter() {
+				assert!(relative.contains(edge));
+			}
+		}
+		// a collinear chain is its own tree
+		let line = [Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::new(1.0, 0.0)];
+		let data = Delaunay2d::compute_triangulation_2d_allow_collinear(&line).unwrap();
+		assert_eq!(
+			vec![EdgeNode2d::new(0, 2), EdgeNode2d::new(1, 2)],
+			data.minimum_spanning_tree()
+		);
+	}
+}
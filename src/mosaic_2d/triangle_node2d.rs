@@ -5,7 +5,10 @@
 use bevy::prelude::*;
 use std::{cmp::Ordering, collections::BTreeMap};
 
-use crate::{mosaic_2d::edge_node2d::EdgeNode2d, prelude::Circumcircle};
+use crate::{
+	mosaic_2d::edge_node2d::EdgeNode2d,
+	prelude::{Circumcircle, orient2d},
+};
 
 /// Describes a triangle where the vertices are represented by vertex IDs
 #[derive(Eq, Debug, Clone, Copy, PartialOrd, Ord)]
@@ -84,6 +87,12 @@ impl TriangleNode2d {
 				Ordering::Less
 			}
 		});
+		// rounding of the angles can misorder a very thin triangle, fall back
+		// to the exact orientation
+		let [a, b, c] = ids.map(|id| *vertex_lookup.get(&id).unwrap());
+		if orient2d(a, b, c) < 0.0 {
+			ids.swap(1, 2);
+		}
 	}
 	/// Checks if the triangle is degenerate, i.e vertices are colinear/an angle is zero
 	pub fn is_degenerate(&self, vertex_lookup: &BTreeMap<usize, Vec2>) -> bool {
@@ -91,8 +100,7 @@ impl TriangleNode2d {
 		let b = vertex_lookup.get(&self.get_vertex_b_id()).unwrap();
 		let c = vertex_lookup.get(&self.get_vertex_c_id()).unwrap();
 
-		let twice_area = a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y);
-		twice_area == 0.0
+		orient2d(*a, *b, *c) == 0.0
 	}
 }
 
@@ -2,23 +2,45 @@
 //! Triangulation and grouping those centres into a cell based on their shared
 //! cell-site/generating point
 //!
+//! The triangles sharing a generating point are found by walking around it
+//! from one triangle to the next, so the circumcentres of a cell are
+//! collected in anti-clockwise order without any sorting
 //!
 
-use std::{
-	cmp::Ordering,
-	collections::{BTreeMap, BTreeSet},
-};
+use std::collections::BTreeMap;
 
 use crate::{
+	error::MosaicError,
 	mosaic_2d::{delaunay::*, triangle_node2d::TriangleNode2d},
 	prelude::{is_point_within_edge_range_limt, is_vertex_within_polygon, sort_vertices_2d},
 };
 use bevy::{
 	asset::RenderAssetUsages,
+	math::DVec2,
 	mesh::{Indices, PrimitiveTopology},
 	prelude::*,
 };
 
+/// An edge of an open Voronoi Cell that reaches out to infinity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoronoiRay2d {
+	/// ID of the cell vertex the ray starts from
+	start_vertex: usize,
+	/// Unit direction the ray heads in away from the cell vertices
+	direction: Vec2,
+}
+
+impl VoronoiRay2d {
+	/// Get the ID of the cell vertex the ray starts from
+	pub fn get_start_vertex(&self) -> usize {
+		self.start_vertex
+	}
+	/// Get the unit direction the ray heads in away from the cell vertices
+	pub fn get_direction(&self) -> Vec2 {
+		self.direction
+	}
+}
+
 /// The vertices of a Voronoi Cell in 2-dimensions
 #[derive(PartialEq)]
 pub struct VoronoiCell2d {
@@ -27,10 +49,14 @@ pub struct VoronoiCell2d {
 	/// The Delaunay vertex ID which is the nearest site to the boundary vertices of the
 	/// cell compared to any other cell source
 	generating_point: usize,
+	/// The two rays bounding an open cell, `None` if the cell is closed
+	rays: Option<[VoronoiRay2d; 2]>,
 }
 
 impl VoronoiCell2d {
-	/// Get a reference to the list of vertices of this cell
+	/// Get a reference to the list of vertices of this cell. The vertices of
+	/// an open cell are a chain running from the start of its first ray to
+	/// the start of its second
 	pub fn get_vertex_ids(&self) -> &Vec<usize> {
 		&self.vertices
 	}
@@ -39,6 +65,23 @@ impl VoronoiCell2d {
 	pub fn get_generating_point(&self) -> &usize {
 		&self.generating_point
 	}
+	/// Whether the cell is unbounded, the cell of a generating point on the
+	/// outer hull of the data set reaches out to infinity
+	pub fn is_open(&self) -> bool {
+		self.rays.is_some()
+	}
+	/// Whether the cell is a bounded polygon
+	pub fn is_closed(&self) -> bool {
+		self.rays.is_none()
+	}
+	/// Get the two rays bounding an open cell, `None` if the cell is closed.
+	///
+	/// Travelling anti-clockwise around the cell the boundary arrives from
+	/// infinity along the first ray, follows the vertices and leaves along
+	/// the second
+	pub fn get_rays(&self) -> Option<&[VoronoiRay2d; 2]> {
+		self.rays.as_ref()
+	}
 	/// Get the midpoint between all vertices of the cell in real-space
 	pub fn get_centre_position(&self, vertex_lookup: &BTreeMap<usize, Vec2>) -> Vec2 {
 		let vertex_ids = self.get_vertex_ids();
@@ -65,14 +108,16 @@ impl VoronoiCell2d {
 
 /// Describes the Voronoi cells
 pub struct Voronoi2d {
-	/// Uniquely ID'ed cells
+	/// Cells ID'ed by the Delaunay vertex ID of their generating point
 	cells: BTreeMap<usize, VoronoiCell2d>,
 	/// Each vertex of a cell is an ID corresponding to a point in space
 	vertex_lookup: BTreeMap<usize, Vec2>,
 }
 
 impl Voronoi2d {
-	/// Get a reference to the map of Voronoi Cells
+	/// Get a reference to the map of Voronoi Cells. The ID of a cell is the
+	/// Delaunay vertex ID of its generating point, which is in turn the index
+	/// of the supplied point
 	pub fn get_cells(&self) -> &BTreeMap<usize, VoronoiCell2d> {
 		&self.cells
 	}
@@ -88,8 +133,58 @@ impl Voronoi2d {
 	pub fn get_vertex_lookup_mut(&mut self) -> &mut BTreeMap<usize, Vec2> {
 		&mut self.vertex_lookup
 	}
+	/// Find the ID of the cell containing `point`, the cell whose generating
+	/// point is nearest. The search walks across the `delaunay` the cells
+	/// were computed from.
+	///
+	/// `None` if the point has a non-finite coordinate or the nearest
+	/// generating point has no cell
+	pub fn nearest_cell(&self, delaunay: &Delaunay2d, point: Vec2) -> Option<usize> {
+		delaunay
+			.nearest_vertex(point)
+			.filter(|vertex_id| self.cells.contains_key(vertex_id))
+	}
+	/// Find the IDs of the `k` cells whose generating points are nearest to
+	/// `point`, nearest first. Generating points without a cell are skipped
+	pub fn k_nearest_cells(&self, delaunay: &Delaunay2d, point: Vec2, k: usize) -> Vec<usize> {
+		let Some(vertices) = delaunay.vertices_by_distance(point, None) else {
+			return vec![];
+		};
+		vertices
+			.map(|(vertex_id, _)| vertex_id)
+			.filter(|vertex_id| self.cells.contains_key(vertex_id))
+			.take(k)
+			.collect()
+	}
+	/// Find the IDs of the cells whose generating points are no further than
+	/// `radius` from `point`, nearest first. Generating points without a
+	/// cell are skipped
+	pub fn cells_within_radius(
+		&self,
+		delaunay: &Delaunay2d,
+		point: Vec2,
+		radius: f32,
+	) -> Vec<usize> {
+		delaunay
+			.vertices_within_radius(point, radius)
+			.into_iter()
+			.filter(|vertex_id| self.cells.contains_key(vertex_id))
+			.collect()
+	}
 	/// Generate a map of [VoronoiCell2d] from a Delaunay Triangle without any boundary restrictions on the Cells
-	pub fn from_delaunay_2d(delaunay: &Delaunay2d) -> Option<Self> {
+	///
+	/// Every vertex generates a cell whose vertices are the circumcentres of
+	/// the triangles around it. The triangles are found in anti-clockwise
+	/// order by stepping from one to the next across their shared edges, so
+	/// the cell is built already ordered and the tessellation is computed in
+	/// linear time.
+	///
+	/// The cell of a vertex on the outer hull is open, it reaches out to
+	/// infinity along two rays perpendicular to the hull edges either side of
+	/// the vertex, see [VoronoiCell2d::get_rays]
+	///
+	/// Fails with [MosaicError::EmptyTessellation] if no cells can be formed
+	pub fn from_delaunay_2d(delaunay: &Delaunay2d) -> Result<Self, MosaicError> {
 		let triangle_store = delaunay.get_triangles();
 		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
 
@@ -98,31 +193,226 @@ impl Voronoi2d {
 		let (voronoi_vertex_lookup, triangle_to_circumcentre_ids) =
 			create_voronoi_lookup(triangle_store, delaunay_vertex_lookup);
 
-		// loop thorugh triangles and find cases where 3 or more triangles have
-		// a vertex id in common, this means that the circumcentres of those
-		// triangles are the voronoi vertices of a cell.
-		// Keys are sets of triangle IDs, value is the ID of the generating point
-		let cell_triangles = find_shared_sets(triangle_store);
-
-		// convert the triangle groupings into voronoi vertices IDs
-		let cells = compute_cells_from_triangle_sets(
-			&cell_triangles,
-			&voronoi_vertex_lookup,
-			&triangle_to_circumcentre_ids,
-		);
+		// walk the triangles around each vertex, their circumcentres are the
+		// voronoi vertices of the cell the vertex generates
+		let mut cells = BTreeMap::new();
+		for vertex_id in delaunay_vertex_lookup.keys() {
+			if let Some(cell) =
+				compute_cell_from_triangle_fan(delaunay, *vertex_id, &triangle_to_circumcentre_ids)
+			{
+				// a cell shares the ID of its generating Delaunay vertex
+				cells.insert(*vertex_id, cell);
+			}
+		}
 
-		Some(Voronoi2d {
+		if cells.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(Voronoi2d {
 			cells,
 			vertex_lookup: voronoi_vertex_lookup,
 		})
 	}
 
+	/// Generate a map of [VoronoiCell2d] from a Delaunay Triangulation of
+	/// collinear points, see
+	/// [Delaunay2d::compute_triangulation_2d_allow_collinear]. The cells of
+	/// points along a line are parallel strips which are unbounded, so each
+	/// one is clipped to the `boundary` polygon. Neighbouring cells share the
+	/// vertices where the line between them meets the boundary.
+	///
+	/// The boundary polygon must contain at least 3 vertices, it may be
+	/// ordered clockwise or anti-clockwise and must be convex, concave
+	/// boundaries are not supported. Cells that sit entirely outside of the
+	/// boundary are skipped.
+	///
+	/// Fails with [MosaicError::InvalidBoundary] if the boundary is unusable,
+	/// or [MosaicError::EmptyTessellation] if the triangulation is not a
+	/// collinear chain or no cell overlaps the boundary
+	pub fn from_collinear_delaunay_2d(
+		delaunay: &Delaunay2d,
+		boundary: &[Vec2],
+	) -> Result<Self, MosaicError> {
+		let boundary = convex_anti_clockwise_boundary(boundary)?;
+		let chain = delaunay.get_collinear_chain();
+		if chain.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
+		// every cell is bounded by lines perpendicular to the chain, so each
+		// is a slab of positions projected onto the chain direction
+		let origin = delaunay_vertex_lookup[&chain[0]].as_dvec2();
+		let direction = delaunay_vertex_lookup[&chain[chain.len() - 1]].as_dvec2() - origin;
+		let project = |v: DVec2| (v - origin).dot(direction);
+		let sites: Vec<f64> = chain
+			.iter()
+			.map(|id| project(delaunay_vertex_lookup[id].as_dvec2()))
+			.collect();
+		let bisectors: Vec<f64> = sites.windows(2).map(|w| (w[0] + w[1]) / 2.0).collect();
+
+		let mut cells = BTreeMap::new();
+		let mut vertex_lookup = BTreeMap::new();
+		// vertices are shared between neighbouring cells by their position
+		let mut vertex_ids: BTreeMap<(u32, u32), usize> = BTreeMap::new();
+		for (i, generating_point) in chain.iter().enumerate() {
+			let lower = if i > 0 {
+				bisectors[i - 1]
+			} else {
+				f64::NEG_INFINITY
+			};
+			let upper = bisectors.get(i).copied().unwrap_or(f64::INFINITY);
+			let clipped = clip_boundary_to_slab(&boundary, &project, lower, upper);
+			if clipped.len() < 3 {
+				continue;
+			}
+			let mut vertices = vec![];
+			for vertex in clipped {
+				// adding zero turns -0.0 into 0.0 so equal positions share a key
+				let key = ((vertex.x + 0.0).to_bits(), (vertex.y + 0.0).to_bits());
+				let id = *vertex_ids.entry(key).or_insert_with(|| {
+					let id = vertex_lookup.len();
+					vertex_lookup.insert(id, vertex);
+					id
+				});
+				if !vertices.contains(&id) {
+					vertices.push(id);
+				}
+			}
+			if vertices.len() < 3 {
+				continue;
+			}
+			cells.insert(
+				*generating_point,
+				VoronoiCell2d {
+					vertices,
+					generating_point: *generating_point,
+					rays: None,
+				},
+			);
+		}
+		if cells.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(Voronoi2d {
+			cells,
+			vertex_lookup,
+		})
+	}
+
+	/// Generate a map of [VoronoiCell2d] from a Delaunay Triangulation with
+	/// every cell clipped to the `boundary` polygon, so the cells tile the
+	/// boundary without gaps. Unlike [Voronoi2d::from_delaunay_2d] every cell
+	/// is closed, the open cells of vertices on the hull are cut off where
+	/// they meet the boundary.
+	///
+	/// Vertices where cells cross the boundary and the corners of the
+	/// boundary are added to the vertex lookup, each is shared by the cells
+	/// that meet there. A collinear triangulation is tessellated with
+	/// [Voronoi2d::from_collinear_delaunay_2d].
+	///
+	/// The boundary polygon must contain at least 3 vertices, it may be
+	/// ordered clockwise or anti-clockwise and must be convex, concave
+	/// boundaries are not supported. Cells that sit entirely outside of the
+	/// boundary are skipped.
+	///
+	/// Fails with [MosaicError::InvalidBoundary] if the boundary is unusable,
+	/// or [MosaicError::EmptyTessellation] if no cell overlaps the boundary
+	pub fn from_delaunay_2d_bounded(
+		delaunay: &Delaunay2d,
+		boundary: &[Vec2],
+	) -> Result<Self, MosaicError> {
+		if delaunay.is_collinear() {
+			return Voronoi2d::from_collinear_delaunay_2d(delaunay, boundary);
+		}
+		let boundary = convex_anti_clockwise_boundary(boundary)?;
+		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
+		let (circumcentres, triangle_to_circumcentre_ids) =
+			create_voronoi_lookup(delaunay.get_triangles(), delaunay_vertex_lookup);
+
+		let mut cells = BTreeMap::new();
+		let mut vertex_lookup = BTreeMap::new();
+		// circumcentres, boundary crossings and boundary corners are shared
+		// between the cells that meet at them, different kinds of vertex can
+		// land on the same position such as a crossing at a corner
+		let mut vertex_ids: BTreeMap<BoundedVertex, usize> = BTreeMap::new();
+		let mut position_ids: BTreeMap<[u32; 2], usize> = BTreeMap::new();
+		for vertex_id in delaunay_vertex_lookup.keys() {
+			let Some(edges) = compute_cell_edges(
+				delaunay,
+				*vertex_id,
+				&circumcentres,
+				&triangle_to_circumcentre_ids,
+			) else {
+				continue;
+			};
+			let mut corners = clip_cell_edges_to_boundary(
+				&edges,
+				*vertex_id,
+				&boundary,
+				delaunay_vertex_lookup,
+				&circumcentres,
+			);
+			// a cell without any edge crossing the boundary either misses it
+			// or covers all of it
+			if corners.is_empty()
+				&& delaunay.nearest_vertex(boundary[0].as_vec2()) == Some(*vertex_id)
+			{
+				corners = (0..boundary.len()).map(BoundedVertex::Corner).collect();
+			}
+			let mut vertices: Vec<usize> = vec![];
+			for corner in corners {
+				let id = *vertex_ids.entry(corner).or_insert_with(|| {
+					let position = corner
+						.position(&boundary, delaunay_vertex_lookup, &circumcentres)
+						.as_vec2();
+					*position_ids
+						.entry(position.to_array().map(|v| (v + 0.0).to_bits()))
+						.or_insert_with(|| {
+							let id = vertex_lookup.len();
+							vertex_lookup.insert(id, position);
+							id
+						})
+				});
+				if vertices.last() != Some(&id) {
+					vertices.push(id);
+				}
+			}
+			if vertices.len() > 1 && vertices.first() == vertices.last() {
+				vertices.pop();
+			}
+			if vertices.len() < 3 {
+				continue;
+			}
+			cells.insert(
+				*vertex_id,
+				VoronoiCell2d {
+					vertices,
+					generating_point: *vertex_id,
+					rays: None,
+				},
+			);
+		}
+		if cells.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(Voronoi2d {
+			cells,
+			vertex_lookup,
+		})
+	}
+
 	/// Convert each Voronoi Cell into a Bevy Mesh. These are for use in 2d with assumed normals of [Vec3::Z]
-	pub fn as_bevy2d_meshes(&self) -> BTreeMap<usize, (Mesh, Vec2)> {
+	///
+	/// Meshes are keyed by the ID of the cell they were generated from
+	///
+	/// Open cells are unbounded and cells which cannot be meshed are skipped,
+	/// if no meshes at all could be created then
+	/// [MosaicError::EmptyTessellation] is returned
+	pub fn as_bevy2d_meshes(&self) -> Result<BTreeMap<usize, (Mesh, Vec2)>, MosaicError> {
 		let mut meshes = BTreeMap::new();
 		let cells = self.get_cells();
 		let vertex_lookup = self.get_vertex_lookup();
-		for (id, cell) in cells.iter() {
+		for (id, cell) in cells.iter().filter(|(_, cell)| cell.is_closed()) {
 			let origin = cell.get_centre_position(vertex_lookup);
 			let cell_vertex_ids = cell.get_vertex_ids();
 			// find the vertices in real-space
@@ -136,28 +426,42 @@ impl Voronoi2d {
 			// normalise vertices around origin 0,0
 			let cell_vertices_normalised: Vec<Vec2> =
 				cell_vertices.iter().map(|v| v - origin).collect();
-			if let Some(mesh) = triangulate_mesh(&cell_vertices_normalised) {
-				meshes.insert(*id, (mesh, origin));
-			} else {
-				warn!("Failed to generate a mesh");
-				warn!("Cell vertices {:?}", cell_vertices);
+			match triangulate_mesh(&cell_vertices_normalised) {
+				Ok(mesh) => {
+					meshes.insert(*id, (mesh, origin));
+				}
+				Err(e) => {
+					warn!("Failed to generate a mesh: {}", e);
+					warn!("Cell vertices {:?}", cell_vertices);
+				}
 			}
 		}
-		meshes
+		if meshes.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(meshes)
 	}
 
 	/// Convert each Voronoi Cell into a Bevy Mesh that is clipped to a boundary polygon.
+	/// Meshes are keyed by the ID of the cell they were generated from.
 	///
-	/// The boundary polygon must contain at least 3 vertices and the vertices
-	/// should be expressed in an anti-clockwise order around their centre
+	/// The boundary polygon must contain at least 3 vertices with finite
+	/// coordinates, otherwise [MosaicError::InvalidBoundary] is returned, and
+	/// may be ordered clockwise or anti-clockwise. Cells
+	/// that sit entirely outside of the boundary are skipped, as are open
+	/// cells and cells which cannot be meshed, if no meshes at all could be
+	/// created then [MosaicError::EmptyTessellation] is returned
 	///
 	/// *NB: Delaunay and Voronoi are duals - they can precisely be converted from one fomrat to the other back and forth. By applying clipping to the Voronoi, cell vertices may be added/removed which will destroy the duality - i.e if you apply clipping you cannot convert meshes into Delaunay and expect to get your oringal dataset back*
-	pub fn as_clipped_bevy2d_meshes(&self, boundary: &[Vec2]) -> BTreeMap<usize, (Mesh, Vec2)> {
-		//TODO sort the supplied boundary points or trust user input?
+	pub fn as_clipped_bevy2d_meshes(
+		&self,
+		boundary: &[Vec2],
+	) -> Result<BTreeMap<usize, (Mesh, Vec2)>, MosaicError> {
+		validate_boundary(boundary)?;
 		let mut meshes = BTreeMap::new();
 		let cells = self.get_cells();
 		let vertex_lookup = self.get_vertex_lookup();
-		for (id, cell) in cells.iter() {
+		for (id, cell) in cells.iter().filter(|(_, cell)| cell.is_closed()) {
 			let cell_vertex_ids = cell.get_vertex_ids();
 			// find the vertices in real-space
 			let mut cell_vertices = vec![];
@@ -173,16 +477,108 @@ impl Voronoi2d {
 					.map(|v| v - cell.get_centre_position(vertex_lookup))
 					.collect();
 
-				if let Some(mesh) = triangulate_mesh(&cell_vertices_normalised) {
+				if let Ok(mesh) = triangulate_mesh(&cell_vertices_normalised) {
 					let origin = cell.get_centre_position(vertex_lookup);
 					meshes.insert(*id, (mesh, origin));
 				}
 			}
 		}
-		meshes
+		if meshes.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(meshes)
 	}
 }
 
+/// Ensure a boundary polygon has enough vertices to enclose an area and that
+/// each vertex is a real position
+fn validate_boundary(boundary: &[Vec2]) -> Result<(), MosaicError> {
+	if boundary.len() < 3 {
+		return Err(MosaicError::InvalidBoundary(
+			"a boundary requires at least 3 vertices",
+		));
+	}
+	if boundary.iter().any(|v| !v.is_finite()) {
+		return Err(MosaicError::InvalidBoundary(
+			"boundary vertices must be finite",
+		));
+	}
+	Ok(())
+}
+
+/// Validate a boundary polygon that cells are built within and convert it to
+/// double precision, reversing it if it winds clockwise. Only convex
+/// boundaries which enclose an area are accepted
+fn convex_anti_clockwise_boundary(boundary: &[Vec2]) -> Result<Vec<DVec2>, MosaicError> {
+	validate_boundary(boundary)?;
+	let mut boundary: Vec<DVec2> = boundary.iter().map(|v| v.as_dvec2()).collect();
+	let twice_area = compute_twice_signed_area(&boundary);
+	if twice_area == 0.0 {
+		return Err(MosaicError::InvalidBoundary(
+			"boundary must enclose an area",
+		));
+	}
+	if twice_area < 0.0 {
+		boundary.reverse();
+	}
+	// a polygon that only turns left can still cross itself, such as a
+	// pentagram, it is only convex if every vertex is on or to the left of
+	// every edge
+	let is_convex = boundary
+		.iter()
+		.zip(boundary.iter().cycle().skip(1))
+		.all(|(a, b)| boundary.iter().all(|c| (*b - *a).perp_dot(*c - *a) >= 0.0));
+	if !is_convex {
+		return Err(MosaicError::InvalidBoundary("boundary must be convex"));
+	}
+	Ok(boundary)
+}
+
+/// Twice the area enclosed by a polygon, positive when its vertices are
+/// anti-clockwise
+fn compute_twice_signed_area(polygon: &[DVec2]) -> f64 {
+	polygon
+		.iter()
+		.zip(polygon.iter().cycle().skip(1))
+		.map(|(a, b)| a.perp_dot(*b))
+		.sum()
+}
+
+/// Clip an anti-clockwise boundary polygon to the slab of positions whose
+/// projection lies between `lower` and `upper`, keeping the anti-clockwise
+/// order.
+///
+/// Each crossing of the slab is computed from the original boundary edge so
+/// neighbouring slabs that share a limit produce identical vertices
+fn clip_boundary_to_slab(
+	boundary: &[DVec2],
+	project: &impl Fn(DVec2) -> f64,
+	lower: f64,
+	upper: f64,
+) -> Vec<Vec2> {
+	let is_inside = |t: f64| lower <= t && t <= upper;
+	let mut clipped = vec![];
+	for (a, b) in boundary.iter().zip(boundary.iter().cycle().skip(1)) {
+		let (start, end) = (project(*a), project(*b));
+		if is_inside(start) {
+			clipped.push(a.as_vec2());
+		}
+		// limits crossed part way along the edge, in the order met
+		let mut crossings: Vec<f64> = [lower, upper]
+			.into_iter()
+			.filter(|t| (start < *t && *t < end) || (end < *t && *t < start))
+			.collect();
+		if start > end {
+			crossings.reverse();
+		}
+		for t in crossings {
+			let fraction = (t - start) / (end - start);
+			clipped.push((a + (b - a) * fraction).as_vec2());
+		}
+	}
+	clipped
+}
+
 /// Find and store all Voronoi vertices with a unique ID for each one.
 /// Additionally create a map of triangle ids to circumcentre ids
 fn create_voronoi_lookup(
@@ -193,169 +589,375 @@ fn create_voronoi_lookup(
 	let mut voronoi_vertex_lookup = BTreeMap::new();
 	// store the triangle ID and what circumcentre ID is corresponds to
 	let mut triangle_to_circumcentre_ids = BTreeMap::new();
-
-	let mut temp_centre_store = vec![];
-	let mut temp_id_store = vec![];
+	// NB: if you take a square made of two triangles their circumcentres will overlap,
+	// centres are keyed by their position so overlapping ones share an ID
+	let mut centre_ids: BTreeMap<(u32, u32), usize> = BTreeMap::new();
 	for (tri_id, triangle) in triangle_store.iter() {
 		if let Some(circumcircle) = triangle.compute_circumcircle(delaunay_vertex_lookup) {
-			let centre = circumcircle.get_centre();
-			let voronoi_id = voronoi_vertex_lookup.len();
-			// NB: if you take a square made of two triangles their circumcentres will overlap
-			// ignore overlapping circumcentres and link the triangle to the existing stored lookup
-			if !temp_centre_store.contains(centre) {
-				temp_centre_store.push(*centre);
-				temp_id_store.push(voronoi_id);
-
-				voronoi_vertex_lookup.insert(voronoi_id, *centre);
-				triangle_to_circumcentre_ids.insert(*tri_id, voronoi_id);
-			} else {
-				for (i, t) in temp_centre_store.iter().enumerate() {
-					if *t == *centre {
-						let shared_voronoi_id = temp_id_store.get(i).unwrap();
-						triangle_to_circumcentre_ids.insert(*tri_id, *shared_voronoi_id);
-						break;
-					}
-				}
-			}
+			let centre = *circumcircle.get_centre();
+			// adding zero turns -0.0 into 0.0 so equal positions share a key
+			let key = ((centre.x + 0.0).to_bits(), (centre.y + 0.0).to_bits());
+			let voronoi_id = *centre_ids.entry(key).or_insert_with(|| {
+				let id = voronoi_vertex_lookup.len();
+				voronoi_vertex_lookup.insert(id, centre);
+				id
+			});
+			triangle_to_circumcentre_ids.insert(*tri_id, voronoi_id);
 		}
 	}
 	(voronoi_vertex_lookup, triangle_to_circumcentre_ids)
 }
 
-/// Compare the vertices of triangles and identify groupings of IDs whereby 3
-/// or more triangles share a vertex.
+/// Form the cell generated by a Delaunay vertex from the circumcentres of the
+/// triangles around it, in the anti-clockwise order of
+/// [Delaunay2d::vertex_star].
 ///
-/// The grouping forms the key and the value is the Delaunay vertex ID they all
-/// have in common - i.e the generating point of a Voronoi cell of the
-/// triangles
-fn find_shared_sets(
-	triangle_store: &BTreeMap<usize, TriangleNode2d>,
-) -> BTreeMap<BTreeSet<&usize>, &usize> {
-	let mut cell_triangles = BTreeMap::new();
-	for (this_tri_id, this_tri) in triangle_store.iter() {
-		// loop through all vertex IDs
-		for this_vert_id in this_tri.get_vertex_ids() {
-			let mut shared_tri_ids = BTreeSet::from([this_tri_id]);
-			// loop over other triangles
-			for (other_tri_id, other_tri) in triangle_store.iter() {
-				if this_tri_id != other_tri_id && other_tri.get_vertex_ids().contains(this_vert_id)
-				{
-					// triangles share a common vertex ID, store other
-					shared_tri_ids.insert(other_tri_id);
-				}
+/// The fan of triangles around a vertex on the hull is open, the cell is
+/// bounded by a ray perpendicular to the hull edge at each end of the fan.
+/// `None` if a closed cell has fewer than 3 distinct circumcentres
+fn compute_cell_from_triangle_fan(
+	delaunay: &Delaunay2d,
+	vertex_id: usize,
+	triangle_to_circumcentre_ids: &BTreeMap<usize, usize>,
+) -> Option<VoronoiCell2d> {
+	let fan = delaunay.vertex_star(vertex_id);
+	let mut vertex_ids = vec![];
+	for tri_id in fan.iter() {
+		if let Some(circum_id) = triangle_to_circumcentre_ids.get(tri_id) {
+			// avoid duplciates from overlapping circumcentres
+			if !vertex_ids.contains(circum_id) {
+				vertex_ids.push(*circum_id);
 			}
-			if shared_tri_ids.len() >= 3 {
-				// we have found a series of triangles with a common vertex,
-				// their circumcentres are voronoi vertices
-				cell_triangles.insert(shared_tri_ids, this_vert_id);
+		} else {
+			warn!("Failed to lookup circumcentre ID");
+		}
+	}
+	let position = |id: usize| delaunay.get_vertex_lookup()[&id];
+	let (first, last) = (*fan.first()?, *fan.last()?);
+	let first_ids = delaunay.get_triangles()[&first].get_vertex_ids();
+	let i = first_ids.iter().position(|id| *id == vertex_id)?;
+	// the fan of a hull vertex begins with a triangle whose edge leading out
+	// of the vertex is on the hull
+	if delaunay.triangle_neighbours(first)?[i].is_some() {
+		// if duplacites removed and len drops below 3 then the cell is no onger valid
+		if vertex_ids.len() < 3 {
+			return None;
+		}
+		return Some(VoronoiCell2d {
+			vertices: vertex_ids,
+			generating_point: vertex_id,
+			rays: None,
+		});
+	}
+	// and ends with a triangle whose edge leading into the vertex is on the
+	// hull. Triangles sit to the left of their edges so the cell reaches out
+	// to the right of each
+	let last_ids = delaunay.get_triangles()[&last].get_vertex_ids();
+	let j = last_ids.iter().position(|id| *id == vertex_id)?;
+	let leaving = position(first_ids[(i + 1) % 3]) - position(vertex_id);
+	let arriving = position(vertex_id) - position(last_ids[(j + 2) % 3]);
+	let rays = [
+		VoronoiRay2d {
+			start_vertex: *vertex_ids.first()?,
+			direction: Vec2::new(leaving.y, -leaving.x).normalize(),
+		},
+		VoronoiRay2d {
+			start_vertex: *vertex_ids.last()?,
+			direction: Vec2::new(arriving.y, -arriving.x).normalize(),
+		},
+	];
+	Some(VoronoiCell2d {
+		vertices: vertex_ids,
+		generating_point: vertex_id,
+		rays: Some(rays),
+	})
+}
+
+/// A vertex of a cell clipped to a boundary, identified by how it was formed
+/// so the cells meeting at it agree on both its ID and its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BoundedVertex {
+	/// The ID of a circumcentre inside the boundary
+	Circumcentre(usize),
+	/// Where the bisector of two Delaunay vertices, lowest ID first, crosses
+	/// the boundary edge starting at the given corner
+	Crossing(usize, usize, usize),
+	/// The index of a boundary corner
+	Corner(usize),
+}
+
+impl BoundedVertex {
+	/// Find the position of the vertex
+	fn position(
+		&self,
+		boundary: &[DVec2],
+		delaunay_vertex_lookup: &BTreeMap<usize, Vec2>,
+		circumcentres: &BTreeMap<usize, Vec2>,
+	) -> DVec2 {
+		match *self {
+			BoundedVertex::Circumcentre(id) => circumcentres[&id].as_dvec2(),
+			BoundedVertex::Crossing(a, b, edge) => {
+				let a = delaunay_vertex_lookup[&a].as_dvec2();
+				let b = delaunay_vertex_lookup[&b].as_dvec2();
+				let midpoint = (a + b) / 2.0;
+				let bisector = (b - a).perp();
+				let start = boundary[edge];
+				let along = boundary[(edge + 1) % boundary.len()] - start;
+				let fraction = (midpoint - start).perp_dot(bisector) / along.perp_dot(bisector);
+				start + along * fraction.clamp(0.0, 1.0)
 			}
+			BoundedVertex::Corner(index) => boundary[index],
 		}
 	}
-	cell_triangles
 }
-/// From triangle groupings calculate each [VoronoiCell2d] from their
-/// circumcentres
-fn compute_cells_from_triangle_sets(
-	cell_triangles: &BTreeMap<BTreeSet<&usize>, &usize>,
-	voronoi_vertex_lookup: &BTreeMap<usize, Vec2>,
+
+/// An edge of an unclipped cell, part of the bisector between the generating
+/// point and a neighbouring Delaunay vertex. Positions along the edge are
+/// `start + direction * t` for `t` between `lower` and `upper`, the limit of
+/// an edge reaching out to infinity is infinite
+struct CellEdge {
+	/// The Delaunay vertex on the far side of the edge
+	neighbour: usize,
+	/// Position the edge is measured from
+	start: DVec2,
+	/// Direction travelled anti-clockwise around the cell
+	direction: DVec2,
+	/// Where the edge begins along `direction`
+	lower: f64,
+	/// Where the edge ends along `direction`
+	upper: f64,
+	/// Circumcentre IDs at the `lower` and `upper` ends, `None` at infinity
+	ends: [Option<usize>; 2],
+}
+
+/// Walk the triangles around a Delaunay vertex to find the edges of its cell
+/// in anti-clockwise order. The edges of a vertex on the hull begin and end
+/// with rays perpendicular to the hull.
+///
+/// `None` if a triangle around the vertex has no circumcentre
+fn compute_cell_edges(
+	delaunay: &Delaunay2d,
+	vertex_id: usize,
+	circumcentres: &BTreeMap<usize, Vec2>,
 	triangle_to_circumcentre_ids: &BTreeMap<usize, usize>,
-) -> BTreeMap<usize, VoronoiCell2d> {
-	let mut cells = BTreeMap::new();
-	for (tri_ids, generating_point_id) in cell_triangles.iter() {
-		// lookup the circumcentre IDs of each triangle
-		let mut vertex_ids = vec![];
-		for tri_id in tri_ids.iter() {
-			if let Some(circum_id) = triangle_to_circumcentre_ids.get(tri_id) {
-				// avoid duplciates from overlapping circumcentres
-				if !vertex_ids.contains(circum_id) {
-					vertex_ids.push(*circum_id);
+) -> Option<Vec<CellEdge>> {
+	let fan = delaunay.vertex_star(vertex_id);
+	let centre_ids: Vec<usize> = fan
+		.iter()
+		.map(|tri_id| triangle_to_circumcentre_ids.get(tri_id).copied())
+		.collect::<Option<_>>()?;
+	let position = |id: usize| delaunay.get_vertex_lookup()[&id].as_dvec2();
+	let centre = |id: usize| circumcentres[&id].as_dvec2();
+	// the vertex opposite `vertex_id` along the edge leading into it, or out of it
+	let neighbour = |tri_id: usize, leading_in: bool| {
+		let ids = delaunay.get_triangles()[&tri_id].get_vertex_ids();
+		let i = ids.iter().position(|id| *id == vertex_id)?;
+		Some(if leading_in {
+			ids[(i + 2) % 3]
+		} else {
+			ids[(i + 1) % 3]
+		})
+	};
+	let (first, last) = (*fan.first()?, *fan.last()?);
+	let first_ids = delaunay.get_triangles()[&first].get_vertex_ids();
+	let i = first_ids.iter().position(|id| *id == vertex_id)?;
+	let is_open = delaunay.triangle_neighbours(first)?[i].is_none();
+
+	let mut edges = vec![];
+	if is_open {
+		// arrive from infinity along the ray to the right of the hull edge
+		// leading out of the vertex
+		let u = neighbour(first, false)?;
+		let leaving = position(u) - position(vertex_id);
+		edges.push(CellEdge {
+			neighbour: u,
+			start: centre(centre_ids[0]),
+			direction: -DVec2::new(leaving.y, -leaving.x),
+			lower: f64::NEG_INFINITY,
+			upper: 0.0,
+			ends: [None, Some(centre_ids[0])],
+		});
+	}
+	// neighbouring triangles of the fan share the edge leading into the vertex
+	let chain_count = if is_open { fan.len() - 1 } else { fan.len() };
+	for k in 0..chain_count {
+		let (a, b) = (centre_ids[k], centre_ids[(k + 1) % fan.len()]);
+		edges.push(CellEdge {
+			neighbour: neighbour(fan[k], true)?,
+			start: centre(a),
+			direction: centre(b) - centre(a),
+			lower: 0.0,
+			upper: 1.0,
+			ends: [Some(a), Some(b)],
+		});
+	}
+	if is_open {
+		let w = neighbour(last, true)?;
+		let arriving = position(vertex_id) - position(w);
+		let end = centre_ids[fan.len() - 1];
+		edges.push(CellEdge {
+			neighbour: w,
+			start: centre(end),
+			direction: DVec2::new(arriving.y, -arriving.x),
+			lower: 0.0,
+			upper: f64::INFINITY,
+			ends: [Some(end), None],
+		});
+	}
+	Some(edges)
+}
+
+/// Clip the anti-clockwise edges of a cell to a convex anti-clockwise
+/// boundary and find the vertices of the clipped cell in order. Where the
+/// cell leaves the boundary it follows the boundary round to where it next
+/// enters, picking up the corners it passes.
+///
+/// Empty if no edge of the cell crosses the boundary
+fn clip_cell_edges_to_boundary(
+	edges: &[CellEdge],
+	vertex_id: usize,
+	boundary: &[DVec2],
+	delaunay_vertex_lookup: &BTreeMap<usize, Vec2>,
+	circumcentres: &BTreeMap<usize, Vec2>,
+) -> Vec<BoundedVertex> {
+	// the part of each edge inside the boundary and the boundary edges it
+	// enters and leaves through
+	let mut pieces = vec![];
+	'edges: for edge in edges.iter() {
+		let (mut lower, mut upper) = (edge.lower, edge.upper);
+		let (mut entry, mut exit) = (None, None);
+		for j in 0..boundary.len() {
+			let start = boundary[j];
+			// points inwards for an anti-clockwise boundary
+			let normal = (boundary[(j + 1) % boundary.len()] - start).perp();
+			let distance = normal.dot(edge.start - start);
+			let rate = normal.dot(edge.direction);
+			if rate == 0.0 {
+				if distance < 0.0 {
+					continue 'edges;
 				}
-			} else {
-				warn!("Failed to lookup circumcentre ID");
+				continue;
+			}
+			let t = -distance / rate;
+			if rate > 0.0 && t > lower {
+				lower = t;
+				entry = Some(j);
+			} else if rate < 0.0 && t < upper {
+				upper = t;
+				exit = Some(j);
 			}
 		}
-		// if duplacites removed and len drops below 3 then the cell is no onger valid
-		if vertex_ids.len() < 3 {
+		if lower >= upper {
 			continue;
 		}
-		// order the vertex ids in an anti-clockwise fashion about their centre
-		let midpoint = {
-			let mut sum = Vec2::ZERO;
-			for id in vertex_ids.iter() {
-				sum += voronoi_vertex_lookup.get(id).unwrap();
-			}
-			sum / vertex_ids.len() as f32
+		let crossing = |j: usize| {
+			BoundedVertex::Crossing(
+				vertex_id.min(edge.neighbour),
+				vertex_id.max(edge.neighbour),
+				j,
+			)
 		};
-		vertex_ids.sort_by(|a, b| {
-			let a_pos = voronoi_vertex_lookup.get(a).unwrap();
-			let b_pos = voronoi_vertex_lookup.get(b).unwrap();
-			if let Some(ordering) = Vec2::Y
-				.angle_to(*a_pos - midpoint)
-				.partial_cmp(&Vec2::Y.angle_to(*b_pos - midpoint))
-			{
-				ordering
-			} else {
-				warn!("Unable to find Ordering between {} and {}", a, b);
-				Ordering::Less
-			}
-		});
-		let key = cells.len();
-		let cell = VoronoiCell2d {
-			vertices: vertex_ids,
-			generating_point: **generating_point_id,
+		let start = match (entry, edge.ends[0]) {
+			(Some(j), _) => crossing(j),
+			(None, Some(id)) => BoundedVertex::Circumcentre(id),
+			(None, None) => continue,
 		};
-		cells.insert(key, cell);
+		let end = match (exit, edge.ends[1]) {
+			(Some(j), _) => crossing(j),
+			(None, Some(id)) => BoundedVertex::Circumcentre(id),
+			(None, None) => continue,
+		};
+		pieces.push((entry, start, exit, end));
+	}
+	// begin with a piece entering the boundary so the corners between
+	// leaving and re-entering are known
+	if let Some(first_entry) = pieces.iter().position(|piece| piece.0.is_some()) {
+		pieces.rotate_left(first_entry);
 	}
-	cells
+	let position =
+		|vertex: BoundedVertex| vertex.position(boundary, delaunay_vertex_lookup, circumcentres);
+	// corners passed going anti-clockwise from leaving the boundary on one
+	// edge to entering it on another
+	let corners_between = |(exit, leaving): (usize, BoundedVertex),
+	                       (entry, entering): (usize, BoundedVertex)| {
+		let mut corners = vec![];
+		if exit == entry {
+			let along = boundary[(exit + 1) % boundary.len()] - boundary[exit];
+			if along.dot(position(entering)) >= along.dot(position(leaving)) {
+				return corners;
+			}
+		}
+		let mut corner = (exit + 1) % boundary.len();
+		loop {
+			corners.push(BoundedVertex::Corner(corner));
+			if corner == entry {
+				return corners;
+			}
+			corner = (corner + 1) % boundary.len();
+		}
+	};
+	let mut vertices = vec![];
+	let mut leaving = None;
+	for (entry, start, exit, end) in pieces.iter() {
+		if let (Some(left), Some(j)) = (leaving.take(), entry) {
+			vertices.extend(corners_between(left, (*j, *start)));
+		}
+		vertices.push(*start);
+		vertices.push(*end);
+		if let Some(j) = exit {
+			leaving = Some((*j, *end));
+		}
+	}
+	if let (Some(left), Some((Some(j), start, _, _))) = (leaving, pieces.first()) {
+		vertices.extend(corners_between(left, (*j, *start)));
+	}
+	vertices
 }
 
 /// To create a mesh we need a series of triangles describing the mesh.
 /// By applying Delaunay to the vertices of a cell we can
 /// triangulate the triangles that make up the mesh
-fn triangulate_mesh(offset_cell_vertices: &Vec<Vec2>) -> Option<Mesh> {
-	if let Some(delaunay) = Delaunay2d::compute_triangulation_2d(offset_cell_vertices) {
-		let delaunay_triangles = delaunay.get_triangles();
-		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
+fn triangulate_mesh(offset_cell_vertices: &[Vec2]) -> Result<Mesh, MosaicError> {
+	let delaunay = Delaunay2d::compute_triangulation_2d(offset_cell_vertices)?;
+	let delaunay_triangles = delaunay.get_triangles();
+	let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
 
-		// store all the vertices of the mesh
-		let positions: Vec<Vec3> = delaunay_vertex_lookup
-			.values()
-			.map(|v| v.extend(0.0))
-			.collect();
-		let normals = vec![Vec3::Z; positions.len()];
-		let uvs = compute_mesh_uvs(&positions);
-
-		//TODO tests to ensure right number of indices/postions
-		//TODO verify no "hole" in mesh
-		// for each triangle lookup the index in `positions` of each vertex
-		let mut indices = vec![];
-		for (_, tri) in delaunay_triangles.iter() {
-			let ids = tri.get_vertex_ids();
-			// indices are in groupings of 3
-			for id in ids.iter() {
-				let tri_ver = delaunay_vertex_lookup.get(id).unwrap();
-				// find the index in positions of this vertex
-				for (i, p) in positions.iter().enumerate() {
-					if tri_ver.extend(0.0) == *p {
-						indices.push(i as u32);
-					}
+	// store all the vertices of the mesh
+	let positions: Vec<Vec3> = delaunay_vertex_lookup
+		.values()
+		.map(|v| v.extend(0.0))
+		.collect();
+	let normals = vec![Vec3::Z; positions.len()];
+	let uvs = compute_mesh_uvs(&positions);
+
+	//TODO tests to ensure right number of indices/postions
+	//TODO verify no "hole" in mesh
+	// for each triangle lookup the index in `positions` of each vertex
+	let mut indices = vec![];
+	for (_, tri) in delaunay_triangles.iter() {
+		let ids = tri.get_vertex_ids();
+		// indices are in groupings of 3
+		for id in ids.iter() {
+			let tri_ver = delaunay_vertex_lookup.get(id).unwrap();
+			// find the index in positions of this vertex
+			for (i, p) in positions.iter().enumerate() {
+				if tri_ver.extend(0.0) == *p {
+					indices.push(i as u32);
 				}
 			}
 		}
+	}
 
-		let mesh = Mesh::new(
-			PrimitiveTopology::TriangleList,
-			RenderAssetUsages::default(),
-		)
-		.with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
-		.with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
-		.with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
-		.with_inserted_indices(Indices::U32(indices));
+	let mesh = Mesh::new(
+		PrimitiveTopology::TriangleList,
+		RenderAssetUsages::default(),
+	)
+	.with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+	.with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+	.with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+	.with_inserted_indices(Indices::U32(indices));
 
-		Some(mesh)
-	} else {
-		None
-	}
+	Ok(mesh)
 }
 
 /// Each vertex of a mesh requires a UV coordinate. A UV coordinate describes
@@ -635,26 +1237,60 @@ mod tests {
 	// 	assert!(Vec2::ZERO == *generating_point);
 	// }
 	#[test]
-	fn shared_groupings() {
-		let a = 0;
-		let b = 1;
-		let c = 2;
-		let d = 3;
-		let e = 4;
-		let triangle_store = BTreeMap::from([
-			(0, TriangleNode2d::new(a, b, c)),
-			(1, TriangleNode2d::new(a, b, d)),
-			(2, TriangleNode2d::new(a, b, e)),
-			(3, TriangleNode2d::new(e, b, c)),
-			(4, TriangleNode2d::new(d, b, c)),
-		]);
-		let sets = find_shared_sets(&triangle_store);
-		let actual: BTreeMap<BTreeSet<&usize>, &usize> = BTreeMap::from([
-			(BTreeSet::from([&0, &1, &2]), &a),
-			(BTreeSet::from([&0, &1, &2, &3, &4]), &b),
-			(BTreeSet::from([&0, &3, &4]), &c),
-		]);
-		assert_eq!(actual, sets);
+	fn triangle_fans() {
+		// a vertex surrounded by a square of four triangles
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(0.0, 10.0),
+			Vec2::new(-10.0, 0.0),
+			Vec2::new(0.0, -10.0),
+		];
+		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let (voronoi_vertex_lookup, triangle_to_circumcentre_ids) =
+			create_voronoi_lookup(delaunay.get_triangles(), delaunay.get_vertex_lookup());
+		let cell =
+			compute_cell_from_triangle_fan(&delaunay, 0, &triangle_to_circumcentre_ids).unwrap();
+		// circumcentres follow the triangles anti-clockwise around the vertex
+		let expected: Vec<usize> = delaunay
+			.vertex_star(0)
+			.iter()
+			.map(|tri_id| triangle_to_circumcentre_ids[tri_id])
+			.collect();
+		assert_eq!(&expected, cell.get_vertex_ids());
+		let positions: Vec<Vec2> = expected
+			.iter()
+			.map(|id| voronoi_vertex_lookup[id])
+			.collect();
+		let twice_area: f32 = positions
+			.iter()
+			.zip(positions.iter().cycle().skip(1))
+			.map(|(a, b)| a.perp_dot(*b))
+			.sum();
+		assert_eq!(200.0, twice_area);
+		assert!(cell.is_closed());
+		// hull vertices have open cells bounded by rays pointing out across
+		// the hull edges either side of them
+		let cell =
+			compute_cell_from_triangle_fan(&delaunay, 1, &triangle_to_circumcentre_ids).unwrap();
+		assert!(cell.is_open());
+		assert_eq!(2, cell.get_vertex_ids().len());
+		let [first, second] = cell.get_rays().unwrap();
+		assert_eq!(cell.get_vertex_ids()[0], first.get_start_vertex());
+		assert_eq!(cell.get_vertex_ids()[1], second.get_start_vertex());
+		let diagonal = 0.5_f32.sqrt();
+		assert!(
+			first
+				.get_direction()
+				.distance(Vec2::new(diagonal, diagonal))
+				< 1e-6
+		);
+		assert!(
+			second
+				.get_direction()
+				.distance(Vec2::new(diagonal, -diagonal))
+				< 1e-6
+		);
 	}
 
 	#[test]
@@ -682,7 +1318,30 @@ mod tests {
 			Vec2::new(-5.0, -5.0),
 		];
 		let mesh = triangulate_mesh(&offset_cell_vertices);
-		assert!(mesh.is_some());
+		assert!(mesh.is_ok());
+	}
+	#[test]
+	fn boundary_too_few_vertices() {
+		let boundary = vec![Vec2::new(10.0, -10.0), Vec2::new(10.0, 10.0)];
+		assert!(validate_boundary(&boundary).is_err());
+	}
+	#[test]
+	fn boundary_clockwise() {
+		let boundary = vec![
+			Vec2::new(-10.0, -10.0),
+			Vec2::new(-10.0, 10.0),
+			Vec2::new(10.0, 10.0),
+			Vec2::new(10.0, -10.0),
+		];
+		assert_eq!(Ok(()), validate_boundary(&boundary));
+		// cells are built within the boundary reversed to be anti-clockwise
+		let reversed: Vec<Vec2> = boundary.iter().rev().copied().collect();
+		let expected: Vec<DVec2> = reversed.iter().map(|v| v.as_dvec2()).collect();
+		assert_eq!(
+			Ok(expected.clone()),
+			convex_anti_clockwise_boundary(&boundary)
+		);
+		assert_eq!(Ok(expected), convex_anti_clockwise_boundary(&reversed));
 	}
 	#[test]
 	fn voronoi_lookup() {
@@ -739,17 +1398,22 @@ mod tests {
 		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
 		let triangle_store = delaunay.get_triangles();
 		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
-		let (voronoi_vertex_lookup, triangle_to_circumcentre_ids) =
+		let (_, triangle_to_circumcentre_ids) =
 			create_voronoi_lookup(triangle_store, delaunay_vertex_lookup);
-		let cell_triangles = find_shared_sets(triangle_store);
-
-		let cells = compute_cells_from_triangle_sets(
-			&cell_triangles,
-			&voronoi_vertex_lookup,
-			&triangle_to_circumcentre_ids,
-		);
-
-		assert!(cells.len() == 3);
+		let cells: BTreeMap<usize, VoronoiCell2d> = delaunay_vertex_lookup
+			.keys()
+			.filter_map(|vertex_id| {
+				compute_cell_from_triangle_fan(&delaunay, *vertex_id, &triangle_to_circumcentre_ids)
+					.map(|cell| (*vertex_id, cell))
+			})
+			.collect();
+		// every point has a cell, only the one inside the hull is closed
+		assert_eq!(9, cells.len());
+		assert_eq!(1, cells.values().filter(|cell| cell.is_closed()).count());
+		// cells are keyed by their generating point
+		for (id, cell) in cells.iter() {
+			assert_eq!(id, cell.get_generating_point());
+		}
 	}
 	#[test]
 	fn clipping() {
@@ -774,4 +1438,218 @@ mod tests {
 		];
 		assert_eq!(actual, clipped)
 	}
+	#[test]
+	fn collinear_strips() {
+		let points = vec![
+			Vec2::new(0.0, 0.0),
+			Vec2::new(-20.0, 0.0),
+			Vec2::new(20.0, 0.0),
+			Vec2::new(0.0, 0.0),
+		];
+		let delaunay = Delaunay2d::compute_triangulation_2d_allow_collinear(&points).unwrap();
+		// clockwise boundaries are accepted
+		let boundary = vec![
+			Vec2::new(-50.0, 10.0),
+			Vec2::new(50.0, 10.0),
+			Vec2::new(50.0, -10.0),
+			Vec2::new(-50.0, -10.0),
+		];
+		let voronoi = Voronoi2d::from_collinear_delaunay_2d(&delaunay, &boundary).unwrap();
+		assert_eq!(3, voronoi.get_cells().len());
+		// 4 boundary corners and 2 points along the top and bottom
+		assert_eq!(8, voronoi.get_vertex_lookup().len());
+		let lookup = voronoi.get_vertex_lookup();
+		let mut total_area = 0.0;
+		for (id, cell) in voronoi.get_cells().iter() {
+			assert_eq!(id, cell.get_generating_point());
+			let vertices: Vec<Vec2> = cell.get_vertex_ids().iter().map(|v| lookup[v]).collect();
+			let area: f32 = vertices
+				.iter()
+				.zip(vertices.iter().cycle().skip(1))
+				.map(|(a, b)| a.perp_dot(*b) / 2.0)
+				.sum();
+			// anti-clockwise cells
+			assert!(area > 0.0);
+			total_area += area;
+		}
+		let middle = &voronoi.get_cells()[&0];
+		let middle_vertices: Vec<Vec2> =
+			middle.get_vertex_ids().iter().map(|v| lookup[v]).collect();
+		assert!(middle_vertices.contains(&Vec2::new(-10.0, 10.0)));
+		assert!(middle_vertices.contains(&Vec2::new(10.0, -10.0)));
+		// the strips tile the boundary
+		assert_eq!(2000.0, total_area);
+		assert!(voronoi.as_bevy2d_meshes().is_ok());
+
+		let triangulated =
+			Delaunay2d::compute_triangulation_2d(&[Vec2::ZERO, Vec2::X, Vec2::Y]).unwrap();
+		assert_eq!(
+			Some(MosaicError::EmptyTessellation),
+			Voronoi2d::from_collinear_delaunay_2d(&triangulated, &boundary).err()
+		);
+	}
+	#[test]
+	fn bounded_cells() {
+		use rand::{Rng, SeedableRng};
+		use rand_chacha::ChaCha20Rng;
+		let mut rng = ChaCha20Rng::seed_from_u64(987654321);
+		let points: Vec<Vec2> = (0..300)
+			.map(|_| Vec2::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0)))
+			.collect();
+		let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let cell_area = |voronoi: &Voronoi2d, cell: &VoronoiCell2d| {
+			let lookup = voronoi.get_vertex_lookup();
+			let vertices: Vec<DVec2> = cell
+				.get_vertex_ids()
+				.iter()
+				.map(|v| lookup[v].as_dvec2())
+				.collect();
+			vertices
+				.iter()
+				.zip(vertices.iter().cycle().skip(1))
+				.map(|(a, b)| a.perp_dot(*b) / 2.0)
+				.sum::<f64>()
+		};
+		let rectangle = vec![
+			Vec2::new(-100.0, -100.0),
+			Vec2::new(1100.0, -100.0),
+			Vec2::new(1100.0, 1100.0),
+			Vec2::new(-100.0, 1100.0),
+		];
+		let voronoi = Voronoi2d::from_delaunay_2d_bounded(&delaunay, &rectangle).unwrap();
+		let lookup = voronoi.get_vertex_lookup();
+		// every site has a closed cell containing it
+		assert_eq!(points.len(), voronoi.get_cells().len());
+		let mut total_area = 0.0;
+		let mut edges = std::collections::BTreeSet::new();
+		for (id, cell) in voronoi.get_cells().iter() {
+			assert!(cell.is_closed());
+			let vertices: Vec<Vec2> = cell.get_vertex_ids().iter().map(|v| lookup[v]).collect();
+			for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+				assert!((b - a).perp_dot(points[*id] - a) > 0.0);
+			}
+			total_area += cell_area(&voronoi, cell);
+			let ids = cell.get_vertex_ids();
+			for (a, b) in ids.iter().zip(ids.iter().cycle().skip(1)) {
+				assert!(edges.insert((*a, *b)));
+			}
+		}
+		// the cells tile the boundary
+		assert!((total_area - 1200.0 * 1200.0).abs() < 1.0);
+		let on_boundary =
+			|v: Vec2| v.x == -100.0 || v.x == 1100.0 || v.y == -100.0 || v.y == 1100.0;
+		for (a, b) in edges.iter() {
+			assert!(
+				edges.contains(&(*b, *a)) || (on_boundary(lookup[a]) && on_boundary(lookup[b]))
+			);
+		}
+		assert_eq!(points.len(), voronoi.as_bevy2d_meshes().unwrap().len());
+		// cells share a vertex wherever they meet at the same position
+		let positions: std::collections::BTreeSet<[u32; 2]> = lookup
+			.values()
+			.map(|v| v.to_array().map(f32::to_bits))
+			.collect();
+		assert_eq!(lookup.len(), positions.len());
+
+		// a clockwise boundary gives the same cells while a concave one is
+		// rejected
+		let clockwise: Vec<Vec2> = rectangle.iter().rev().copied().collect();
+		let reversed = Voronoi2d::from_delaunay_2d_bounded(&delaunay, &clockwise).unwrap();
+		assert_eq!(points.len(), reversed.get_cells().len());
+		for (id, cell) in reversed.get_cells().iter() {
+			let expected = cell_area(&voronoi, &voronoi.get_cells()[id]);
+			assert!((cell_area(&reversed, cell) - expected).abs() < 1e-6);
+		}
+		let l_shape = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(1000.0, 0.0),
+			Vec2::new(1000.0, 500.0),
+			Vec2::new(500.0, 500.0),
+			Vec2::new(500.0, 1000.0),
+			Vec2::new(0.0, 1000.0),
+		];
+		assert_eq!(
+			Some(MosaicError::InvalidBoundary("boundary must be convex")),
+			Voronoi2d::from_delaunay_2d_bounded(&delaunay, &l_shape).err()
+		);
+		// a pentagram only turns left but crosses itself
+		let pentagram: Vec<Vec2> = [0, 2, 4, 1, 3]
+			.map(|i| {
+				let angle = i as f32 * std::f32::consts::TAU / 5.0;
+				Vec2::new(500.0, 500.0) + Vec2::from_angle(angle) * 150.0
+			})
+			.to_vec();
+		assert_eq!(
+			Some(MosaicError::InvalidBoundary("boundary must be convex")),
+			Voronoi2d::from_delaunay_2d_bounded(&delaunay, &pentagram).err()
+		);
+
+		// a cell edge crossing the boundary at a corner shares the vertex of
+		// the corner instead of adding a zero length edge
+		let corner_delaunay = Delaunay2d::compute_triangulation_2d(&[
+			Vec2::new(10.0, 10.0),
+			Vec2::new(30.0, 30.0),
+			Vec2::new(35.0, 5.0),
+		])
+		.unwrap();
+		let square = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(40.0, 0.0),
+			Vec2::new(40.0, 40.0),
+			Vec2::new(0.0, 40.0),
+		];
+		let voronoi = Voronoi2d::from_delaunay_2d_bounded(&corner_delaunay, &square).unwrap();
+		let lookup = voronoi.get_vertex_lookup();
+		for cell in voronoi.get_cells().values() {
+			let ids = cell.get_vertex_ids();
+			for (a, b) in ids.iter().zip(ids.iter().cycle().skip(1)) {
+				assert_ne!(lookup[a], lookup[b]);
+			}
+		}
+		let total_area: f64 = voronoi
+			.get_cells()
+			.values()
+			.map(|cell| cell_area(&voronoi, cell))
+			.sum();
+		assert!((total_area - 1600.0).abs() < 1e-3);
+
+		// a hexagon inside the points skips the sites whose cells miss it
+		let hexagon: Vec<Vec2> = (0..6)
+			.map(|i| {
+				let angle = i as f32 * std::f32::consts::PI / 3.0;
+				Vec2::new(500.0, 500.0) + Vec2::from_angle(angle) * 300.0
+			})
+			.collect();
+		let voronoi = Voronoi2d::from_delaunay_2d_bounded(&delaunay, &hexagon).unwrap();
+		assert!(voronoi.get_cells().len() < points.len());
+		let total_area: f64 = voronoi
+			.get_cells()
+			.values()
+			.map(|cell| cell_area(&voronoi, cell))
+			.sum();
+		let hexagon_area = 3.0 * 3.0_f64.sqrt() / 2.0 * 300.0 * 300.0;
+		assert!((total_area - hexagon_area).abs() < 1.0);
+
+		// a boundary inside a single cell becomes that cell
+		let small = [
+			Vec2::new(10.0, 10.0),
+			Vec2::new(20.0, 10.0),
+			Vec2::new(20.0, 20.0),
+		];
+		let triangle = Delaunay2d::compute_triangulation_2d(&[
+			Vec2::ZERO,
+			Vec2::new(500.0, 0.0),
+			Vec2::new(0.0, 500.0),
+		])
+		.unwrap();
+		let voronoi = Voronoi2d::from_delaunay_2d_bounded(&triangle, &small).unwrap();
+		assert_eq!(1, voronoi.get_cells().len());
+		assert_eq!(3, voronoi.get_cells()[&0].get_vertex_ids().len());
+		assert_eq!(
+			Some(MosaicError::InvalidBoundary(
+				"a boundary requires at least 3 vertices"
+			)),
+			Voronoi2d::from_delaunay_2d_bounded(&triangle, &small[..2]).err()
+		);
+	}
 }
@@ -3,7 +3,12 @@
 //!
 //! In this case we restrict computations to those of tetrahedrons
 
-use bevy::prelude::*;
+use bevy::{
+	math::{DMat4, DVec3, DVec4},
+	prelude::*,
+};
+
+use crate::utilities::{insphere, orient3d};
 
 /// Describes a sphere whereby the vertices of a polyhedron sit upon its
 /// surface
@@ -12,12 +17,23 @@ pub struct Circumsphere {
 	circumcentre: Vec3,
 	/// Sphere radius squared
 	radius_squared: f32,
+	/// The tetrahedron vertices that sit on the surface, used for exact
+	/// containment tests
+	vertices: [Vec3; 4],
+	/// Whether the vertices are positively oriented, see [orient3d]
+	is_positive: bool,
 }
 
 impl Circumsphere {
 	/// From a series of tetrahedron vertices calculate the [Circumsphere] if
-	/// it exists
+	/// it exists, coplanar vertices do not have one
 	pub fn new(vertex_a: Vec3, vertex_b: Vec3, vertex_c: Vec3, vertex_d: Vec3) -> Option<Self> {
+		let orientation = orient3d(vertex_a, vertex_b, vertex_c, vertex_d);
+		if orientation == 0.0 {
+			return None;
+		}
+		let vertices = [vertex_a, vertex_b, vertex_c, vertex_d];
+		let is_positive = orientation > 0.0;
 		let (a_x, a_y, a_z) = (vertex_a.x, vertex_a.y, vertex_a.z);
 		let (b_x, b_y, b_z) = (vertex_b.x, vertex_b.y, vertex_b.z);
 		let (c_x, c_y, c_z) = (vertex_c.x, vertex_c.y, vertex_c.z);
@@ -80,9 +96,32 @@ impl Circumsphere {
 			Some(Circumsphere {
 				circumcentre: Vec3::new(x, y, z),
 				radius_squared,
+				vertices,
+				is_positive,
 			})
 		} else {
-			None
+			// the tetrahedron is too small for f32 precision, fall back to f64
+			let [a, b, c, d] = vertices.map(|v| v.as_dvec3());
+			let lengths_sq = DVec4::new(
+				a.length_squared(),
+				b.length_squared(),
+				c.length_squared(),
+				d.length_squared(),
+			);
+			let xs = DVec4::new(a.x, b.x, c.x, d.x);
+			let ys = DVec4::new(a.y, b.y, c.y, d.y);
+			let zs = DVec4::new(a.z, b.z, c.z, d.z);
+			let det_a = DMat4::from_cols(xs, ys, zs, DVec4::ONE).determinant();
+			let det_x = DMat4::from_cols(lengths_sq, ys, zs, DVec4::ONE).determinant();
+			let det_y = -DMat4::from_cols(lengths_sq, xs, zs, DVec4::ONE).determinant();
+			let det_z = DMat4::from_cols(lengths_sq, xs, ys, DVec4::ONE).determinant();
+			let centre = DVec3::new(det_x, det_y, det_z) / (2.0 * det_a);
+			Some(Circumsphere {
+				circumcentre: centre.as_vec3(),
+				radius_squared: (centre - a).length_squared() as f32,
+				vertices,
+				is_positive,
+			})
 		}
 	}
 	/// Get the centre of the circumsphere
@@ -93,19 +132,19 @@ impl Circumsphere {
 	pub fn get_radius_squared(&self) -> f32 {
 		self.radius_squared
 	}
-	/// Is the `point` position within the sphere
+	/// Is the `point` position within the sphere, a point sat exactly on the
+	/// surface is not considered within.
+	///
+	/// This uses the exact [insphere] predicate against the tetrahedron
+	/// vertices rather than the rounded centre and radius
 	pub fn is_point_within_sphere(&self, point: &Vec3) -> bool {
-		//TODO is there a way of informing tolerance based on the density/closeness of the data set?
-		// due to nature of floating points we introduce a
-		// tolerance factor to a handle cases where
-		// the point delta is extremely close to the
-		// radius squared
-		let tolerance = 0.001;
-		//(x - x_c)^2 + (y - y_c)^2 + (z - z_c)^2 > r^2
-		(point.x - self.get_centre().x).powf(2.0)
-			+ (point.y - self.get_centre().y).powf(2.0)
-			+ (point.z - self.get_centre().z).powf(2.0)
-			< (self.get_radius_squared() * (1.0 - tolerance))
+		let [a, b, c, d] = self.vertices;
+		let det = insphere(a, b, c, d, *point);
+		if self.is_positive {
+			det > 0.0
+		} else {
+			det < 0.0
+		}
 	}
 }
 
@@ -148,4 +187,26 @@ mod tests {
 		let point = Vec3::new(0.0, -15.0, 0.0);
 		assert!(!c.is_point_within_sphere(&point));
 	}
+	#[test]
+	fn point_on_surface_is_not_within() {
+		// corners of a cube are all on the same sphere
+		let vertex_a = Vec3::new(100.0, 100.0, 100.0);
+		let vertex_b = Vec3::new(101.0, 100.0, 100.0);
+		let vertex_c = Vec3::new(100.0, 101.0, 100.0);
+		let vertex_d = Vec3::new(100.0, 100.0, 101.0);
+		let point = Vec3::new(101.0, 101.0, 101.0);
+		let c = Circumsphere::new(vertex_a, vertex_b, vertex_c, vertex_d).unwrap();
+		assert!(!c.is_point_within_sphere(&point));
+		let c = Circumsphere::new(vertex_b, vertex_a, vertex_c, vertex_d).unwrap();
+		assert!(!c.is_point_within_sphere(&point));
+		assert!(c.is_point_within_sphere(&Vec3::new(100.5, 100.5, 100.5)));
+	}
+	#[test]
+	fn coplanar_circumsphere_is_none() {
+		let vertex_a = Vec3::new(10.0, 0.0, 0.0);
+		let vertex_b = Vec3::new(0.0, 0.0, 20.0);
+		let vertex_c = Vec3::new(-10.0, 0.0, 0.0);
+		let vertex_d = Vec3::new(0.0, 0.0, -10.0);
+		assert!(Circumsphere::new(vertex_a, vertex_b, vertex_c, vertex_d).is_none());
+	}
 }
@@ -4,251 +4,182 @@
 //! circumspheres of these tetrahedra and ensure that the spheres do not
 //! contain any data points
 //!
+//! Rather than enclosing the points in a large imaginary super tetrahedron,
+//! each face of the outer hull is treated as a ghost tetrahedron joined to a
+//! vertex at infinity. The circumsphere of a ghost tetrahedron is the
+//! half-space beyond its face, so a point outside of the tetrahedralization
+//! replaces every ghost tetrahedron whose face it can see. Together with the
+//! exact predicates this means every point can be inserted, whatever the
+//! spread or magnitude of the points.
+//!
+//! The ID of each vertex is the index of the point in the supplied data set
+//! that created it. Points that were merged as duplicates do not have a
+//! vertex of their own, instead [Delaunay3d::get_vertex_id] resolves them to
+//! the vertex they were merged into.
+//!
 
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{mosaic_3d::circumsphere::Circumsphere, mosaic_3d::tetrahedron_node::TetrahedronNode};
-use bevy::prelude::*;
+use crate::{
+	error::MosaicError,
+	mosaic_3d::circumsphere::Circumsphere,
+	mosaic_3d::edge_node3d::EdgeNode3d,
+	mosaic_3d::tetrahedron_node::TetrahedronNode,
+	utilities::{find_duplicate_points_3d, minimum_spanning_edges, orient3d},
+};
+use bevy::{math::DVec3, prelude::*};
+
+/// ID of the vertex at infinity shared by every ghost tetrahedron, it sits at
+/// the top end of the ID range so that it never collides with the index of a
+/// supplied point
+const GHOST_VERTEX_ID: usize = usize::MAX;
 
 /// Describes the tetrahedralization of a series of data points. Tetrahedra and
-/// vertices are stored with unique IDs
+/// vertices are stored with unique IDs, a vertex ID is the index of the
+/// supplied point that it represents
 pub struct Delaunay3d {
 	/// Uniquely ID'ed tetrahedra nodes
 	tetrahedra: BTreeMap<usize, TetrahedronNode>,
-	/// Uniquely ID'ed positions in space of each vertex
+	/// Positions in space of each vertex, ID'ed by the index of their
+	/// supplied point
 	vertex_lookup: BTreeMap<usize, Vec3>,
+	/// Index of each supplied point that was merged into another point and
+	/// the vertex ID of the point it was merged into
+	merged_points: BTreeMap<usize, usize>,
 }
 
 impl Delaunay3d {
 	/// From a series of 3d points in space calculate the Delaunay Tetrahedralization
-	pub fn compute_triangulation_3d(points: &[Vec3]) -> Option<Self> {
+	///
+	/// Fails if fewer than 4 points are supplied, a point has a non-finite
+	/// coordinate, every point is coplanar or no tetrahedra could be formed
+	///
+	/// Points which exactly duplicate an earlier point are merged into it,
+	/// see [Delaunay3d::get_merged_points], every other point becomes a
+	/// vertex of at least one tetrahedron
+	pub fn compute_triangulation_3d(points: &[Vec3]) -> Result<Self, MosaicError> {
+		Delaunay3d::compute_triangulation_3d_with_merge_tolerance(points, 0.0)
+	}
+	/// From a series of 3d points in space calculate the Delaunay
+	/// Tetrahedralization, any point within `merge_tolerance` distance of an
+	/// earlier point is merged into that earlier point rather than becoming a
+	/// vertex of its own.
+	///
+	/// The merged points can be found with [Delaunay3d::get_merged_points]
+	pub fn compute_triangulation_3d_with_merge_tolerance(
+		points: &[Vec3],
+		merge_tolerance: f32,
+	) -> Result<Self, MosaicError> {
 		if points.len() < 4 {
-			error!(
-				"Minimum of 4 points required for tetrahedralization, supplied {} points",
-				points.len()
-			);
-			return None;
-		}
-		//TODO ensure no duplicates in points?
-		// identify spacial boundaries
-		let (minimum_world_dimensions, maximum_world_dimensions) = compute_dimension_bounds(points);
-
-		// compute the positions of a super tetrahedron that encompasses all points in space
-		// [mid_up, bottom_right, top_right, top_left, bottom_left, mid_down]
-		// [ up, down, top, bottom, left, right]
-		let super_tetra =
-			compute_super_tetrahedra(points, &minimum_world_dimensions, &maximum_world_dimensions);
-
-		// store vertices with a unique id
-		let mut vertex_lookup = BTreeMap::from([
-			(0, super_tetra[0]),
-			(1, super_tetra[1]),
-			(2, super_tetra[2]),
-			(3, super_tetra[3]),
-			(4, super_tetra[4]),
-			(5, super_tetra[5]),
-		]);
-
-		// store tetrahedra starting with the super 4
-		let mut tetrahedra = BTreeSet::from([
-			TetrahedronNode::new(0, 5, 2, 4),
-			TetrahedronNode::new(0, 5, 3, 4),
-			TetrahedronNode::new(1, 5, 2, 4),
-			TetrahedronNode::new(1, 5, 3, 4),
-		]);
-
-		let mut problematic_points = vec![];
-		// add each point at a time to the triangulation
-		for point in points {
-			// find tetrahedra that are not Delaunay
-			let bad_tetrahedra = find_bad_tetrahedra(point, &tetrahedra, &vertex_lookup);
-
-			if bad_tetrahedra.is_empty() {
-				// if empty then the point is in a hole where previous tetrahedralizations failed to produce delaunay tetras that could fill the void
-				// store the point to be retried at the end
-				problematic_points.push(point);
-			} else {
-				// store the point with a unique ID
-				let new_point_id = vertex_lookup.len();
-				vertex_lookup.insert(new_point_id, *point);
-
-				// remove any bad tetrahedrons from the set
-				tetrahedra.retain(|t| !bad_tetrahedra.contains(t));
-
-				// removing bad tetras creates a hole around the point, we want
-				// to build new tetrahedrons with the point to
-				// progress the tetrahedralization
-				//
-				// store each triangle face
-				let mut face_triangles = vec![];
-				// store each duplicate triangle face
-				let mut duplicate_face_triangles = vec![];
-				for bad_tetra in bad_tetrahedra.iter() {
-					let faces = bad_tetra.get_triangle_node_3d_faces();
-					for face in faces.iter() {
-						if !face_triangles.contains(face) {
-							face_triangles.push(*face);
-						} else {
-							duplicate_face_triangles.push(*face);
-						}
-					}
-				}
-				// remove duplicate faces as that face crosses the polyhedral hole
-				face_triangles.retain(|f| !duplicate_face_triangles.contains(f));
-
-				// construct new tetrahedra from the point and each triangle face
-				let mut new_tetras = vec![];
-				for tri in face_triangles {
-					new_tetras.push(TetrahedronNode::new(
-						new_point_id,
-						tri.get_vertex_a_id(),
-						tri.get_vertex_b_id(),
-						tri.get_vertex_c_id(),
-					));
-				}
-
-				//TODO is this needed?
-				// only store a new tetra if it is Delaunay - test to
-				// // ensure it doesn't intersect with any existing tetras
-				while let Some(n_tet) = new_tetras.pop() {
-					let mut is_valid = true;
-
-					// // if an edge of a proposed tetra intersects an existing tetra
-					// // then the proposed is not Delaunay
-					// for edge in n_tet.get_edges() {
-					// 	for tetra in tetrahedra.iter() {
-					// 		for face in tetra.get_triangle_node_3d_faces() {
-					// 			if face.does_edge_intersect_id(&edge, &vertex_lookup) {
-					// 				is_valid = false;
-					// 			}
-					// 		}
-					// 	}
-					// }
-					// // as edges are allowed to touch faces/vertices we
-					// // perform an additional check to verify that a face
-					// // doesn't slice into another face - use a bisecting
-					// // line down the middle of each face of the
-					// // proposed new tetra and check for intersection
-					// for face in n_tet.get_triangle_node_3d_faces().iter() {
-					// 	let a = vertex_lookup.get(&face.get_vertex_a_id()).unwrap();
-					// 	let b = vertex_lookup.get(&face.get_vertex_b_id()).unwrap();
-					// 	let c = vertex_lookup.get(&face.get_vertex_c_id()).unwrap();
-
-					// 	let edge_vertex_a = a;
-					// 	let edge_vertex_b = (b + c) / 2.0;
-					// 	for tetra in tetrahedra.iter() {
-					// 		for tetra_face in tetra.get_triangle_node_3d_faces() {
-					// 			let tri_vertex_a =
-					// 				vertex_lookup.get(&tetra_face.get_vertex_a_id()).unwrap();
-					// 			let tri_vertex_b =
-					// 				vertex_lookup.get(&tetra_face.get_vertex_b_id()).unwrap();
-					// 			let tri_vertex_c =
-					// 				vertex_lookup.get(&tetra_face.get_vertex_c_id()).unwrap();
-					// 			if tetra_face.does_edge_intersect(
-					// 				tri_vertex_a,
-					// 				tri_vertex_b,
-					// 				tri_vertex_c,
-					// 				edge_vertex_a,
-					// 				&edge_vertex_b,
-					// 			) {
-					// 				is_valid = false;
-					// 			}
-					// 		}
-					// 	}
-					// }
-					// if the tetra has no circumsphere then consider it invalid,
-					// i.e its vertices are coplanar so it is degenerate
-					if n_tet.compute_circumsphere(&vertex_lookup).is_none() {
-						is_valid = false;
-					}
-					if n_tet.is_degenerate(&vertex_lookup) {
-						is_valid = false;
-					}
-					if is_valid {
-						tetrahedra.insert(n_tet);
-					}
-				}
-			}
-		}
-
-		//TODO retry adding the problematic points
-		if !problematic_points.is_empty() {
-			warn!(
-				"Number of problematic points ignored {}",
-				problematic_points.len()
-			);
+			return Err(MosaicError::InsufficientPoints {
+				required: 4,
+				supplied: points.len(),
+			});
 		}
-
-		// remove any tetrahedra that use vertices of the starting
-		// super-tetrahedra - these were not real points in the data set,
-		// merely an initialisation to kick start triangulation
-		let mut count: usize = 0;
-		let mut final_tetrahedra = BTreeMap::new();
-		for tet in tetrahedra {
-			// IDs of starting vertices
-			let s_a = 0;
-			let s_b = 1;
-			let s_c = 2;
-			let s_d = 3;
-			let s_e = 4;
-			let s_f = 5;
-
-			if !tet.get_vertex_ids().contains(&s_a)
-				&& !tet.get_vertex_ids().contains(&s_b)
-				&& !tet.get_vertex_ids().contains(&s_c)
-				&& !tet.get_vertex_ids().contains(&s_d)
-				&& !tet.get_vertex_ids().contains(&s_e)
-				&& !tet.get_vertex_ids().contains(&s_f)
-			{
-				final_tetrahedra.insert(count, tet);
-				count += 1;
-			}
+		if let Some(index) = points.iter().position(|p| !p.is_finite()) {
+			return Err(MosaicError::NonFiniteCoordinate { index });
 		}
-
-		//verify all tetra are Delaunay qualified
-		// for t in final_tetrahedra.iter() {
-		// 	for (p, point) in points.iter().enumerate() {
-		// 		if p == points.len() -1 {
-		// 		// break;
-		// 		}
-		// 		if let Some(c) = t.compute_circumsphere() {
-		// 			if c.is_point_within_sphere(point) {
-		// 				error!("point is in a sphere {}", point);
-		// 				error!("centre {}", c.get_centre());
-		// 				error!("length {}", (point-c.get_centre()).length());
-		// 				error!("radius {}", c.get_radius_squared().sqrt());
-		// 			}
-		// 		}
-		// 	}
-		// }
-
-		// remove the super tetra vertices from the vertex lookup
-		vertex_lookup.remove(&0);
-		vertex_lookup.remove(&1);
-		vertex_lookup.remove(&2);
-		vertex_lookup.remove(&3);
-		vertex_lookup.remove(&4);
-		vertex_lookup.remove(&5);
+		// duplicate points cannot form tetrahedra, identify them so they can
+		// be skipped
+		let duplicates = find_duplicate_points_3d(points, merge_tolerance);
+		let insertion_order: Vec<usize> = (0..points.len())
+			.filter(|i| !duplicates.contains_key(i))
+			.collect();
+		let unique_points: Vec<Vec3> = insertion_order.iter().map(|i| points[*i]).collect();
+		let Some(first) = find_first_tetrahedron(&unique_points) else {
+			return Err(MosaicError::CoplanarPoints);
+		};
+		let first = first.map(|i| insertion_order[i]);
+		let (tetrahedra, vertex_lookup) = tetrahedralize(points, first, &insertion_order);
+		// the ghost tetrahedra only describe the hull, drop them
+		let final_tetrahedra: BTreeMap<usize, TetrahedronNode> = tetrahedra
+			.into_iter()
+			.filter(|tet| !tet.get_vertex_ids().contains(&GHOST_VERTEX_ID))
+			.enumerate()
+			.collect();
 
 		if !final_tetrahedra.is_empty() {
-			Some(Delaunay3d {
+			Ok(Delaunay3d {
 				tetrahedra: final_tetrahedra,
 				vertex_lookup,
+				merged_points: duplicates,
 			})
 		} else {
-			warn!("No tetrahedralization found");
-			None
+			Err(MosaicError::EmptyTriangulation)
 		}
 	}
 	/// Get a refernce to the tetrahedron map
 	pub fn get_tetrahedra(&self) -> &BTreeMap<usize, TetrahedronNode> {
 		&self.tetrahedra
 	}
-	/// Get a refernce to the map of vertex IDs and their position
+	/// Get a refernce to the map of vertex IDs and their position. Each ID
+	/// is the index of the supplied point the vertex was created from
 	pub fn get_vertex_lookup(&self) -> &BTreeMap<usize, Vec3> {
 		&self.vertex_lookup
 	}
+	/// Get a reference to the map of supplied point indices that were merged
+	/// into another point as duplicates. The value is the vertex ID of the
+	/// surviving point
+	pub fn get_merged_points(&self) -> &BTreeMap<usize, usize> {
+		&self.merged_points
+	}
+	/// Find the vertex ID that represents the supplied point at `point_index`.
+	///
+	/// This is the index itself unless the point was merged into another as a
+	/// duplicate, in which case the surviving vertex ID is returned. `None`
+	/// means the point is not part of the tetrahedralization
+	pub fn get_vertex_id(&self, point_index: usize) -> Option<usize> {
+		if self.vertex_lookup.contains_key(&point_index) {
+			Some(point_index)
+		} else {
+			self.merged_points.get(&point_index).copied()
+		}
+	}
+	/// Find the Euclidean minimum spanning tree, the shortest set of edges
+	/// joining every vertex without a loop. It is made of edges of the
+	/// tetrahedralization, they are listed from the shortest to the longest
+	pub fn minimum_spanning_tree(&self) -> Vec<EdgeNode3d> {
+		let mut edges = BTreeSet::new();
+		for tetra in self.tetrahedra.values() {
+			for edge in tetra.get_edges() {
+				let (a, b) = (edge.get_vertex_a_id(), edge.get_vertex_b_id());
+				edges.insert((a.min(b), a.max(b)));
+			}
+		}
+		let edges = edges
+			.into_iter()
+			.map(|(a, b)| {
+				let length = self.vertex_lookup[&a]
+					.as_dvec3()
+					.distance_squared(self.vertex_lookup[&b].as_dvec3());
+				(length, a, b)
+			})
+			.collect();
+		minimum_spanning_edges(edges)
+			.into_iter()
+			.map(|(a, b)| EdgeNode3d::new(a, b))
+			.collect()
+	}
+}
+/// Find the indices of 4 points which form a tetrahedron, `None` means every
+/// point sits upon a single plane, this includes the cases of all points
+/// being collinear or on top of each other
+fn find_first_tetrahedron(points: &[Vec3]) -> Option<[usize; 4]> {
+	let origin = points[0];
+	// find a second point distinct from the first
+	let second = points.iter().position(|p| *p != origin)?;
+	// find a third point off the line to define the plane
+	let direction = points[second].as_dvec3() - origin.as_dvec3();
+	let third = points
+		.iter()
+		.position(|p| direction.cross(p.as_dvec3() - origin.as_dvec3()) != DVec3::ZERO)?;
+	// any point off the plane means a tetrahedron can be formed
+	let fourth = points
+		.iter()
+		.position(|p| orient3d(origin, points[second], points[third], *p) != 0.0)?;
+	Some([0, second, third, fourth])
 }
+
 /// Find the minimum `x-y-z` and maximum `x-y-z` of space containing all points
 pub fn compute_dimension_bounds(points: &[Vec3]) -> (Vec3, Vec3) {
 	let mut minimum_world_dimensions = Vec3::ZERO;
@@ -281,77 +212,94 @@ pub fn compute_dimension_bounds(points: &[Vec3]) -> (Vec3, Vec3) {
 	)
 }
 
-/// Compute the vertices of 4 tetrahedra aligned in a diamond formation to ensure that all data points sit within the tetrahedra and that all possible circumspheres between data points sit within the tetrahedra too
-fn compute_super_tetrahedra(
+/// Run Bowyer-Watson over the points in `insertion_order` starting from the
+/// `first` tetrahedron, the ghost tetrahedra around the hull are still
+/// included in the result.
+///
+/// Every tetrahedron is kept positively oriented, see [orient3d], a ghost
+/// tetrahedron is oriented as if its vertex at infinity were a position far
+/// beyond its face
+fn tetrahedralize(
 	points: &[Vec3],
-	minimum_world_dimensions: &Vec3,
-	maximum_world_dimensions: &Vec3,
-) -> [Vec3; 6] {
-	let mut largest_radius_sq = 0.0;
-
-	//TODO if out duplicate points to avoid calling circumcpshere code, expensive
-	for vertex_a in points.iter() {
-		for vertex_b in points.iter() {
-			for vertex_c in points.iter() {
-				for vertex_d in points.iter() {
-					if let Some(sphere) =
-						Circumsphere::new(*vertex_a, *vertex_b, *vertex_c, *vertex_d)
-						&& sphere.get_radius_squared() > largest_radius_sq
-					{
-						largest_radius_sq = sphere.get_radius_squared();
-					}
-				}
-			}
+	first: [usize; 4],
+	insertion_order: &[usize],
+) -> (BTreeSet<TetrahedronNode>, BTreeMap<usize, Vec3>) {
+	// store vertices with a unique id
+	let mut vertex_lookup: BTreeMap<usize, Vec3> =
+		first.iter().map(|id| (*id, points[*id])).collect();
+	let [a, b, c, d] = first;
+	let first = if orient3d(points[a], points[b], points[c], points[d]) > 0.0 {
+		TetrahedronNode::new(a, b, c, d)
+	} else {
+		TetrahedronNode::new(b, a, c, d)
+	};
+	let mut tetrahedra = BTreeSet::from([first]);
+	// the vertex at infinity replaces the vertex across from each face but
+	// sits on the other side of it, swapping two vertices keeps the ghost
+	// tetrahedron positively oriented
+	for i in 0..4 {
+		let mut ghost = first;
+		let ids = ghost.get_vertex_ids_mut();
+		ids[i] = GHOST_VERTEX_ID;
+		ids.swap((i + 1) % 4, (i + 2) % 4);
+		tetrahedra.insert(ghost);
+	}
+	// add each point at a time to the tetrahedralization
+	for index in insertion_order {
+		if !vertex_lookup.contains_key(index) {
+			insert_point(*index, points[*index], &mut tetrahedra, &mut vertex_lookup);
 		}
 	}
-	let largest_radius = largest_radius_sq.sqrt();
-	let diff = Vec3::new(largest_radius, largest_radius, largest_radius);
-	let new_min = minimum_world_dimensions - diff;
-	let new_max = maximum_world_dimensions + diff;
-	compute_super_tetra_vertices(&new_min, &new_max)
+	(tetrahedra, vertex_lookup)
 }
 
-/// Given minimum and maximum bounds of space find the shared vertices of 4 super tetrahedra
-pub fn compute_super_tetra_vertices(min_dimensions: &Vec3, max_dimensions: &Vec3) -> [Vec3; 6] {
-	let delta_x = max_dimensions.x - min_dimensions.x;
-	let delta_y = max_dimensions.y - min_dimensions.y;
-	let delta_z = max_dimensions.z - min_dimensions.z;
-	let midpoint = (max_dimensions + min_dimensions) / 2.0;
-
-	// let safety_factor = 1.0;
-
-	// let mid_up = midpoint + Vec3::new(0.0, delta_y * safety_factor, 0.0);
-	// let mid_down = midpoint + Vec3::new(0.0, -delta_y * safety_factor, 0.0);
-	// // in the -x, z plane
-	// let top_left = midpoint + Vec3::new(-delta_x * safety_factor, 0.0, delta_z * safety_factor);
-	// // in the x, z plane
-	// let top_right = midpoint + Vec3::new(delta_x * safety_factor, 0.0, delta_z * safety_factor);
-	// // in the x, -z plane
-	// let bottom_right = midpoint + Vec3::new(delta_x * safety_factor, 0.0, -delta_z * safety_factor);
-	// // in the -x, -z plane
-	// let bottom_left = midpoint + Vec3::new(-delta_x * safety_factor, 0.0, -delta_z * safety_factor);
-
-	// [
-	// 	[top_left, bottom_right, top_right, mid_up],
-	// 	[top_left, bottom_right, bottom_left, mid_up],
-	// 	[top_left, bottom_right, top_right, mid_down],
-	// 	[top_left, bottom_right, bottom_left, mid_down],
-	// ];
-	// [
-	// 	mid_up,
-	// 	bottom_right,
-	// 	top_right,
-	// 	top_left,
-	// 	bottom_left,
-	// 	mid_down,
-	// ];
-	let up = midpoint + Vec3::new(0.0, delta_y, 0.0);
-	let down = midpoint + Vec3::new(0.0, -delta_y, 0.0);
-	let top = midpoint + Vec3::new(0.0, 0.0, delta_z);
-	let bottom = midpoint + Vec3::new(0.0, 0.0, -delta_z);
-	let left = midpoint + Vec3::new(-delta_x, 0.0, 0.0);
-	let right = midpoint + Vec3::new(delta_x, 0.0, 0.0);
-	[up, down, top, bottom, left, right]
+/// Add a point to the tetrahedralization with its index as the ID.
+///
+/// The tetrahedra whose circumsphere contains the point leave a cavity which
+/// the point can see every face of, each face is joined to the point by
+/// replacing the vertex across from it in its tetrahedron. A point is always
+/// within the circumsphere of the tetrahedron containing it, or of a ghost
+/// tetrahedron if it is outside of the hull, so insertion cannot fail
+fn insert_point(
+	index: usize,
+	point: Vec3,
+	tetrahedra: &mut BTreeSet<TetrahedronNode>,
+	vertex_lookup: &mut BTreeMap<usize, Vec3>,
+) {
+	// find tetrahedra that are not Delaunay
+	let bad_tetrahedra = find_bad_tetrahedra(&point, tetrahedra, vertex_lookup);
+	// store the point with its index as the ID
+	vertex_lookup.insert(index, point);
+
+	// a face shared by two bad tetrahedra crosses the cavity, the faces only
+	// seen once bound it
+	let face = |tet: &TetrahedronNode, i: usize| {
+		let ids = tet.get_vertex_ids();
+		let mut face = [ids[(i + 1) % 4], ids[(i + 2) % 4], ids[(i + 3) % 4]];
+		face.sort();
+		face
+	};
+	let mut face_counts: BTreeMap<[usize; 3], usize> = BTreeMap::new();
+	for tet in bad_tetrahedra.iter() {
+		for i in 0..4 {
+			*face_counts.entry(face(tet, i)).or_default() += 1;
+		}
+	}
+	// the point sits on the same side of a cavity face as the vertex it
+	// replaces so the new tetrahedron keeps the positive orientation
+	let mut new_tetrahedra = vec![];
+	for tet in bad_tetrahedra.iter() {
+		for i in 0..4 {
+			if face_counts[&face(tet, i)] == 1 {
+				let mut new_tetra = *tet;
+				new_tetra.get_vertex_ids_mut()[i] = index;
+				new_tetrahedra.push(new_tetra);
+			}
+		}
+	}
+	// remove any bad tetrahedrons from the set
+	tetrahedra.retain(|t| !bad_tetrahedra.contains(t));
+	tetrahedra.extend(new_tetrahedra);
 }
 
 /// Search through tetrahedra and identify any that do not qualify as Delaunay with respect to `point`
@@ -363,9 +311,7 @@ fn find_bad_tetrahedra(
 	let mut set = BTreeSet::new();
 	// check if the point lies within the circumsphere of a tetrahedron
 	for tet in tetrahedra.iter() {
-		if let Some(circumsphere) = tet.compute_circumsphere(vertex_lookup)
-			&& circumsphere.is_point_within_sphere(point)
-		{
+		if is_within_circumsphere(point, tet, vertex_lookup) {
 			// if a point is within then it is not a delaunay tetrahedron,
 			// record this tetrahedron for removal
 			set.insert(*tet);
@@ -374,6 +320,50 @@ fn find_bad_tetrahedra(
 	set
 }
 
+/// Is the `point` strictly within the circumsphere of a tetrahedron. The
+/// circumsphere of a ghost tetrahedron is the half-space beyond its face
+/// along with the inside of the circumcircle of the face upon its plane
+fn is_within_circumsphere(
+	point: &Vec3,
+	tet: &TetrahedronNode,
+	vertex_lookup: &BTreeMap<usize, Vec3>,
+) -> bool {
+	let ids = tet.get_vertex_ids();
+	if !ids.contains(&GHOST_VERTEX_ID) {
+		return tet
+			.compute_circumsphere(vertex_lookup)
+			.is_some_and(|sphere| sphere.is_point_within_sphere(point));
+	}
+	// with the point in place of the vertex at infinity a positive
+	// orientation means the point is beyond the face
+	let [a, b, c, d] = ids.map(|id| {
+		if id == GHOST_VERTEX_ID {
+			*point
+		} else {
+			vertex_lookup[&id]
+		}
+	});
+	let orientation = orient3d(a, b, c, d);
+	if orientation != 0.0 {
+		return orientation > 0.0;
+	}
+	// any sphere through the face cuts its plane along the circumcircle of
+	// the face, so one through a position off the plane decides it
+	let face: Vec<Vec3> = ids
+		.iter()
+		.filter(|id| **id != GHOST_VERTEX_ID)
+		.map(|id| vertex_lookup[id])
+		.collect();
+	let scale = face
+		.iter()
+		.map(|v| v.abs().max_element())
+		.fold(1.0, f32::max);
+	[Vec3::X, Vec3::Y, Vec3::Z]
+		.iter()
+		.find_map(|axis| Circumsphere::new(face[0], face[1], face[2], face[0] + axis * scale))
+		.is_some_and(|sphere| sphere.is_point_within_sphere(point))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -397,10 +387,30 @@ mod tests {
 			Vec3::new(32.0, -3.0, -12.0),
 		];
 		let result = Delaunay3d::compute_triangulation_3d(&points);
-		assert!(result.is_none());
+		assert_eq!(
+			Some(MosaicError::InsufficientPoints {
+				required: 4,
+				supplied: 3
+			}),
+			result.err()
+		);
+	}
+	#[test]
+	fn delaunay_coplanar() {
+		let points = vec![
+			Vec3::new(-50.0, 0.0, -50.0),
+			Vec3::new(50.0, 0.0, -50.0),
+			Vec3::new(50.0, 0.0, 50.0),
+			Vec3::new(-50.0, 0.0, 50.0),
+			Vec3::new(0.0, 0.0, 0.0),
+		];
+		let result = Delaunay3d::compute_triangulation_3d(&points);
+		assert_eq!(Some(MosaicError::CoplanarPoints), result.err());
 	}
 	#[test]
-	fn super_tetra() {
+	fn delaunay() {
+		// ensure that the hull points are perfectly triangulated.
+		// cube with a point in the middle
 		let points = vec![
 			Vec3::new(-50.0, -50.0, -50.0),
 			Vec3::new(50.0, -50.0, -50.0),
@@ -414,27 +424,83 @@ mod tests {
 			//
 			Vec3::new(0.0, 0.0, 0.0),
 		];
-		let (min, max) = compute_dimension_bounds(&points);
-		let super_tetrahedra = compute_super_tetrahedra(&points, &min, &max);
-
-		let t1 = Vec3::new(0.0, 361.80762, 0.0);
-		assert_eq!(t1, super_tetrahedra[0]);
-		let t2 = Vec3::new(0.0, -361.80762, 0.0);
-		assert_eq!(t2, super_tetrahedra[1]);
-		let t3 = Vec3::new(0.0, 0.0, 361.80762);
-		assert_eq!(t3, super_tetrahedra[2]);
-		let t4 = Vec3::new(0.0, 0.0, -361.80762);
-		assert_eq!(t4, super_tetrahedra[3]);
-		let t5 = Vec3::new(-361.80762, 0.0, 0.0);
-		assert_eq!(t5, super_tetrahedra[4]);
-		let t6 = Vec3::new(361.80762, 0.0, 0.0);
-		assert_eq!(t6, super_tetrahedra[5]);
+		let delaunay = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		assert_eq!(12, delaunay.get_tetrahedra().len());
 	}
 	#[test]
-	fn delaunay() {
-		// ensure that the super tetra is sized correctly so that the points
-		// are perfectly triangulated.
-		// cube with a point in the middle
+	fn minimum_spanning_tree() {
+		use rand::{Rng, SeedableRng};
+		use rand_chacha::ChaCha20Rng;
+		let mut rng = ChaCha20Rng::seed_from_u64(123456789);
+		let points: Vec<Vec3> = (0..40)
+			.map(|_| {
+				Vec3::new(
+					rng.random_range(0.0..100.0),
+					rng.random_range(0.0..100.0),
+					rng.random_range(0.0..100.0),
+				)
+			})
+			.collect();
+		let delaunay = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		let tree = delaunay.minimum_spanning_tree();
+		assert_eq!(points.len() - 1, tree.len());
+		// the same length as Prim's algorithm over every pair of points
+		let distance = |a: usize, b: usize| points[a].as_dvec3().distance(points[b].as_dvec3());
+		let mut joined = vec![0];
+		let mut prim_length = 0.0;
+		while joined.len() < points.len() {
+			let (length, next) = (0..points.len())
+				.filter(|b| !joined.contains(b))
+				.map(|b| {
+					let nearest = joined
+						.iter()
+						.map(|a| distance(*a, b))
+						.fold(f64::MAX, f64::min);
+					(nearest, b)
+				})
+				.min_by(|x, y| x.0.total_cmp(&y.0))
+				.unwrap();
+			prim_length += length;
+			joined.push(next);
+		}
+		let tree_length: f64 = tree
+			.iter()
+			.map(|edge| distance(edge.get_vertex_a_id(), edge.get_vertex_b_id()))
+			.sum();
+		assert!((prim_length - tree_length).abs() < 1e-6);
+	}
+	#[test]
+	fn cospherical_grid() {
+		// each cube of a grid has cospherical corners, every point must
+		// still be a vertex and the cubes must be completely filled
+		let mut points = vec![];
+		for x in 0..3 {
+			for y in 0..3 {
+				for z in 0..3 {
+					points.push(Vec3::new(x as f32, y as f32, z as f32) * 10.0);
+				}
+			}
+		}
+		let delaunay = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		let used: BTreeSet<usize> = delaunay
+			.get_tetrahedra()
+			.values()
+			.flat_map(|t| *t.get_vertex_ids())
+			.collect();
+		assert_eq!(27, used.len());
+		// orient3d is 6x the volume of a tetrahedron
+		let six_volume: f64 = delaunay
+			.get_tetrahedra()
+			.values()
+			.map(|t| {
+				let [a, b, c, d] = t.get_vertex_ids().map(|id| points[id]);
+				orient3d(a, b, c, d).abs()
+			})
+			.sum();
+		assert_eq!(6.0 * 8000.0, six_volume);
+	}
+	#[test]
+	fn duplicates_merged() {
 		let points = vec![
 			Vec3::new(-50.0, -50.0, -50.0),
 			Vec3::new(50.0, -50.0, -50.0),
@@ -447,8 +513,77 @@ mod tests {
 			Vec3::new(-50.0, 50.0, 50.0),
 			//
 			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(50.0, -50.0, -50.0),
+			Vec3::new(0.1, 0.0, 0.0),
 		];
-		let delaunay = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		let delaunay =
+			Delaunay3d::compute_triangulation_3d_with_merge_tolerance(&points, 0.5).unwrap();
 		assert_eq!(12, delaunay.get_tetrahedra().len());
+		assert_eq!(9, delaunay.get_vertex_lookup().len());
+		assert_eq!(
+			&BTreeMap::from([(9, 1), (10, 8)]),
+			delaunay.get_merged_points()
+		);
+		assert_eq!(Some(8), delaunay.get_vertex_id(10));
+	}
+	#[test]
+	fn insert_point_on_hull_face() {
+		let points = [
+			Vec3::ZERO,
+			Vec3::X,
+			Vec3::Y,
+			Vec3::Z,
+			Vec3::new(0.25, 0.25, 0.0),
+		];
+		let (tetrahedra, _) = tetrahedralize(&points, [0, 1, 2, 3], &[0, 1, 2, 3, 4]);
+		let (ghosts, real): (Vec<TetrahedronNode>, Vec<TetrahedronNode>) = tetrahedra
+			.iter()
+			.partition(|tet| tet.get_vertex_ids().contains(&GHOST_VERTEX_ID));
+		// the point splits the face it sits on into 3 faces of the hull
+		assert_eq!(3, real.len());
+		assert_eq!(6, ghosts.len());
+		for tet in real {
+			let [a, b, c, d] = tet.get_vertex_ids().map(|id| points[id]);
+			assert!(orient3d(a, b, c, d) > 0.0);
+		}
+	}
+	#[test]
+	fn wide_flat_data() {
+		// a thin slab far from the origin which a super tetrahedron would
+		// struggle to enclose
+		use rand::{Rng, SeedableRng};
+		use rand_chacha::ChaCha20Rng;
+		let mut rng = ChaCha20Rng::seed_from_u64(123456789);
+		let points: Vec<Vec3> = (0..150)
+			.map(|_| {
+				Vec3::new(
+					rng.random_range(0.0..1e6),
+					rng.random_range(0.0..1e6),
+					rng.random_range(0.0..1.0),
+				) + Vec3::splat(1e5)
+			})
+			.collect();
+		let delaunay = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		let used: BTreeSet<usize> = delaunay
+			.get_tetrahedra()
+			.values()
+			.flat_map(|t| *t.get_vertex_ids())
+			.collect();
+		assert_eq!(points.len(), used.len());
+		// every tetrahedron is Delaunay and no face is shared by more than 2
+		let mut face_counts: BTreeMap<[usize; 3], usize> = BTreeMap::new();
+		for tet in delaunay.get_tetrahedra().values() {
+			let sphere = tet.compute_circumsphere(&points_lookup(&points)).unwrap();
+			assert!(!points.iter().any(|p| sphere.is_point_within_sphere(p)));
+			for face in tet.get_triangle_node_3d_faces() {
+				let mut ids = *face.get_vertex_ids();
+				ids.sort();
+				*face_counts.entry(ids).or_default() += 1;
+			}
+		}
+		assert!(face_counts.values().all(|count| *count <= 2));
+	}
+	fn points_lookup(points: &[Vec3]) -> BTreeMap<usize, Vec3> {
+		points.iter().copied().enumerate().collect()
 	}
 }
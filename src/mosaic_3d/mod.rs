@@ -4,6 +4,8 @@
 
 use bevy::math::Vec3;
 
+use crate::error::{MosaicError, MosaicStage};
+
 pub mod circumsphere;
 pub mod delaunay;
 pub mod edge_node3d;
@@ -14,39 +16,38 @@ pub mod voronoi;
 /// Defines the Delaunay-Voronoi dual
 pub struct Mosaic3d {
 	/// The generated Delaunay Tetrahedralization
-	delaunay: Option<delaunay::Delaunay3d>,
+	delaunay: delaunay::Delaunay3d,
 	/// The generated Voronoi Tessellation
-	voronoi: Option<voronoi::Voronoi3d>,
+	voronoi: voronoi::Voronoi3d,
 }
 
 impl Mosaic3d {
 	/// Generate the Delaunay and Voronoi for a series of 3d points
-	pub fn new(data_points: &[Vec3]) -> Self {
-		if let Some(delaunay) = delaunay::Delaunay3d::compute_triangulation_3d(data_points) {
-			if let Some(voronoi) = voronoi::Voronoi3d::from_delaunay_3d(&delaunay) {
-				Mosaic3d {
-					delaunay: Some(delaunay),
-					voronoi: Some(voronoi),
-				}
-			} else {
-				Mosaic3d {
-					delaunay: Some(delaunay),
-					voronoi: None,
-				}
-			}
-		} else {
-			Mosaic3d {
-				delaunay: None,
-				voronoi: None,
-			}
-		}
+	///
+	/// If either fails to be computed the error is wrapped in
+	/// [MosaicError::Stage] to identify which one failed
+	pub fn new(data_points: &[Vec3]) -> Result<Self, MosaicError> {
+		let delaunay = delaunay::Delaunay3d::compute_triangulation_3d(data_points)
+			.map_err(|e| e.at_stage(MosaicStage::Delaunay))?;
+		let voronoi = voronoi::Voronoi3d::from_delaunay_3d(&delaunay)
+			.map_err(|e| e.at_stage(MosaicStage::Voronoi))?;
+		Ok(Mosaic3d { delaunay, voronoi })
+	}
+	/// Get the computed Delaunay Tetrahedralization
+	pub fn get_delaunay(&self) -> &delaunay::Delaunay3d {
+		&self.delaunay
 	}
-	/// Get the computed Delaunay Tetrahedralization, if it exists
-	pub fn get_delaunay(&self) -> Option<&delaunay::Delaunay3d> {
-		self.delaunay.as_ref()
+	/// Get the computed Voronoi Tesselation
+	pub fn get_voronoi(&self) -> &voronoi::Voronoi3d {
+		&self.voronoi
 	}
-	/// Get the computed Voronoi Tesselation, if it exists
-	pub fn get_voronoi(&self) -> Option<&voronoi::Voronoi3d> {
-		self.voronoi.as_ref()
+	/// Find the Voronoi Cell generated by the supplied point at `point_index`.
+	///
+	/// Points merged as duplicates resolve to the cell of the point they were
+	/// merged into. `None` is returned if the point has no cell, e.g it sits
+	/// on the outer edge of the data set
+	pub fn get_cell_for_point(&self, point_index: usize) -> Option<&voronoi::VoronoiCell3d> {
+		let vertex_id = self.delaunay.get_vertex_id(point_index)?;
+		self.voronoi.get_cells().get(&vertex_id)
 	}
 }
@@ -3,10 +3,13 @@
 
 use std::collections::BTreeMap;
 
-use bevy::math::{Mat3, Vec3};
+use bevy::math::Vec3;
 
-use crate::mosaic_3d::{
-	circumsphere::Circumsphere, edge_node3d::EdgeNode3d, triangle_node3d::TriangleNode3d,
+use crate::{
+	mosaic_3d::{
+		circumsphere::Circumsphere, edge_node3d::EdgeNode3d, triangle_node3d::TriangleNode3d,
+	},
+	utilities::orient3d,
 };
 
 /// Describes a tetrahedron where the vertices are represented by vertex IDs
@@ -101,18 +104,15 @@ impl TetrahedronNode {
 			),
 		]
 	}
-	/// Compute the volume of the tetrahedron (actually 6x volume but doesn't
-	/// matter), if zero then it means its vertices are coplanar and this
-	/// tetrahedron is degenerate
+	/// Check whether the vertices are coplanar which makes this tetrahedron
+	/// degenerate, uses the exact [orient3d] predicate
 	pub fn is_degenerate(&self, vertex_lookup: &BTreeMap<usize, Vec3>) -> bool {
 		let a = vertex_lookup.get(&self.get_vertex_a_id()).unwrap();
 		let b = vertex_lookup.get(&self.get_vertex_b_id()).unwrap();
 		let c = vertex_lookup.get(&self.get_vertex_c_id()).unwrap();
 		let d = vertex_lookup.get(&self.get_vertex_d_id()).unwrap();
 
-		let mat = Mat3::from_cols(a - d, b - d, c - d);
-		//TODO should use a tolerance to consider very small volumes as being degenerate?
-		mat.determinant() == 0.0
+		orient3d(*a, *b, *c, *d) == 0.0
 	}
 }
 
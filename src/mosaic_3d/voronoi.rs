@@ -8,8 +8,9 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use bevy::prelude::*;
 
-use crate::mosaic_3d::{
-	delaunay::Delaunay3d, edge_node3d::EdgeNode3d, tetrahedron_node::TetrahedronNode,
+use crate::{
+	error::MosaicError,
+	mosaic_3d::{delaunay::Delaunay3d, edge_node3d::EdgeNode3d, tetrahedron_node::TetrahedronNode},
 };
 
 /// The vertices of a Voronoi Cell in 3-dimensions
@@ -51,14 +52,16 @@ impl VoronoiCell3d {
 
 /// Describes Voronoi Cells
 pub struct Voronoi3d {
-	/// Uniquely ID'ed cells
+	/// Cells ID'ed by the Delaunay vertex ID of their generating point
 	cells: BTreeMap<usize, VoronoiCell3d>,
 	/// Each vertex of a cell is an ID corresponding to a point in space
 	vertex_lookup: BTreeMap<usize, Vec3>,
 }
 
 impl Voronoi3d {
-	/// Get a reference to the list of usize Cells
+	/// Get a reference to the map of Voronoi Cells. The ID of a cell is the
+	/// Delaunay vertex ID of its generating point, which is in turn the index
+	/// of the supplied point
 	pub fn get_cells(&self) -> &BTreeMap<usize, VoronoiCell3d> {
 		&self.cells
 	}
@@ -75,7 +78,9 @@ impl Voronoi3d {
 		&mut self.vertex_lookup
 	}
 	/// From a Delaunay Tetrahedralization compute its dual - the Voronoi Cells
-	pub fn from_delaunay_3d(delaunay: &Delaunay3d) -> Option<Self> {
+	///
+	/// Fails with [MosaicError::EmptyTessellation] if no cells can be formed
+	pub fn from_delaunay_3d(delaunay: &Delaunay3d) -> Result<Self, MosaicError> {
 		// each circumcentre of a Delaunay tetrahedron is a vertex of a Voronoi cell
 		let tetras_store = delaunay.get_tetrahedra();
 		let delaunay_vertex_lookup = delaunay.get_vertex_lookup();
@@ -98,26 +103,45 @@ impl Voronoi3d {
 			&tetrahedron_to_circumcentre_ids,
 		);
 
-		Some(Voronoi3d {
+		if cells.is_empty() {
+			return Err(MosaicError::EmptyTessellation);
+		}
+		Ok(Voronoi3d {
 			cells,
 			vertex_lookup: voronoi_vertex_lookup,
 		})
 	}
 	/// Convert each Voronoi Cell into a Bevy Mesh
-	pub fn as_bevy3d_meshes(&self) -> BTreeMap<usize, (Mesh, Vec3)> {
+	///
+	/// Meshing in 3d is not yet implemented, an empty map is returned
+	pub fn as_bevy3d_meshes(&self) -> Result<BTreeMap<usize, (Mesh, Vec3)>, MosaicError> {
 		warn!("Unimplemented, this currently does nothing");
-		BTreeMap::new()
+		Ok(BTreeMap::new())
 	}
 	/// Clip all the [VoronoiCell3d] so they cannot extend or exist outside of
 	/// a boundary polyhedron
 	///
-	/// The boundary polyhedron must contain at least 4 vertices and the vertices
-	/// should be expressed in an anti-clockwise order around their centre
+	/// The boundary polyhedron must contain at least 4 vertices and each must
+	/// be a real position, otherwise [MosaicError::InvalidBoundary] is
+	/// returned. Meshing in 3d is not yet implemented, an empty map is returned
 	///
 	/// *NB: Delaunay and Voronoi are duals - they can precisely be converted from one fomrat to the other back and forth. By applying clipping to the Voronoi, cell vertices may be added/removed which will destroy the duality - i.e if you apply clipping you cannot convert Voronoi into Delaunay and expect to get your oringal dataset back*
-	pub fn as_clipped_bevy3d_meshes(&self, _boundary: &[Vec3]) -> BTreeMap<usize, (Mesh, Vec3)> {
+	pub fn as_clipped_bevy3d_meshes(
+		&self,
+		boundary: &[Vec3],
+	) -> Result<BTreeMap<usize, (Mesh, Vec3)>, MosaicError> {
+		if boundary.len() < 4 {
+			return Err(MosaicError::InvalidBoundary(
+				"a boundary requires at least 4 vertices",
+			));
+		}
+		if boundary.iter().any(|v| !v.is_finite()) {
+			return Err(MosaicError::InvalidBoundary(
+				"boundary vertices must be finite",
+			));
+		}
 		warn!("Unimplemented, this currently does nothing");
-		BTreeMap::new()
+		Ok(BTreeMap::new())
 	}
 }
 
@@ -244,8 +268,8 @@ fn compute_cells_from_tetrahedra_sets(
 			edges,
 			generating_point: **generating_point_id,
 		};
-		let key = cells.len();
-		cells.insert(key, cell);
+		// a cell shares the ID of its generating Delaunay vertex
+		cells.insert(**generating_point_id, cell);
 	}
 	cells
 }
@@ -2,12 +2,17 @@
 //!
 
 #[doc(hidden)]
-pub use crate::{utilities::*, *};
+pub use crate::{error::*, utilities::*, *};
+
+#[doc(hidden)]
+#[cfg(any(feature = "2d", feature = "3d"))]
+pub use crate::quality::*;
 
 #[doc(hidden)]
 #[cfg(feature = "2d")]
 pub use crate::mosaic_2d::{
-	Mosaic2d, circumcircle::*, delaunay::*, edge_node2d::*, triangle_node2d::*, voronoi::*,
+	Mosaic2d, alpha_shape::*, circumcircle::*, delaunay::*, edge_node2d::*, interpolation::*,
+	triangle_node2d::*, voronoi::*,
 };
 
 #[doc(hidden)]
@@ -0,0 +1,546 @@
+//! Measure the shape of the triangles, tetrahedra or cells of a mosaic to
+//! find slivers and degenerate elements before they are meshed.
+//!
+//! Each element is measured by:
+//! - minimum angle, the smallest interior angle in degrees. For a
+//!   tetrahedron this is the smallest dihedral angle between two faces
+//! - aspect ratio, how stretched the element is. For a triangle or cell this
+//!   is its diameter over its width and for a tetrahedron its longest edge
+//!   over its shortest altitude, scaled so that an equilateral triangle,
+//!   regular hexagon or regular tetrahedron is `1.0`
+//! - radius-edge ratio, the radius of the circumcircle or circumsphere over
+//!   the shortest edge. A cell uses the distance from its generating point to
+//!   its furthest vertex as the radius
+//! - size, the area or volume
+//!
+//! Each measure is summarised by a [QualityHistogram] over every element.
+//! Points closer together than a chosen distance are reported as near
+//! duplicates, they are what usually produce slivers.
+//!
+
+use std::collections::BTreeMap;
+
+use bevy::math::DVec3;
+
+#[cfg(feature = "2d")]
+use crate::mosaic_2d::{delaunay::Delaunay2d, voronoi::Voronoi2d};
+#[cfg(feature = "2d")]
+use bevy::math::DVec2;
+
+#[cfg(feature = "3d")]
+use crate::mosaic_3d::delaunay::Delaunay3d;
+
+/// Number of equal width bins in each [QualityHistogram]
+pub const QUALITY_HISTOGRAM_BINS: usize = 10;
+
+/// Measures of the shape of a single triangle, tetrahedron or cell, see the
+/// module docs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementQuality {
+	/// Smallest interior or dihedral angle in degrees
+	minimum_angle: f32,
+	/// How stretched the element is, `1.0` for a regular element
+	aspect_ratio: f32,
+	/// Circumradius over the shortest edge
+	radius_edge_ratio: f32,
+	/// Area or volume
+	size: f32,
+}
+
+impl ElementQuality {
+	/// Get the smallest interior angle, or dihedral angle of a tetrahedron,
+	/// in degrees
+	pub fn get_minimum_angle(&self) -> f32 {
+		self.minimum_angle
+	}
+	/// Get the aspect ratio, `1.0` for an equilateral triangle, regular
+	/// hexagon or regular tetrahedron and growing as the element is stretched
+	pub fn get_aspect_ratio(&self) -> f32 {
+		self.aspect_ratio
+	}
+	/// Get the circumradius over the shortest edge
+	pub fn get_radius_edge_ratio(&self) -> f32 {
+		self.radius_edge_ratio
+	}
+	/// Get the area, or volume of a tetrahedron
+	pub fn get_size(&self) -> f32 {
+		self.size
+	}
+}
+
+/// Summary of one measure over every element. The range between the
+/// smallest and largest finite values is split into
+/// [QUALITY_HISTOGRAM_BINS] bins of equal width
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityHistogram {
+	/// Smallest finite value
+	minimum: f32,
+	/// Largest finite value
+	maximum: f32,
+	/// Mean of the finite values
+	mean: f32,
+	/// Number of finite values within each bin
+	bin_counts: Vec<usize>,
+	/// Number of values that are infinite or NaN, from degenerate elements
+	non_finite_count: usize,
+}
+
+impl QualityHistogram {
+	/// Summarise a series of values
+	fn new(values: impl Iterator<Item = f32>) -> Self {
+		let mut non_finite_count = 0;
+		let finite: Vec<f32> = values
+			.filter(|value| {
+				if !value.is_finite() {
+					non_finite_count += 1;
+				}
+				value.is_finite()
+			})
+			.collect();
+		let minimum = finite.iter().copied().fold(f32::INFINITY, f32::min);
+		let maximum = finite.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+		let mut bin_counts = vec![0; QUALITY_HISTOGRAM_BINS];
+		let width = (maximum - minimum) / QUALITY_HISTOGRAM_BINS as f32;
+		for value in finite.iter() {
+			let bin = if width > 0.0 {
+				(((value - minimum) / width) as usize).min(QUALITY_HISTOGRAM_BINS - 1)
+			} else {
+				0
+			};
+			bin_counts[bin] += 1;
+		}
+		let mean = if finite.is_empty() {
+			f32::NAN
+		} else {
+			(finite.iter().map(|value| *value as f64).sum::<f64>() / finite.len() as f64) as f32
+		};
+		QualityHistogram {
+			minimum,
+			maximum,
+			mean,
+			bin_counts,
+			non_finite_count,
+		}
+	}
+	/// Get the smallest finite value, infinite if there are none
+	pub fn get_minimum(&self) -> f32 {
+		self.minimum
+	}
+	/// Get the largest finite value, negative infinity if there are none
+	pub fn get_maximum(&self) -> f32 {
+		self.maximum
+	}
+	/// Get the mean of the finite values, NaN if there are none
+	pub fn get_mean(&self) -> f32 {
+		self.mean
+	}
+	/// Get the number of finite values within each bin
+	pub fn get_bin_counts(&self) -> &[usize] {
+		&self.bin_counts
+	}
+	/// Get the lower and upper value of a bin, the last bin includes its
+	/// upper value
+	pub fn get_bin_range(&self, bin: usize) -> (f32, f32) {
+		let width = (self.maximum - self.minimum) / QUALITY_HISTOGRAM_BINS as f32;
+		(
+			self.minimum + width * bin as f32,
+			self.minimum + width * (bin + 1) as f32,
+		)
+	}
+	/// Get the number of values that are infinite or NaN, these come from
+	/// degenerate elements with no area or a zero length edge
+	pub fn get_non_finite_count(&self) -> usize {
+		self.non_finite_count
+	}
+}
+
+/// The quality of every element of a triangulation or tessellation, see the
+/// module docs
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityReport {
+	/// Measures of each triangle, tetrahedron or cell by its ID
+	elements: BTreeMap<usize, ElementQuality>,
+	/// Summary of the minimum angles
+	minimum_angle: QualityHistogram,
+	/// Summary of the aspect ratios
+	aspect_ratio: QualityHistogram,
+	/// Summary of the radius-edge ratios
+	radius_edge_ratio: QualityHistogram,
+	/// Summary of the areas or volumes
+	size: QualityHistogram,
+	/// Pairs of supplied point indices that are near duplicates, the smaller
+	/// index first
+	near_duplicates: Vec<(usize, usize)>,
+}
+
+impl QualityReport {
+	/// Get the measures of each triangle, tetrahedron or cell by its ID
+	pub fn get_elements(&self) -> &BTreeMap<usize, ElementQuality> {
+		&self.elements
+	}
+	/// Get the summary of the minimum angles
+	pub fn get_minimum_angle_histogram(&self) -> &QualityHistogram {
+		&self.minimum_angle
+	}
+	/// Get the summary of the aspect ratios
+	pub fn get_aspect_ratio_histogram(&self) -> &QualityHistogram {
+		&self.aspect_ratio
+	}
+	/// Get the summary of the radius-edge ratios
+	pub fn get_radius_edge_ratio_histogram(&self) -> &QualityHistogram {
+		&self.radius_edge_ratio
+	}
+	/// Get the summary of the areas or volumes
+	pub fn get_size_histogram(&self) -> &QualityHistogram {
+		&self.size
+	}
+	/// Get the pairs of supplied point indices that are near duplicates,
+	/// including points that were merged into an earlier point
+	pub fn get_near_duplicates(&self) -> &[(usize, usize)] {
+		&self.near_duplicates
+	}
+	/// Whether any supplied points are near duplicates of each other
+	pub fn has_near_duplicates(&self) -> bool {
+		!self.near_duplicates.is_empty()
+	}
+	/// IDs of the elements whose minimum angle is below `angle` in degrees
+	pub fn elements_below_minimum_angle(&self, angle: f32) -> Vec<usize> {
+		self.elements
+			.iter()
+			.filter(|(_, element)| element.minimum_angle.is_nan() || element.minimum_angle < angle)
+			.map(|(id, _)| *id)
+			.collect()
+	}
+	/// Measure every triangle of a triangulation. Supplied points within
+	/// `duplicate_distance` of each other are reported as near duplicates.
+	///
+	/// A collinear chain has no triangles so only near duplicates are found
+	#[cfg(feature = "2d")]
+	pub fn from_delaunay_2d(delaunay: &Delaunay2d, duplicate_distance: f32) -> Self {
+		let elements = delaunay
+			.get_triangles()
+			.iter()
+			.map(|(tri_id, tri)| {
+				let positions: Vec<DVec2> = tri
+					.get_vertex_ids()
+					.iter()
+					.map(|id| delaunay.get_vertex_lookup()[id].as_dvec2())
+					.collect();
+				let [a, b, c] = [positions[0], positions[1], positions[2]];
+				let area = (b - a).perp_dot(c - a).abs() / 2.0;
+				let radius = a.distance(b) * b.distance(c) * c.distance(a) / (4.0 * area);
+				(*tri_id, measure_polygon(&positions, radius, area))
+			})
+			.collect();
+		let positions = delaunay
+			.get_vertex_lookup()
+			.iter()
+			.map(|(id, position)| (*id, position.as_dvec2().extend(0.0)))
+			.collect();
+		QualityReport::new(
+			elements,
+			find_near_duplicates(positions, duplicate_distance, delaunay.get_merged_points()),
+		)
+	}
+	/// Measure every tetrahedron of a tetrahedralization. Supplied points
+	/// within `duplicate_distance` of each other are reported as near
+	/// duplicates
+	#[cfg(feature = "3d")]
+	pub fn from_delaunay_3d(delaunay: &Delaunay3d, duplicate_distance: f32) -> Self {
+		let elements = delaunay
+			.get_tetrahedra()
+			.iter()
+			.map(|(tetra_id, tetra)| {
+				let positions = tetra
+					.get_vertex_ids()
+					.map(|id| delaunay.get_vertex_lookup()[&id].as_dvec3());
+				(*tetra_id, measure_tetrahedron(positions))
+			})
+			.collect();
+		let positions = delaunay
+			.get_vertex_lookup()
+			.iter()
+			.map(|(id, position)| (*id, position.as_dvec3()))
+			.collect();
+		QualityReport::new(
+			elements,
+			find_near_duplicates(positions, duplicate_distance, delaunay.get_merged_points()),
+		)
+	}
+	/// Measure every closed cell of a tessellation, `delaunay` is the
+	/// triangulation the tessellation was computed from and supplies the
+	/// generating points.
+	/// Supplied points within `duplicate_distance` of each other are reported
+	/// as near duplicates
+	#[cfg(feature = "2d")]
+	pub fn from_voronoi_2d(
+		voronoi: &Voronoi2d,
+		delaunay: &Delaunay2d,
+		duplicate_distance: f32,
+	) -> Self {
+		let elements = voronoi
+			.get_cells()
+			.iter()
+			.filter(|(_, cell)| cell.is_closed())
+			.filter_map(|(cell_id, cell)| {
+				let site = delaunay
+					.get_vertex_lookup()
+					.get(cell.get_generating_point())?
+					.as_dvec2();
+				let positions: Vec<DVec2> = cell
+					.get_vertex_ids()
+					.iter()
+					.map(|id| voronoi.get_vertex_lookup()[id].as_dvec2())
+					.collect();
+				let radius = positions
+					.iter()
+					.map(|position| position.distance(site))
+					.fold(0.0, f64::max);
+				let twice_area: f64 = positions
+					.iter()
+					.zip(positions.iter().cycle().skip(1))
+					.map(|(a, b)| a.perp_dot(*b))
+					.sum();
+				Some((
+					*cell_id,
+					measure_polygon(&positions, radius, twice_area.abs() / 2.0),
+				))
+			})
+			.collect();
+		let positions = delaunay
+			.get_vertex_lookup()
+			.iter()
+			.map(|(id, position)| (*id, position.as_dvec2().extend(0.0)))
+			.collect();
+		QualityReport::new(
+			elements,
+			find_near_duplicates(positions, duplicate_distance, delaunay.get_merged_points()),
+		)
+	}
+	/// Summarise the measured elements
+	fn new(
+		elements: BTreeMap<usize, ElementQuality>,
+		near_duplicates: Vec<(usize, usize)>,
+	) -> Self {
+		let summarise = |measure: fn(&ElementQuality) -> f32| {
+			QualityHistogram::new(elements.values().map(measure))
+		};
+		QualityReport {
+			minimum_angle: summarise(ElementQuality::get_minimum_angle),
+			aspect_ratio: summarise(ElementQuality::get_aspect_ratio),
+			radius_edge_ratio: summarise(ElementQuality::get_radius_edge_ratio),
+			size: summarise(ElementQuality::get_size),
+			elements,
+			near_duplicates,
+		}
+	}
+}
+
+/// Measure a convex polygon whose vertices are in order around it, the
+/// radius and area are measured by the caller
+#[cfg(feature = "2d")]
+fn measure_polygon(positions: &[DVec2], radius: f64, area: f64) -> ElementQuality {
+	let count = positions.len();
+	let mut minimum_angle = f64::INFINITY;
+	let mut shortest_edge = f64::INFINITY;
+	let mut width = f64::INFINITY;
+	let mut diameter: f64 = 0.0;
+	for i in 0..count {
+		let (previous, current, next) = (
+			positions[(i + count - 1) % count],
+			positions[i],
+			positions[(i + 1) % count],
+		);
+		minimum_angle = minimum_angle.min((previous - current).angle_to(next - current).abs());
+		let edge = next - current;
+		shortest_edge = shortest_edge.min(edge.length());
+		// the width of a convex polygon is found across from one of its edges
+		let across = positions
+			.iter()
+			.map(|position| edge.perp_dot(*position - current).abs())
+			.fold(0.0, f64::max);
+		width = width.min(across / edge.length());
+		for other in positions[i + 1..].iter() {
+			diameter = diameter.max(current.distance(*other));
+		}
+	}
+	ElementQuality {
+		minimum_angle: minimum_angle.to_degrees() as f32,
+		aspect_ratio: (diameter / width * 3.0_f64.sqrt() / 2.0) as f32,
+		radius_edge_ratio: (radius / shortest_edge) as f32,
+		size: area as f32,
+	}
+}
+
+/// Measure a tetrahedron from the positions of its vertices
+#[cfg(feature = "3d")]
+fn measure_tetrahedron(positions: [DVec3; 4]) -> ElementQuality {
+	let [a, b, c, d] = positions;
+	let volume = (b - a).dot((c - a).cross(d - a)).abs() / 6.0;
+	let mut minimum_angle = f64::INFINITY;
+	let mut shortest_edge = f64::INFINITY;
+	let mut longest_edge: f64 = 0.0;
+	for (i, j, k, l) in [
+		(0, 1, 2, 3),
+		(0, 2, 1, 3),
+		(0, 3, 1, 2),
+		(1, 2, 0, 3),
+		(1, 3, 0, 2),
+		(2, 3, 0, 1),
+	] {
+		let edge = positions[j] - positions[i];
+		shortest_edge = shortest_edge.min(edge.length());
+		longest_edge = longest_edge.max(edge.length());
+		// the dihedral angle along an edge is between the parts of the two
+		// opposite vertices perpendicular to it
+		let perpendicular = |position: DVec3| {
+			let offset = position - positions[i];
+			offset - edge * offset.dot(edge) / edge.length_squared()
+		};
+		let angle = perpendicular(positions[k]).angle_between(perpendicular(positions[l]));
+		minimum_angle = minimum_angle.min(angle);
+	}
+	let largest_face = [(a, b, c), (a, b, d), (a, c, d), (b, c, d)]
+		.into_iter()
+		.map(|(p, q, r)| (q - p).cross(r - p).length() / 2.0)
+		.fold(0.0, f64::max);
+	let shortest_altitude = 3.0 * volume / largest_face;
+	// circumcentre relative to the first vertex
+	let (u, v, w) = (b - a, c - a, d - a);
+	let centre = (u.length_squared() * v.cross(w)
+		+ v.length_squared() * w.cross(u)
+		+ w.length_squared() * u.cross(v))
+		/ (2.0 * u.dot(v.cross(w)));
+	ElementQuality {
+		minimum_angle: minimum_angle.to_degrees() as f32,
+		aspect_ratio: (longest_edge / shortest_altitude * (2.0_f64 / 3.0).sqrt()) as f32,
+		radius_edge_ratio: (centre.length() / shortest_edge) as f32,
+		size: volume as f32,
+	}
+}
+
+/// Find the pairs of vertices within `distance` of each other, along with the
+/// points merged into a vertex. Vertices are swept in order of their `x`
+/// coordinate so only those close along `x` are compared
+fn find_near_duplicates(
+	mut positions: Vec<(usize, DVec3)>,
+	distance: f32,
+	merged_points: &BTreeMap<usize, usize>,
+) -> Vec<(usize, usize)> {
+	let distance = distance as f64;
+	positions.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+	let mut pairs: Vec<(usize, usize)> = merged_points
+		.iter()
+		.map(|(point, vertex)| (*point.min(vertex), *point.max(vertex)))
+		.collect();
+	for (index, (id, position)) in positions.iter().enumerate() {
+		for (other_id, other) in positions[index + 1..].iter() {
+			if other.x - position.x > distance {
+				break;
+			}
+			if position.distance(*other) <= distance {
+				pairs.push((*id.min(other_id), *id.max(other_id)));
+			}
+		}
+	}
+	pairs.sort();
+	pairs
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy::prelude::*;
+
+	#[test]
+	#[cfg(feature = "2d")]
+	fn quality_2d() {
+		let height = 3.0_f32.sqrt() / 2.0 * 10.0;
+		let equilateral = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(5.0, height),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&equilateral).unwrap();
+		let report = QualityReport::from_delaunay_2d(&data, 0.1);
+		assert_eq!(1, report.get_elements().len());
+		let element = report.get_elements().values().next().unwrap();
+		assert!((element.get_minimum_angle() - 60.0).abs() < 1e-3);
+		assert!((element.get_aspect_ratio() - 1.0).abs() < 1e-4);
+		assert!((element.get_radius_edge_ratio() - 1.0 / 3.0_f32.sqrt()).abs() < 1e-4);
+		assert!((element.get_size() - 5.0 * height).abs() < 1e-3);
+		let sliver = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(5.0, 0.5),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&sliver).unwrap();
+		let report = QualityReport::from_delaunay_2d(&data, 0.1);
+		assert_eq!(1, report.elements_below_minimum_angle(10.0).len());
+		let element = report.get_elements().values().next().unwrap();
+		assert!((element.get_aspect_ratio() - 10.0 / 0.5 * 3.0_f32.sqrt() / 2.0).abs() < 1e-3);
+		assert!(!report.has_near_duplicates());
+		// points closer than the distance and merged points are both reported
+		let points = [
+			Vec2::new(0.0, 0.0),
+			Vec2::new(10.0, 0.0),
+			Vec2::new(5.0, 10.0),
+			Vec2::new(5.0, 10.05),
+			Vec2::new(0.0, 0.0),
+		];
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let report = QualityReport::from_delaunay_2d(&data, 0.1);
+		assert_eq!(&[(0, 4), (2, 3)], report.get_near_duplicates());
+		// cells of a hexagonal grid are regular hexagons
+		let mut points = vec![];
+		for row in 0..7 {
+			for column in 0..7 {
+				let offset = if row % 2 == 0 { 0.0 } else { 5.0 };
+				points.push(Vec2::new(
+					column as f32 * 10.0 + offset,
+					row as f32 * height,
+				));
+			}
+		}
+		let data = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+		let report = QualityReport::from_delaunay_2d(&data, 0.1);
+		let histogram = report.get_minimum_angle_histogram();
+		assert!((histogram.get_maximum() - 60.0).abs() < 1e-3);
+		assert_eq!(
+			data.get_triangles().len(),
+			histogram.get_bin_counts().iter().sum::<usize>()
+		);
+		let voronoi = Voronoi2d::from_delaunay_2d(&data).unwrap();
+		let report = QualityReport::from_voronoi_2d(&voronoi, &data, 0.1);
+		// cells beside the ragged edge of the grid are cut short
+		let interior = (2..5).flat_map(|row| (2..5).map(move |column| row * 7 + column));
+		for site in interior {
+			let element = report.get_elements()[&site];
+			assert!((element.get_minimum_angle() - 120.0).abs() < 0.1);
+			assert!((element.get_aspect_ratio() - 1.0).abs() < 1e-3);
+			assert!((element.get_radius_edge_ratio() - 1.0).abs() < 1e-3);
+		}
+	}
+	#[test]
+	#[cfg(feature = "3d")]
+	fn quality_3d() {
+		// a regular tetrahedron from alternate corners of a cube
+		let points = [
+			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+			Vec3::new(1.0, 0.0, 1.0),
+			Vec3::new(0.0, 1.0, 1.0),
+		];
+		let data = Delaunay3d::compute_triangulation_3d(&points).unwrap();
+		let report = QualityReport::from_delaunay_3d(&data, 0.1);
+		assert_eq!(1, report.get_elements().len());
+		let element = report.get_elements().values().next().unwrap();
+		let dihedral = (1.0_f32 / 3.0).acos().to_degrees();
+		assert!((element.get_minimum_angle() - dihedral).abs() < 1e-3);
+		assert!((element.get_aspect_ratio() - 1.0).abs() < 1e-4);
+		assert!((element.get_radius_edge_ratio() - 6.0_f32.sqrt() / 4.0).abs() < 1e-4);
+		assert!((element.get_size() - 1.0 / 3.0).abs() < 1e-4);
+		assert_eq!(0, report.get_size_histogram().get_non_finite_count());
+		assert!(!report.has_near_duplicates());
+	}
+}
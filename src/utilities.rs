@@ -1,9 +1,9 @@
 //! Helper functions
 //!
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeMap};
 
-use bevy::prelude::*;
+use bevy::{math::DVec3, prelude::*};
 
 /// Reorder a series of 2d vertices in-place based on their angular position around a point.
 ///
@@ -160,6 +160,594 @@ pub fn is_point_within_edge_range_limt(point: &Vec2, edge_start: &Vec2, edge_end
 			|| (point.y >= edge_end.y && point.y <= edge_start.y))
 }
 
+/// Find the root of the group an ID belongs to, `parents` maps each ID to
+/// another ID in its group and a root maps to itself. The path to the root
+/// is shortened as it goes
+#[cfg(any(feature = "2d", feature = "3d"))]
+pub(crate) fn find_root(parents: &mut BTreeMap<usize, usize>, id: usize) -> usize {
+	let mut root = id;
+	while parents[&root] != root {
+		root = parents[&root];
+	}
+	let mut current = id;
+	while current != root {
+		let next = parents[&current];
+		parents.insert(current, root);
+		current = next;
+	}
+	root
+}
+
+/// From edges given as `(length, vertex a, vertex b)` pick the shortest edges
+/// that join every vertex without forming a loop (Kruskal's algorithm).
+/// Edges of equal length are picked in order of their vertex IDs.
+///
+/// Returns the picked edges in the order they were picked
+#[cfg(any(feature = "2d", feature = "3d"))]
+pub(crate) fn minimum_spanning_edges(mut edges: Vec<(f64, usize, usize)>) -> Vec<(usize, usize)> {
+	edges.sort_by(|x, y| x.0.total_cmp(&y.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+	let mut parents = BTreeMap::new();
+	for (_, a, b) in edges.iter() {
+		parents.insert(*a, *a);
+		parents.insert(*b, *b);
+	}
+	let mut tree = vec![];
+	for (_, a, b) in edges {
+		let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+		if root_a != root_b {
+			parents.insert(root_b, root_a);
+			tree.push((a, b));
+		}
+	}
+	tree
+}
+
+/// Identify points which sit on top of, or within `tolerance` distance of,
+/// an earlier point in the list.
+///
+/// Returns a map where the key is the index of a duplicate point and the
+/// value is the index of the earlier point it should be merged into. A
+/// `tolerance` of zero only merges exact duplicates
+pub fn find_duplicate_points_2d(points: &[Vec2], tolerance: f32) -> BTreeMap<usize, usize> {
+	let mut duplicates = BTreeMap::new();
+	if tolerance > 0.0 {
+		// bucket points into a grid of tolerance sized squares so only
+		// neighbouring squares need searching for points within range
+		let mut grid: BTreeMap<[i64; 2], Vec<usize>> = BTreeMap::new();
+		let tolerance_sq = tolerance * tolerance;
+		for (index, point) in points.iter().enumerate() {
+			// a far point with a tiny tolerance saturates at the edge of the
+			// grid, the distance check still decides which points merge
+			let cell = [
+				(point.x / tolerance).floor() as i64,
+				(point.y / tolerance).floor() as i64,
+			];
+			let mut survivor = None;
+			'search: for dx in -1..=1 {
+				for dy in -1..=1 {
+					if let Some(candidates) =
+						grid.get(&[cell[0].saturating_add(dx), cell[1].saturating_add(dy)])
+					{
+						for candidate in candidates.iter() {
+							if points[*candidate].distance_squared(*point) <= tolerance_sq {
+								survivor = Some(*candidate);
+								break 'search;
+							}
+						}
+					}
+				}
+			}
+			match survivor {
+				Some(s) => {
+					duplicates.insert(index, s);
+				}
+				None => grid.entry(cell).or_default().push(index),
+			}
+		}
+	} else {
+		// adding zero normalises negative zero so that bit patterns match
+		let mut seen: BTreeMap<[u32; 2], usize> = BTreeMap::new();
+		for (index, point) in points.iter().enumerate() {
+			let key = [(point.x + 0.0).to_bits(), (point.y + 0.0).to_bits()];
+			if let Some(s) = seen.get(&key) {
+				duplicates.insert(index, *s);
+			} else {
+				seen.insert(key, index);
+			}
+		}
+	}
+	duplicates
+}
+
+/// Identify points which sit on top of, or within `tolerance` distance of,
+/// an earlier point in the list.
+///
+/// Returns a map where the key is the index of a duplicate point and the
+/// value is the index of the earlier point it should be merged into. A
+/// `tolerance` of zero only merges exact duplicates
+pub fn find_duplicate_points_3d(points: &[Vec3], tolerance: f32) -> BTreeMap<usize, usize> {
+	let mut duplicates = BTreeMap::new();
+	if tolerance > 0.0 {
+		// bucket points into a grid of tolerance sized cubes so only
+		// neighbouring cubes need searching for points within range
+		let mut grid: BTreeMap<[i64; 3], Vec<usize>> = BTreeMap::new();
+		let tolerance_sq = tolerance * tolerance;
+		for (index, point) in points.iter().enumerate() {
+			// a far point with a tiny tolerance saturates at the edge of the
+			// grid, the distance check still decides which points merge
+			let cell = [
+				(point.x / tolerance).floor() as i64,
+				(point.y / tolerance).floor() as i64,
+				(point.z / tolerance).floor() as i64,
+			];
+			let mut survivor = None;
+			'search: for dx in -1..=1 {
+				for dy in -1..=1 {
+					for dz in -1..=1 {
+						if let Some(candidates) = grid.get(&[
+							cell[0].saturating_add(dx),
+							cell[1].saturating_add(dy),
+							cell[2].saturating_add(dz),
+						]) {
+							for candidate in candidates.iter() {
+								if points[*candidate].distance_squared(*point) <= tolerance_sq {
+									survivor = Some(*candidate);
+									break 'search;
+								}
+							}
+						}
+					}
+				}
+			}
+			match survivor {
+				Some(s) => {
+					duplicates.insert(index, s);
+				}
+				None => grid.entry(cell).or_default().push(index),
+			}
+		}
+	} else {
+		// adding zero normalises negative zero so that bit patterns match
+		let mut seen: BTreeMap<[u32; 3], usize> = BTreeMap::new();
+		for (index, point) in points.iter().enumerate() {
+			let key = [
+				(point.x + 0.0).to_bits(),
+				(point.y + 0.0).to_bits(),
+				(point.z + 0.0).to_bits(),
+			];
+			if let Some(s) = seen.get(&key) {
+				duplicates.insert(index, *s);
+			} else {
+				seen.insert(key, index);
+			}
+		}
+	}
+	duplicates
+}
+
+/// Half of the machine epsilon of an `f64`, the largest relative error of a
+/// single rounded operation. Used to bound the error of the fast floating
+/// point path of the geometric predicates
+const PREDICATE_EPSILON: f64 = f64::EPSILON / 2.0;
+
+/// Orientation of three points in a plane.
+///
+/// * Value > 0 means `a`, `b` and `c` run in an anti-clockwise order
+/// * Value == 0 means the points are collinear
+/// * Value < 0 means the points run in a clockwise order
+///
+/// The sign of the result is exact. A fast floating point evaluation is used
+/// when its error bound proves the sign is correct, otherwise the
+/// determinant is evaluated exactly with floating point expansions (after
+/// Shewchuk's adaptive predicates)
+pub fn orient2d(a: Vec2, b: Vec2, c: Vec2) -> f64 {
+	let (a, b, c) = (a.as_dvec2(), b.as_dvec2(), c.as_dvec2());
+	let det_left = (a.x - c.x) * (b.y - c.y);
+	let det_right = (a.y - c.y) * (b.x - c.x);
+	let det = det_left - det_right;
+	// when the two products differ in sign no cancellation can occur so the
+	// sign of the result is already correct
+	let det_sum = if det_left > 0.0 {
+		if det_right <= 0.0 {
+			return det;
+		}
+		det_left + det_right
+	} else if det_left < 0.0 {
+		if det_right >= 0.0 {
+			return det;
+		}
+		-det_left - det_right
+	} else {
+		return det;
+	};
+	let error_bound = (3.0 + 16.0 * PREDICATE_EPSILON) * PREDICATE_EPSILON * det_sum;
+	if det >= error_bound || -det >= error_bound {
+		return det;
+	}
+	// too close to call, compute the determinant exactly
+	let acx = diff_expansion(a.x, c.x);
+	let bcy = diff_expansion(b.y, c.y);
+	let acy = diff_expansion(a.y, c.y);
+	let bcx = diff_expansion(b.x, c.x);
+	let exact = expansion_diff(
+		&expansion_product(&acx, &bcy),
+		&expansion_product(&acy, &bcx),
+	);
+	estimate_expansion(&exact)
+}
+
+/// Position of point `d` relative to the circle passing through `a`, `b` and
+/// `c`, where `a`, `b` and `c` are in an anti-clockwise order.
+///
+/// * Value > 0 means `d` is inside the circle
+/// * Value == 0 means `d` sits on the circle
+/// * Value < 0 means `d` is outside the circle
+///
+/// If `a`, `b` and `c` are in a clockwise order the sign is reversed. As with
+/// [orient2d] the sign of the result is exact
+pub fn incircle(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> f64 {
+	let (a, b, c, d) = (a.as_dvec2(), b.as_dvec2(), c.as_dvec2(), d.as_dvec2());
+	let adx = a.x - d.x;
+	let bdx = b.x - d.x;
+	let cdx = c.x - d.x;
+	let ady = a.y - d.y;
+	let bdy = b.y - d.y;
+	let cdy = c.y - d.y;
+
+	let bdx_cdy = bdx * cdy;
+	let cdx_bdy = cdx * bdy;
+	let a_lift = adx * adx + ady * ady;
+
+	let cdx_ady = cdx * ady;
+	let adx_cdy = adx * cdy;
+	let b_lift = bdx * bdx + bdy * bdy;
+
+	let adx_bdy = adx * bdy;
+	let bdx_ady = bdx * ady;
+	let c_lift = cdx * cdx + cdy * cdy;
+
+	let det =
+		a_lift * (bdx_cdy - cdx_bdy) + b_lift * (cdx_ady - adx_cdy) + c_lift * (adx_bdy - bdx_ady);
+	let permanent = (bdx_cdy.abs() + cdx_bdy.abs()) * a_lift
+		+ (cdx_ady.abs() + adx_cdy.abs()) * b_lift
+		+ (adx_bdy.abs() + bdx_ady.abs()) * c_lift;
+	let error_bound = (10.0 + 96.0 * PREDICATE_EPSILON) * PREDICATE_EPSILON * permanent;
+	if det > error_bound || -det > error_bound {
+		return det;
+	}
+	// too close to call, compute the determinant exactly
+	let adx = diff_expansion(a.x, d.x);
+	let bdx = diff_expansion(b.x, d.x);
+	let cdx = diff_expansion(c.x, d.x);
+	let ady = diff_expansion(a.y, d.y);
+	let bdy = diff_expansion(b.y, d.y);
+	let cdy = diff_expansion(c.y, d.y);
+
+	let a_lift = expansion_sum(
+		&expansion_product(&adx, &adx),
+		&expansion_product(&ady, &ady),
+	);
+	let b_lift = expansion_sum(
+		&expansion_product(&bdx, &bdx),
+		&expansion_product(&bdy, &bdy),
+	);
+	let c_lift = expansion_sum(
+		&expansion_product(&cdx, &cdx),
+		&expansion_product(&cdy, &cdy),
+	);
+
+	let bc = expansion_diff(
+		&expansion_product(&bdx, &cdy),
+		&expansion_product(&cdx, &bdy),
+	);
+	let ca = expansion_diff(
+		&expansion_product(&cdx, &ady),
+		&expansion_product(&adx, &cdy),
+	);
+	let ab = expansion_diff(
+		&expansion_product(&adx, &bdy),
+		&expansion_product(&bdx, &ady),
+	);
+
+	let exact = expansion_sum(
+		&expansion_sum(
+			&expansion_product(&a_lift, &bc),
+			&expansion_product(&b_lift, &ca),
+		),
+		&expansion_product(&c_lift, &ab),
+	);
+	estimate_expansion(&exact)
+}
+
+/// Orientation of point `d` relative to the plane passing through `a`, `b`
+/// and `c`.
+///
+/// * Value > 0 means `d` is below the plane, where "above" is the side from
+///   which `a`, `b` and `c` appear in an anti-clockwise order
+/// * Value == 0 means the points are coplanar
+/// * Value < 0 means `d` is above the plane
+///
+/// Equivalently the value is positive when the tetrahedron `a-b-c-d` is
+/// positively oriented. As with [orient2d] the sign of the result is exact
+pub fn orient3d(a: Vec3, b: Vec3, c: Vec3, d: Vec3) -> f64 {
+	let (a, b, c, d) = (a.as_dvec3(), b.as_dvec3(), c.as_dvec3(), d.as_dvec3());
+	let ad = a - d;
+	let bd = b - d;
+	let cd = c - d;
+
+	let bdy_cdz = bd.y * cd.z;
+	let bdz_cdy = bd.z * cd.y;
+	let cdy_adz = cd.y * ad.z;
+	let cdz_ady = cd.z * ad.y;
+	let ady_bdz = ad.y * bd.z;
+	let adz_bdy = ad.z * bd.y;
+
+	let det = ad.x * (bdy_cdz - bdz_cdy) + bd.x * (cdy_adz - cdz_ady) + cd.x * (ady_bdz - adz_bdy);
+	let permanent = (bdy_cdz.abs() + bdz_cdy.abs()) * ad.x.abs()
+		+ (cdy_adz.abs() + cdz_ady.abs()) * bd.x.abs()
+		+ (ady_bdz.abs() + adz_bdy.abs()) * cd.x.abs();
+	let error_bound = (7.0 + 56.0 * PREDICATE_EPSILON) * PREDICATE_EPSILON * permanent;
+	if det > error_bound || -det > error_bound {
+		return det;
+	}
+	// too close to call, compute the determinant exactly
+	let [adx, ady, adz] = diff_expansion_3d(a, d);
+	let [bdx, bdy, bdz] = diff_expansion_3d(b, d);
+	let [cdx, cdy, cdz] = diff_expansion_3d(c, d);
+
+	let bc = expansion_diff(
+		&expansion_product(&bdy, &cdz),
+		&expansion_product(&bdz, &cdy),
+	);
+	let ca = expansion_diff(
+		&expansion_product(&cdy, &adz),
+		&expansion_product(&cdz, &ady),
+	);
+	let ab = expansion_diff(
+		&expansion_product(&ady, &bdz),
+		&expansion_product(&adz, &bdy),
+	);
+
+	let exact = expansion_sum(
+		&expansion_sum(&expansion_product(&adx, &bc), &expansion_product(&bdx, &ca)),
+		&expansion_product(&cdx, &ab),
+	);
+	estimate_expansion(&exact)
+}
+
+/// Position of point `e` relative to the sphere passing through `a`, `b`,
+/// `c` and `d`, where [orient3d] of `a-b-c-d` is positive.
+///
+/// * Value > 0 means `e` is inside the sphere
+/// * Value == 0 means `e` sits on the sphere
+/// * Value < 0 means `e` is outside the sphere
+///
+/// If the orientation of `a-b-c-d` is negative the sign is reversed. As with
+/// [orient2d] the sign of the result is exact
+pub fn insphere(a: Vec3, b: Vec3, c: Vec3, d: Vec3, e: Vec3) -> f64 {
+	let (a, b, c, d, e) = (
+		a.as_dvec3(),
+		b.as_dvec3(),
+		c.as_dvec3(),
+		d.as_dvec3(),
+		e.as_dvec3(),
+	);
+	let ae = a - e;
+	let be = b - e;
+	let ce = c - e;
+	let de = d - e;
+
+	let aex_bey = ae.x * be.y;
+	let bex_aey = be.x * ae.y;
+	let ab = aex_bey - bex_aey;
+	let bex_cey = be.x * ce.y;
+	let cex_bey = ce.x * be.y;
+	let bc = bex_cey - cex_bey;
+	let cex_dey = ce.x * de.y;
+	let dex_cey = de.x * ce.y;
+	let cd = cex_dey - dex_cey;
+	let dex_aey = de.x * ae.y;
+	let aex_dey = ae.x * de.y;
+	let da = dex_aey - aex_dey;
+	let aex_cey = ae.x * ce.y;
+	let cex_aey = ce.x * ae.y;
+	let ac = aex_cey - cex_aey;
+	let bex_dey = be.x * de.y;
+	let dex_bey = de.x * be.y;
+	let bd = bex_dey - dex_bey;
+
+	let abc = ae.z * bc - be.z * ac + ce.z * ab;
+	let bcd = be.z * cd - ce.z * bd + de.z * bc;
+	let cda = ce.z * da + de.z * ac + ae.z * cd;
+	let dab = de.z * ab + ae.z * bd + be.z * da;
+
+	let a_lift = ae.length_squared();
+	let b_lift = be.length_squared();
+	let c_lift = ce.length_squared();
+	let d_lift = de.length_squared();
+
+	let det = (d_lift * abc - c_lift * dab) + (b_lift * cda - a_lift * bcd);
+
+	let ab_plus = aex_bey.abs() + bex_aey.abs();
+	let bc_plus = bex_cey.abs() + cex_bey.abs();
+	let cd_plus = cex_dey.abs() + dex_cey.abs();
+	let da_plus = dex_aey.abs() + aex_dey.abs();
+	let ac_plus = aex_cey.abs() + cex_aey.abs();
+	let bd_plus = bex_dey.abs() + dex_bey.abs();
+	let (aez, bez, cez, dez) = (ae.z.abs(), be.z.abs(), ce.z.abs(), de.z.abs());
+	let permanent = (cd_plus * bez + bd_plus * cez + bc_plus * dez) * a_lift
+		+ (da_plus * cez + ac_plus * dez + cd_plus * aez) * b_lift
+		+ (ab_plus * dez + bd_plus * aez + da_plus * bez) * c_lift
+		+ (bc_plus * aez + ac_plus * bez + ab_plus * cez) * d_lift;
+	let error_bound = (16.0 + 224.0 * PREDICATE_EPSILON) * PREDICATE_EPSILON * permanent;
+	if det > error_bound || -det > error_bound {
+		return det;
+	}
+	// too close to call, compute the determinant exactly
+	let [aex, aey, aez] = diff_expansion_3d(a, e);
+	let [bex, bey, bez] = diff_expansion_3d(b, e);
+	let [cex, cey, cez] = diff_expansion_3d(c, e);
+	let [dex, dey, dez] = diff_expansion_3d(d, e);
+
+	let cross = |px: &[f64], py: &[f64], qx: &[f64], qy: &[f64]| {
+		expansion_diff(&expansion_product(px, qy), &expansion_product(qx, py))
+	};
+	let ab = cross(&aex, &aey, &bex, &bey);
+	let bc = cross(&bex, &bey, &cex, &cey);
+	let cd = cross(&cex, &cey, &dex, &dey);
+	let da = cross(&dex, &dey, &aex, &aey);
+	let ac = cross(&aex, &aey, &cex, &cey);
+	let bd = cross(&bex, &bey, &dex, &dey);
+
+	let abc = expansion_sum(
+		&expansion_diff(&expansion_product(&aez, &bc), &expansion_product(&bez, &ac)),
+		&expansion_product(&cez, &ab),
+	);
+	let bcd = expansion_sum(
+		&expansion_diff(&expansion_product(&bez, &cd), &expansion_product(&cez, &bd)),
+		&expansion_product(&dez, &bc),
+	);
+	let cda = expansion_sum(
+		&expansion_sum(&expansion_product(&cez, &da), &expansion_product(&dez, &ac)),
+		&expansion_product(&aez, &cd),
+	);
+	let dab = expansion_sum(
+		&expansion_sum(&expansion_product(&dez, &ab), &expansion_product(&aez, &bd)),
+		&expansion_product(&bez, &da),
+	);
+
+	let lift = |x: &[f64], y: &[f64], z: &[f64]| {
+		expansion_sum(
+			&expansion_sum(&expansion_product(x, x), &expansion_product(y, y)),
+			&expansion_product(z, z),
+		)
+	};
+	let a_lift = lift(&aex, &aey, &aez);
+	let b_lift = lift(&bex, &bey, &bez);
+	let c_lift = lift(&cex, &cey, &cez);
+	let d_lift = lift(&dex, &dey, &dez);
+
+	let exact = expansion_sum(
+		&expansion_diff(
+			&expansion_product(&d_lift, &abc),
+			&expansion_product(&c_lift, &dab),
+		),
+		&expansion_diff(
+			&expansion_product(&b_lift, &cda),
+			&expansion_product(&a_lift, &bcd),
+		),
+	);
+	estimate_expansion(&exact)
+}
+
+// The exact evaluation of predicates represents a number as an "expansion",
+// a list of non-overlapping floats ordered by increasing magnitude whose sum
+// is the exact value. Zero components are dropped so an empty list is zero
+
+/// Sum two floats exactly, returning the rounded sum and its rounding error
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+	let sum = a + b;
+	let b_virtual = sum - a;
+	let a_virtual = sum - b_virtual;
+	let error = (a - a_virtual) + (b - b_virtual);
+	(sum, error)
+}
+
+/// Multiply two floats exactly, returning the rounded product and its
+/// rounding error
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+	let product = a * b;
+	// a fused multiply-add computes the error with a single rounding
+	(product, a.mul_add(b, -product))
+}
+
+/// The exact difference `a - b` as an expansion
+fn diff_expansion(a: f64, b: f64) -> Vec<f64> {
+	let (diff, error) = two_sum(a, -b);
+	[error, diff].into_iter().filter(|c| *c != 0.0).collect()
+}
+
+/// The exact difference `a - b` of each axis as expansions
+fn diff_expansion_3d(a: DVec3, b: DVec3) -> [Vec<f64>; 3] {
+	[
+		diff_expansion(a.x, b.x),
+		diff_expansion(a.y, b.y),
+		diff_expansion(a.z, b.z),
+	]
+}
+
+/// Add a single float to an expansion
+fn grow_expansion(e: &[f64], b: f64) -> Vec<f64> {
+	let mut h = Vec::with_capacity(e.len() + 1);
+	let mut q = b;
+	for component in e.iter() {
+		let (sum, error) = two_sum(q, *component);
+		if error != 0.0 {
+			h.push(error);
+		}
+		q = sum;
+	}
+	if q != 0.0 {
+		h.push(q);
+	}
+	h
+}
+
+/// Sum two expansions
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+	f.iter().fold(e.to_vec(), |sum, component| {
+		grow_expansion(&sum, *component)
+	})
+}
+
+/// Subtract expansion `f` from expansion `e`
+fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+	f.iter().fold(e.to_vec(), |sum, component| {
+		grow_expansion(&sum, -component)
+	})
+}
+
+/// Multiply an expansion by a single float
+fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+	let mut h = Vec::with_capacity(e.len() * 2);
+	let Some((first, rest)) = e.split_first() else {
+		return h;
+	};
+	let (mut q, error) = two_product(*first, b);
+	if error != 0.0 {
+		h.push(error);
+	}
+	for component in rest.iter() {
+		let (product, product_error) = two_product(*component, b);
+		let (sum, sum_error) = two_sum(q, product_error);
+		if sum_error != 0.0 {
+			h.push(sum_error);
+		}
+		let (next_q, error) = two_sum(product, sum);
+		if error != 0.0 {
+			h.push(error);
+		}
+		q = next_q;
+	}
+	if q != 0.0 {
+		h.push(q);
+	}
+	h
+}
+
+/// Multiply two expansions
+fn expansion_product(e: &[f64], f: &[f64]) -> Vec<f64> {
+	f.iter().fold(vec![], |product, component| {
+		expansion_sum(&product, &scale_expansion(e, *component))
+	})
+}
+
+/// Approximate the value of an expansion, the sign of the approximation is
+/// always exact
+fn estimate_expansion(e: &[f64]) -> f64 {
+	e.iter().sum()
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -479,6 +1067,56 @@ mod tests {
 		assert!(!is_vertex_within_polygon(&vertex, &polygon_edges));
 	}
 	#[test]
+	fn exact_duplicates_2d() {
+		let points = vec![
+			Vec2::new(1.0, 2.0),
+			Vec2::new(3.0, 4.0),
+			Vec2::new(1.0, 2.0),
+			Vec2::new(0.0, 0.0),
+			Vec2::new(-0.0, 0.0),
+		];
+		let duplicates = find_duplicate_points_2d(&points, 0.0);
+		assert_eq!(BTreeMap::from([(2, 0), (4, 3)]), duplicates);
+	}
+	#[test]
+	fn near_duplicates_2d() {
+		let points = vec![
+			Vec2::new(1.0, 2.0),
+			Vec2::new(1.05, 1.95),
+			Vec2::new(1.2, 2.0),
+			Vec2::new(0.99, 2.0),
+		];
+		let duplicates = find_duplicate_points_2d(&points, 0.1);
+		assert_eq!(BTreeMap::from([(1, 0), (3, 0)]), duplicates);
+	}
+	#[test]
+	fn near_duplicates_3d() {
+		let points = vec![
+			Vec3::new(1.0, 2.0, 3.0),
+			Vec3::new(1.0, 2.0, 3.05),
+			Vec3::new(1.0, 2.0, 3.2),
+			Vec3::new(1.0, 2.0, 3.2),
+		];
+		let duplicates = find_duplicate_points_3d(&points, 0.1);
+		assert_eq!(BTreeMap::from([(1, 0), (3, 2)]), duplicates);
+		let duplicates = find_duplicate_points_3d(&points, 0.0);
+		assert_eq!(BTreeMap::from([(3, 2)]), duplicates);
+	}
+	#[test]
+	fn duplicates_beyond_grid_range() {
+		// cell indices saturate rather than overflow
+		let points = vec![
+			Vec2::new(1e10, -1e10),
+			Vec2::new(2e10, -1e10),
+			Vec2::new(1e10, -1e10),
+		];
+		let duplicates = find_duplicate_points_2d(&points, 1e-30);
+		assert_eq!(BTreeMap::from([(2, 0)]), duplicates);
+		let points: Vec<Vec3> = points.iter().map(|p| p.extend(1e10)).collect();
+		let duplicates = find_duplicate_points_3d(&points, 1e-30);
+		assert_eq!(BTreeMap::from([(2, 0)]), duplicates);
+	}
+	#[test]
 	fn point_in_range() {
 		let point = Vec2::new(3.0, 5.0);
 		let edge_start = Vec2::new(1.0, 3.0);
@@ -500,4 +1138,99 @@ mod tests {
 			&edge_end
 		));
 	}
+	#[test]
+	fn orient2d_signs() {
+		let a = Vec2::new(0.0, 0.0);
+		let b = Vec2::new(10.0, 0.0);
+		let c = Vec2::new(0.0, 10.0);
+		assert!(orient2d(a, b, c) > 0.0);
+		assert!(orient2d(a, c, b) < 0.0);
+		assert_eq!(0.0, orient2d(a, b, Vec2::new(20.0, 0.0)));
+	}
+	#[test]
+	fn orient2d_exact() {
+		// points along y = x across a huge range of magnitudes are exactly
+		// collinear even though the rounded determinant is not zero
+		let a = Vec2::new(1e-30, 1e-30);
+		let b = Vec2::new(3.0, 3.0);
+		let c = Vec2::new(1e30, 1e30);
+		assert_eq!(0.0, orient2d(a, b, c));
+		// nudging a point above the line turns the points anti-clockwise
+		let a = Vec2::new(1e-30, 2e-30);
+		assert!(orient2d(a, b, c) > 0.0);
+		assert!(orient2d(c, b, a) < 0.0);
+	}
+	#[test]
+	fn incircle_signs() {
+		let a = Vec2::new(0.0, 0.0);
+		let b = Vec2::new(1.0, 0.0);
+		let c = Vec2::new(1.0, 1.0);
+		assert!(incircle(a, b, c, Vec2::new(0.5, 0.5)) > 0.0);
+		assert!(incircle(a, b, c, Vec2::new(2.0, 2.0)) < 0.0);
+		// cocircular corner of the square
+		assert_eq!(0.0, incircle(a, b, c, Vec2::new(0.0, 1.0)));
+		// clockwise ordering reverses the sign
+		assert!(incircle(a, c, b, Vec2::new(0.5, 0.5)) < 0.0);
+	}
+	#[test]
+	fn incircle_exact() {
+		// a unit square far from the origin is still cocircular
+		let offset = Vec2::splat(1_000_000.0);
+		let a = offset;
+		let b = offset + Vec2::new(1.0, 0.0);
+		let c = offset + Vec2::new(1.0, 1.0);
+		let d = offset + Vec2::new(0.0, 1.0);
+		assert_eq!(0.0, incircle(a, b, c, d));
+		// the smallest step left of the corner is outside the circle
+		let d = offset + Vec2::new(-0.0625, 1.0);
+		assert!(incircle(a, b, c, d) < 0.0);
+		// and the smallest step right is inside
+		let d = offset + Vec2::new(0.0625, 1.0);
+		assert!(incircle(a, b, c, d) > 0.0);
+	}
+	#[test]
+	fn orient3d_signs() {
+		let a = Vec3::new(0.0, 0.0, 0.0);
+		let b = Vec3::new(1.0, 0.0, 0.0);
+		let c = Vec3::new(0.0, 1.0, 0.0);
+		// a-b-c is anti-clockwise when viewed from +z so -z is below
+		assert!(orient3d(a, b, c, Vec3::new(0.0, 0.0, -1.0)) > 0.0);
+		assert!(orient3d(a, b, c, Vec3::new(0.0, 0.0, 1.0)) < 0.0);
+		assert_eq!(0.0, orient3d(a, b, c, Vec3::new(5.0, 7.0, 0.0)));
+	}
+	#[test]
+	fn orient3d_exact() {
+		// points on the plane x + y + z = 0 spread across large magnitudes
+		let a = Vec3::new(1e20, -1e20, 0.0);
+		let b = Vec3::new(3.0, 0.0, -3.0);
+		let c = Vec3::new(0.0, 1e-20, -1e-20);
+		let d = Vec3::new(-1e20, 0.0, 1e20);
+		assert_eq!(0.0, orient3d(a, b, c, d));
+	}
+	#[test]
+	fn insphere_signs() {
+		let a = Vec3::new(0.0, 0.0, 0.0);
+		let b = Vec3::new(1.0, 0.0, 0.0);
+		let c = Vec3::new(0.0, 1.0, 0.0);
+		let d = Vec3::new(0.0, 0.0, -1.0);
+		assert!(orient3d(a, b, c, d) > 0.0);
+		assert!(insphere(a, b, c, d, Vec3::new(0.2, 0.2, -0.2)) > 0.0);
+		assert!(insphere(a, b, c, d, Vec3::new(5.0, 5.0, 5.0)) < 0.0);
+		// swapping two vertices reverses the sign
+		assert!(insphere(b, a, c, d, Vec3::new(0.2, 0.2, -0.2)) < 0.0);
+	}
+	#[test]
+	fn insphere_exact() {
+		// corners of a unit cube far from the origin are cospherical
+		let offset = Vec3::splat(1_000_000.0);
+		let a = offset;
+		let b = offset + Vec3::X;
+		let c = offset + Vec3::Y;
+		let d = offset + Vec3::Z;
+		assert_eq!(0.0, insphere(a, b, c, d, offset + Vec3::ONE));
+		let nudge = Vec3::new(0.0, 0.0, 0.0625);
+		let sign = orient3d(a, b, c, d).signum();
+		assert!(sign * insphere(a, b, c, d, offset + Vec3::ONE - nudge) > 0.0);
+		assert!(sign * insphere(a, b, c, d, offset + Vec3::ONE + nudge) < 0.0);
+	}
 }
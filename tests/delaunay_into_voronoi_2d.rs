@@ -22,8 +22,18 @@ fn delaunay_into_voronoi_2d() {
 	let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
 	let voronoi = Voronoi2d::from_delaunay_2d(&delaunay).unwrap();
 
-	let expected_cell_count = 3;
+	// every point has a cell, those on the hull are open
+	let expected_cell_count = 9;
 	assert_eq!(expected_cell_count, voronoi.get_cells().len());
+	let expected_closed_count = 1;
+	assert_eq!(
+		expected_closed_count,
+		voronoi
+			.get_cells()
+			.values()
+			.filter(|cell| cell.is_closed())
+			.count()
+	);
 }
 
 #[test]
@@ -44,8 +54,12 @@ fn mesh_count_unclipped() {
 	let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
 	let voronoi = Voronoi2d::from_delaunay_2d(&delaunay).unwrap();
 
-	let expected_mesh_count = 3;
-	assert_eq!(expected_mesh_count, voronoi.as_bevy2d_meshes().len());
+	// only closed cells are meshed
+	let expected_mesh_count = 1;
+	assert_eq!(
+		expected_mesh_count,
+		voronoi.as_bevy2d_meshes().unwrap().len()
+	);
 }
 
 #[test]
@@ -115,6 +129,65 @@ fn mesh_count_clipped() {
 	let expected_mesh_count = 18;
 	assert_eq!(
 		expected_mesh_count,
-		voronoi.as_clipped_bevy2d_meshes(&boundary).len()
+		voronoi.as_clipped_bevy2d_meshes(&boundary).unwrap().len()
 	);
 }
+
+#[test]
+fn mesh_count_bounded() {
+	let points = vec![
+		Vec2::new(-190.0, 90.0),
+		Vec2::new(-145.0, 120.0),
+		Vec2::new(-120.0, -45.0),
+		Vec2::new(-60.0, -120.0),
+		Vec2::new(-20.0, 190.0),
+		Vec2::new(60.0, -10.0),
+		Vec2::new(80.0, -190.0),
+		Vec2::new(100.0, 140.0),
+		Vec2::new(190.0, -60.0),
+	];
+	let delaunay = Delaunay2d::compute_triangulation_2d(&points).unwrap();
+	let boundary = vec![
+		Vec2::new(200.0, -200.0),
+		Vec2::new(200.0, 200.0),
+		Vec2::new(-200.0, 200.0),
+		Vec2::new(-200.0, -200.0),
+	];
+	let voronoi = Voronoi2d::from_delaunay_2d_bounded(&delaunay, &boundary).unwrap();
+
+	// the hull points have closed cells too so every point is meshed
+	let expected_mesh_count = 9;
+	assert_eq!(
+		expected_mesh_count,
+		voronoi.as_bevy2d_meshes().unwrap().len()
+	);
+}
+
+#[test]
+fn cell_ids_match_point_indices() {
+	let points = vec![
+		Vec2::new(-190.0, 90.0),
+		Vec2::new(-145.0, 120.0),
+		Vec2::new(-120.0, -45.0),
+		Vec2::new(-60.0, -120.0),
+		Vec2::new(-20.0, 190.0),
+		Vec2::new(60.0, -10.0),
+		Vec2::new(80.0, -190.0),
+		Vec2::new(100.0, 140.0),
+		Vec2::new(190.0, -60.0),
+		// duplicate of index 5
+		Vec2::new(60.0, -10.0),
+	];
+	let mosaic = Mosaic2d::new(&points).unwrap();
+	let delaunay_vertices = mosaic.get_delaunay().get_vertex_lookup();
+	for (id, cell) in mosaic.get_voronoi().get_cells().iter() {
+		assert_eq!(id, cell.get_generating_point());
+		assert_eq!(points[*id], delaunay_vertices[id]);
+	}
+	// the duplicate resolves to the same cell as the point it was merged into
+	let cell = mosaic.get_cell_for_point(9).unwrap();
+	assert_eq!(&5, cell.get_generating_point());
+	// hull points have an open cell
+	assert!(mosaic.get_cell_for_point(0).unwrap().is_open());
+	assert!(mosaic.get_cell_for_point(5).unwrap().is_closed());
+}